@@ -0,0 +1,72 @@
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{ErrorKind, ParseError, Reader, Value};
+
+/// `from_reader` for a tokio stream: read all of `r`, then parse it. The
+/// input must be UTF-8. Nothing is parsed before the stream ends.
+pub async fn parse_async<R: AsyncRead + Unpin>(mut r: R) -> Result<Value, ParseError> {
+    let mut s = String::new();
+    if let Err(e) = r.read_to_string(&mut s).await {
+        return Err(ParseError {
+            kind: ErrorKind::Io,
+            row: 0,
+            column: 0,
+            desc: e.to_string(),
+        });
+    }
+    let mut reader = Reader::new(&s);
+    reader.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    use super::*;
+    use crate::from_reader;
+
+    struct NoopWake;
+
+    impl Wake for NoopWake {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    // Poll `f` until done; in-memory readers are always ready, so this
+    // needs no runtime.
+    fn block_on<F: Future>(f: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWake));
+        let mut cx = Context::from_waker(&waker);
+        let mut f = Box::pin(f);
+        loop {
+            if let Poll::Ready(x) = f.as_mut().poll(&mut cx) {
+                return x;
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_async() {
+        block_on(check_parse_async());
+    }
+
+    async fn check_parse_async() {
+        let src = "{\"name\": \"zxh\", \"ports\": [80, 443]}";
+        let v = parse_async(src.as_bytes()).await.unwrap();
+        assert_eq!(from_reader(src.as_bytes()).unwrap(), v);
+
+        // arriving in pieces, one split inside a character
+        let src = "[\"é\", 1]".as_bytes();
+        let r = src[..3].chain(&src[3..]);
+        assert_eq!(
+            Reader::new("[\"é\", 1]").parse().unwrap(),
+            parse_async(r).await.unwrap()
+        );
+
+        let e = parse_async("[1,".as_bytes()).await.unwrap_err();
+        assert_eq!(from_reader("[1,".as_bytes()).unwrap_err(), e);
+        let e = parse_async(&[b'"', 0xff, b'"'][..]).await.unwrap_err();
+        assert_eq!(ErrorKind::Io, e.kind);
+    }
+}
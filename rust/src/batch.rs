@@ -0,0 +1,172 @@
+use std::thread;
+
+use crate::{ParseError, ParseOptions, Reader, Value};
+
+// Fewer documents than this per thread are parsed on the calling thread,
+// spawning would cost more than it saves.
+const MIN_PER_THREAD: usize = 64;
+
+/// Parse each of `inputs` with default options, spread over the available
+/// cores. Results are in the order of `inputs`.
+pub fn parse_batch(inputs: &[&str]) -> Vec<Result<Value, ParseError>> {
+    parse_batch_with(inputs, &ParseOptions::default())
+}
+
+/// `parse_batch` with `opts` for every document. A progress callback is
+/// not called, it can't be shared between threads.
+pub fn parse_batch_with(inputs: &[&str], opts: &ParseOptions) -> Vec<Result<Value, ParseError>> {
+    let make = opts.settings();
+    let parse_all = |chunk: &[&str]| -> Vec<Result<Value, ParseError>> {
+        chunk
+            .iter()
+            .map(|s| Reader::with_options(s, make()).parse())
+            .collect()
+    };
+
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let threads = cores.min(inputs.len() / MIN_PER_THREAD);
+    if threads <= 1 {
+        return parse_all(inputs);
+    }
+
+    let chunk = inputs.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .chunks(chunk)
+            .map(|c| scope.spawn(move || parse_all(c)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("parser thread panicked"))
+            .collect()
+    })
+}
+
+impl ParseOptions {
+    // Makes copies of these options without the progress callback, from any
+    // thread.
+    fn settings(&self) -> impl Fn() -> ParseOptions + Send + Sync {
+        let ParseOptions {
+            allow_nan,
+            on_number_overflow,
+            document_mode,
+            on_duplicate_key,
+            allow_comments,
+            allow_unquoted_keys,
+            allow_single_quotes,
+            allow_hex_numbers,
+            allow_plus_sign,
+            allow_extra_whitespace,
+            require_container_root,
+            reject_empty_keys,
+            numbers_as_strings,
+            max_depth,
+            max_string_length,
+            max_elements,
+            column_unit,
+            max_bytes,
+            deadline,
+            progress: _,
+            ref key_validator,
+        } = *self;
+        let key_validator = key_validator.clone();
+        move || ParseOptions {
+            allow_nan,
+            on_number_overflow,
+            document_mode,
+            on_duplicate_key,
+            allow_comments,
+            allow_unquoted_keys,
+            allow_single_quotes,
+            allow_hex_numbers,
+            allow_plus_sign,
+            allow_extra_whitespace,
+            require_container_root,
+            reject_empty_keys,
+            numbers_as_strings,
+            max_depth,
+            max_string_length,
+            max_elements,
+            column_unit,
+            max_bytes,
+            deadline,
+            progress: None,
+            key_validator: key_validator.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Small documents of every kind, some of them broken.
+    fn corpus(n: usize) -> Vec<String> {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        (0..n)
+            .map(|i| match next() % 6 {
+                0 => format!("{}", next() % 100_000),
+                1 => format!("[{}, \"s{}\", null]", i, next() % 10),
+                2 => format!("{{\"id\": {}, \"ok\": {}}}", i, next() % 2 == 0),
+                3 => format!("[{}, ", i),
+                4 => format!("{{\"a\": [{{}}, [], {}e{}]}}", i, next() % 400),
+                _ => format!("\"{}\\u00e9\"", i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let docs = corpus(10_000);
+        let inputs: Vec<&str> = docs.iter().map(String::as_str).collect();
+        let sequential: Vec<_> = inputs.iter().map(|s| Reader::new(s).parse()).collect();
+        let batch = parse_batch(&inputs);
+        assert_eq!(sequential, batch);
+        assert!(batch.iter().any(Result::is_err));
+        assert!(batch.iter().any(Result::is_ok));
+    }
+
+    #[test]
+    fn test_parse_batch_order() {
+        let docs: Vec<String> = (0..1000).map(|i| format!("[{}]", i)).collect();
+        let mut inputs: Vec<&str> = docs.iter().map(String::as_str).collect();
+        inputs[500] = "[500";
+        let batch = parse_batch(&inputs);
+        assert_eq!(1000, batch.len());
+        for (i, x) in batch.iter().enumerate() {
+            match x {
+                Ok(v) => assert_eq!(Some(&Value::Number(i as f64)), v.pointer("/0")),
+                Err(e) => {
+                    assert_eq!(500, i);
+                    assert_eq!("array end char expect ']'", e.desc);
+                }
+            }
+        }
+
+        assert!(parse_batch(&[]).is_empty());
+        assert_eq!(vec![Ok(Value::Null)], parse_batch(&["null"]));
+    }
+
+    #[test]
+    fn test_parse_batch_with() {
+        let inputs = vec!["[[1]]"; 300];
+        let opts = ParseOptions::new().max_depth(1);
+        let batch = parse_batch_with(&inputs, &opts);
+        assert!(batch
+            .iter()
+            .all(|x| x.as_ref().unwrap_err().desc == "nesting deeper than 1"));
+
+        let inputs = vec!["{\"a\": 1}"; 300];
+        let opts = ParseOptions::new().key_validator(Box::new(|k: &str| k != "a"));
+        let batch = parse_batch_with(&inputs, &opts);
+        assert!(batch
+            .iter()
+            .all(|x| x.as_ref().unwrap_err().desc == "invalid key 'a'"));
+    }
+}
@@ -2,4 +2,3 @@ fn main() {
     let mut r = jsonrs::Reader::new("{}");
     println!("{:?}", r.parse());
 }
-
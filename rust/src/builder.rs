@@ -0,0 +1,354 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{Map, Value};
+
+/// Fluent construction of a `Value::Object`:
+///
+/// ```
+/// use jsonrs::{ArrayBuilder, ObjectBuilder, Value};
+///
+/// let v = ObjectBuilder::new()
+///     .field("name", Value::String("zxh".to_string()))
+///     .field("ports", ArrayBuilder::new().item(Value::Number(80.0)).build())
+///     .build();
+/// assert_eq!(Some(&Value::Number(80.0)), v.pointer("/ports/0"));
+/// ```
+#[must_use]
+#[derive(Debug, Clone, Default)]
+pub struct ObjectBuilder {
+    members: Map,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> ObjectBuilder {
+        ObjectBuilder::default()
+    }
+
+    /// Set member `key`, a repeated key replaces the earlier value.
+    pub fn field<K: Into<String>>(mut self, key: K, v: Value) -> ObjectBuilder {
+        self.members.insert(key.into(), v);
+        self
+    }
+
+    pub fn build(self) -> Value {
+        Value::Object(self.members)
+    }
+}
+
+/// Fluent construction of a `Value::Array`, see `ObjectBuilder`.
+#[must_use]
+#[derive(Debug, Clone, Default)]
+pub struct ArrayBuilder {
+    elements: Vec<Value>,
+}
+
+impl ArrayBuilder {
+    pub fn new() -> ArrayBuilder {
+        ArrayBuilder::default()
+    }
+
+    pub fn item(mut self, v: Value) -> ArrayBuilder {
+        self.elements.push(v);
+        self
+    }
+
+    pub fn build(self) -> Value {
+        Value::Array(self.elements)
+    }
+}
+
+/// A call to `TreeBuilder` out of turn, like `end` with nothing open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildError {
+    /// Containers open when the call was made.
+    pub depth: usize,
+    pub desc: String,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "build {} at depth {}", self.desc, self.depth)
+    }
+}
+
+impl Error for BuildError {}
+
+/// Builds one `Value` from a depth-first sequence of calls, the way the
+/// parser does, for documents too large or deep for `ObjectBuilder`:
+///
+/// ```
+/// use jsonrs::{TreeBuilder, Value};
+///
+/// let mut b = TreeBuilder::new();
+/// b.begin_object(1).unwrap();
+/// b.key("ports").unwrap();
+/// b.begin_array(2).unwrap();
+/// b.value(Value::Number(80.0)).unwrap();
+/// b.value(Value::Number(443.0)).unwrap();
+/// b.end().unwrap();
+/// b.end().unwrap();
+/// assert_eq!(Some(&Value::Number(443.0)), b.finish().unwrap().pointer("/ports/1"));
+/// ```
+///
+/// `begin_array` and `begin_object` take the expected number of elements or
+/// members, reserved up front; 0 when not known. A repeated key replaces the
+/// earlier member.
+#[must_use]
+#[derive(Debug, Default)]
+pub struct TreeBuilder {
+    stack: Vec<Frame>,
+    root: Option<Value>,
+}
+
+#[derive(Debug)]
+enum Frame {
+    Array(Vec<Value>),
+    // the key of the member to come
+    Object(Map, Option<String>),
+}
+
+impl TreeBuilder {
+    pub fn new() -> TreeBuilder {
+        TreeBuilder::default()
+    }
+
+    pub fn begin_array(&mut self, capacity: usize) -> Result<(), BuildError> {
+        self.expect_value()?;
+        self.stack.push(Frame::Array(Vec::with_capacity(capacity)));
+        Ok(())
+    }
+
+    pub fn begin_object(&mut self, capacity: usize) -> Result<(), BuildError> {
+        self.expect_value()?;
+        self.stack
+            .push(Frame::Object(Map::with_capacity(capacity), None));
+        Ok(())
+    }
+
+    /// Name the next member of the object being built.
+    pub fn key<K: Into<String>>(&mut self, k: K) -> Result<(), BuildError> {
+        match self.stack.last_mut() {
+            Some(Frame::Object(_, key @ None)) => {
+                *key = Some(k.into());
+                Ok(())
+            }
+            Some(Frame::Object(..)) => self.error("key after key, expect a value"),
+            _ => self.error("key outside an object"),
+        }
+    }
+
+    /// Add a scalar, or a value built some other way, as the next element,
+    /// member or the root.
+    pub fn value(&mut self, v: Value) -> Result<(), BuildError> {
+        self.expect_value()?;
+        self.add(v);
+        Ok(())
+    }
+
+    /// Close the array or object begun last.
+    pub fn end(&mut self) -> Result<(), BuildError> {
+        let v = match self.stack.pop() {
+            Some(Frame::Array(arr)) => Value::Array(arr),
+            Some(Frame::Object(m, None)) => Value::Object(m),
+            Some(frame @ Frame::Object(..)) => {
+                self.stack.push(frame);
+                return self.error("end after key, expect a value");
+            }
+            None => return self.error("end without begin"),
+        };
+        self.add(v);
+        Ok(())
+    }
+
+    /// The value built, once every container is closed.
+    pub fn finish(self) -> Result<Value, BuildError> {
+        match self.root {
+            Some(v) if self.stack.is_empty() => Ok(v),
+            _ if !self.stack.is_empty() => self.error("finish with containers open"),
+            _ => self.error("finish without a value"),
+        }
+    }
+
+    // Whether a value may come next.
+    fn expect_value(&self) -> Result<(), BuildError> {
+        match self.stack.last() {
+            Some(Frame::Object(_, None)) => self.error("value without key"),
+            Some(_) => Ok(()),
+            None if self.root.is_some() => self.error("second root value"),
+            None => Ok(()),
+        }
+    }
+
+    fn add(&mut self, v: Value) {
+        match self.stack.last_mut() {
+            Some(Frame::Array(arr)) => arr.push(v),
+            Some(Frame::Object(m, k)) => {
+                m.insert(k.take().expect("checked by expect_value"), v);
+            }
+            None => self.root = Some(v),
+        }
+    }
+
+    fn error<T>(&self, desc: &str) -> Result<T, BuildError> {
+        Err(BuildError {
+            depth: self.stack.len(),
+            desc: desc.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    fn num(f: f64) -> Value {
+        Value::Number(f)
+    }
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn test_build_nested() {
+        let v = ObjectBuilder::new()
+            .field("name", string("zxh"))
+            .field("debug", Value::Bool(false))
+            .field("timeout", Value::Null)
+            .field(
+                "servers",
+                ArrayBuilder::new()
+                    .item(
+                        ObjectBuilder::new()
+                            .field("host", string("a"))
+                            .field("port", num(80.0))
+                            .build(),
+                    )
+                    .item(
+                        ObjectBuilder::new()
+                            .field("host", string("b"))
+                            .field("port", num(8080.0))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .field("tags", ArrayBuilder::new().build())
+            .field("empty", ObjectBuilder::new().build())
+            .build();
+
+        let src = "{\"name\":\"zxh\",\"debug\":false,\"timeout\":null,\
+                   \"servers\":[{\"host\":\"a\",\"port\":80},{\"host\":\"b\",\"port\":8080}],\
+                   \"tags\":[],\"empty\":{}}";
+        assert_eq!(Reader::new(src).parse().unwrap(), v);
+        assert_eq!(v, Reader::new(&v.to_string()).parse().unwrap());
+    }
+
+    #[test]
+    fn test_build_replace() {
+        let v = ObjectBuilder::new()
+            .field("a", num(1.0))
+            .field(String::from("a"), num(2.0))
+            .build();
+        assert_eq!(Reader::new("{\"a\":2}").parse().unwrap(), v);
+        assert_eq!(Value::Array(vec![]), ArrayBuilder::new().build());
+    }
+
+    #[test]
+    fn test_tree_builder_large() {
+        // 11 nodes a record
+        let n = 100_000;
+        let mut b = TreeBuilder::new();
+        let mut src = String::from("[");
+        b.begin_array(n).unwrap();
+        for i in 0..n {
+            let f = i as f64;
+            b.begin_object(4).unwrap();
+            b.key("id").unwrap();
+            b.value(num(f)).unwrap();
+            b.key("name").unwrap();
+            b.value(string(&format!("n{}", i))).unwrap();
+            b.key("tags").unwrap();
+            b.begin_array(4).unwrap();
+            for v in [string("a"), num(f), Value::Null, Value::Bool(true)] {
+                b.value(v).unwrap();
+            }
+            b.end().unwrap();
+            b.key("pos").unwrap();
+            b.begin_object(2).unwrap();
+            b.key("x").unwrap();
+            b.value(num(f)).unwrap();
+            b.key("y").unwrap();
+            b.value(num(-f)).unwrap();
+            b.end().unwrap();
+            b.end().unwrap();
+
+            if i > 0 {
+                src.push(',');
+            }
+            src.push_str(&format!(
+                "{{\"id\":{0},\"name\":\"n{0}\",\"tags\":[\"a\",{0},null,true],\
+                 \"pos\":{{\"x\":{0},\"y\":-{0}}}}}",
+                i
+            ));
+        }
+        b.end().unwrap();
+        src.push(']');
+
+        let v = b.finish().unwrap();
+        assert_eq!(n, v.as_array().unwrap().len());
+        assert_eq!(Reader::new(&src).parse().unwrap(), v);
+    }
+
+    #[test]
+    fn test_tree_builder_deep() {
+        // far deeper than a recursive drop survives
+        let mut b = TreeBuilder::new();
+        for _ in 0..1_000_000 {
+            b.begin_array(1).unwrap();
+        }
+        b.begin_object(0).unwrap();
+        b.end().unwrap();
+        for _ in 0..1_000_000 {
+            b.end().unwrap();
+        }
+        drop(b.finish().unwrap());
+    }
+
+    #[test]
+    fn test_tree_builder_errors() {
+        let mut b = TreeBuilder::new();
+        assert_eq!("end without begin", b.end().unwrap_err().desc);
+        assert_eq!("key outside an object", b.key("a").unwrap_err().desc);
+        b.begin_object(0).unwrap();
+        let e = b.value(Value::Null).unwrap_err();
+        assert_eq!((1, "value without key"), (e.depth, e.desc.as_str()));
+        assert_eq!("value without key", b.begin_array(0).unwrap_err().desc);
+        b.key("a").unwrap();
+        assert_eq!(
+            "key after key, expect a value",
+            b.key("b").unwrap_err().desc
+        );
+        assert_eq!("end after key, expect a value", b.end().unwrap_err().desc);
+        b.value(num(1.0)).unwrap();
+        b.end().unwrap();
+        assert_eq!("second root value", b.value(Value::Null).unwrap_err().desc);
+        assert_eq!(
+            Reader::new("{\"a\":1}").parse().unwrap(),
+            b.finish().unwrap()
+        );
+
+        let mut b = TreeBuilder::new();
+        b.begin_array(0).unwrap();
+        let e = b.finish().unwrap_err();
+        assert_eq!(
+            "build finish with containers open at depth 1",
+            e.to_string()
+        );
+        assert_eq!(
+            "finish without a value",
+            TreeBuilder::new().finish().unwrap_err().desc
+        );
+    }
+}
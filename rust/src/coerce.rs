@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use crate::{Reader, Value};
+
+/// Loose, JavaScript-like conversions for template-style consumers. Unlike
+/// the `as_*` accessors these never fail on a type mismatch alone:
+///
+/// | value           | `is_truthy`     | `coerce_str`                 | `coerce_f64`         |
+/// |-----------------|-----------------|------------------------------|----------------------|
+/// | `null`          | false           | `"null"`                     | `None`               |
+/// | `false`, `true` | the boolean     | `"false"`, `"true"`          | `0.0`, `1.0`         |
+/// | number          | not 0 or NaN    | as serialized, `1.5`, `1e+21`| the number           |
+/// | string          | not empty       | the string, borrowed         | a JSON number after trimming whitespace, else `None` |
+/// | array, object   | not empty       | compact JSON if `encode_containers`, else `None` | `None` |
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(f) => *f != 0.0 && !f.is_nan(),
+            Value::String(s) => !s.is_empty(),
+            Value::Array(v) => !v.is_empty(),
+            Value::Object(m) => !m.is_empty(),
+        }
+    }
+
+    pub fn coerce_str(&self, encode_containers: bool) -> Option<Cow<'_, str>> {
+        match self {
+            Value::String(s) => Some(Cow::Borrowed(s)),
+            Value::Array(_) | Value::Object(_) if !encode_containers => None,
+            v => Some(Cow::Owned(v.to_string())),
+        }
+    }
+
+    pub fn coerce_f64(&self) -> Option<f64> {
+        match self {
+            Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            Value::Number(f) => Some(*f),
+            Value::String(s) => match Reader::new(s.trim()).parse() {
+                Ok(Value::Number(f)) => Some(f),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Value {
+        Reader::new(s).parse().unwrap()
+    }
+
+    #[test]
+    fn test_is_truthy() {
+        let falsy = ["null", "false", "0", "-0", "\"\"", "[]", "{}"];
+        for src in falsy.iter() {
+            assert!(!parse(src).is_truthy(), "{}", src);
+        }
+        assert!(!Value::Number(f64::NAN).is_truthy());
+        let truthy = ["true", "1", "-0.5", "\"0\"", "\" \"", "[0]", "{\"a\":null}"];
+        for src in truthy.iter() {
+            assert!(parse(src).is_truthy(), "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_coerce_str() {
+        let cases = [
+            ("null", "null"),
+            ("false", "false"),
+            ("true", "true"),
+            ("1.50", "1.5"),
+            ("1e21", "1e+21"),
+            ("\"a\\tb\"", "a\tb"),
+        ];
+        for (src, expect) in cases.iter() {
+            assert_eq!(Some(*expect), parse(src).coerce_str(false).as_deref());
+        }
+        let s = parse("\"x\"");
+        assert!(matches!(s.coerce_str(false), Some(Cow::Borrowed("x"))));
+
+        assert_eq!(None, parse("[1, \"a\"]").coerce_str(false));
+        let arr = parse("[1, \"a\"]");
+        assert_eq!(Some("[1,\"a\"]"), arr.coerce_str(true).as_deref());
+        assert_eq!(None, parse("{}").coerce_str(false));
+        assert_eq!(Some("{}"), parse("{}").coerce_str(true).as_deref());
+    }
+
+    #[test]
+    fn test_coerce_f64() {
+        let cases = [
+            ("null", None),
+            ("false", Some(0.0)),
+            ("true", Some(1.0)),
+            ("-2.5", Some(-2.5)),
+            ("\" 42 \"", Some(42.0)),
+            ("\"1e3\"", Some(1000.0)),
+            ("\"\"", None),
+            ("\"0x10\"", None),
+            ("\"NaN\"", None),
+            ("\"12px\"", None),
+            ("[1]", None),
+            ("{}", None),
+        ];
+        for (src, expect) in cases.iter() {
+            assert_eq!(*expect, parse(src).coerce_f64(), "{}", src);
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::{from_reader, ParseError, Reader, Value};
+
+/// A value could not be converted, `path` is the JSON Pointer of the
+/// offending node relative to the converted root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    pub path: String,
+    pub desc: String,
+}
+
+impl ConvertError {
+    pub fn new(desc: String) -> ConvertError {
+        ConvertError {
+            path: String::new(),
+            desc,
+        }
+    }
+
+    fn expected(what: &str, v: &Value) -> ConvertError {
+        ConvertError::new(format!("expect {}, found {}", what, type_name_ref(v)))
+    }
+
+    // Prefix the path with one more segment, used on the way out of nested
+    // conversions.
+    fn within(mut self, segment: &str) -> ConvertError {
+        let escaped = segment.replace('~', "~0").replace('/', "~1");
+        self.path = format!("/{}{}", escaped, self.path);
+        self
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "convert {} at '{}'", self.desc, self.path)
+    }
+}
+
+impl Error for ConvertError {}
+
+/// Conversion from a parsed `Value`, implemented by hand for user types:
+///
+/// ```
+/// use jsonrs::{field, ConvertError, FromValue, Value};
+///
+/// struct Server {
+///     host: String,
+///     port: u64,
+/// }
+///
+/// impl FromValue for Server {
+///     fn from_value(v: &Value) -> Result<Server, ConvertError> {
+///         Ok(Server {
+///             host: field(v, "host")?,
+///             port: field(v, "port")?,
+///         })
+///     }
+/// }
+///
+/// let s: Server = jsonrs::parse_typed("{\"host\":\"a\",\"port\":80}").unwrap();
+/// assert_eq!(80, s.port);
+/// ```
+pub trait FromValue: Sized {
+    fn from_value(v: &Value) -> Result<Self, ConvertError>;
+}
+
+/// Convert the member `key` of object `v`, errors carry the member's path.
+/// A missing member converts from `null`, so `Option` fields may be absent.
+pub fn field<T: FromValue>(v: &Value, key: &str) -> Result<T, ConvertError> {
+    let members = match v {
+        Value::Object(v) => v,
+        _ => return Err(ConvertError::expected("object", v)),
+    };
+    match members.get(key) {
+        Some(x) => T::from_value(x).map_err(|e| e.within(key)),
+        None => T::from_value(&Value::Null)
+            .map_err(|_| ConvertError::new(String::from("missing member")).within(key)),
+    }
+}
+
+pub(crate) fn type_name_ref(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(..) => "number",
+        Value::String(..) => "string",
+        Value::Array(..) => "array",
+        Value::Object(..) => "object",
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(v: &Value) -> Result<Value, ConvertError> {
+        Ok(v.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(v: &Value) -> Result<bool, ConvertError> {
+        match v {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(ConvertError::expected("boolean", v)),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(v: &Value) -> Result<f64, ConvertError> {
+        match v {
+            Value::Number(v) => Ok(*v),
+            _ => Err(ConvertError::expected("number", v)),
+        }
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(v: &Value) -> Result<i64, ConvertError> {
+        let f = f64::from_value(v)?;
+        v.as_i64()
+            .ok_or_else(|| ConvertError::new(format!("{} is not an i64", f)))
+    }
+}
+
+impl FromValue for u64 {
+    fn from_value(v: &Value) -> Result<u64, ConvertError> {
+        let f = f64::from_value(v)?;
+        v.as_u64()
+            .ok_or_else(|| ConvertError::new(format!("{} is not an u64", f)))
+    }
+}
+
+impl FromValue for String {
+    fn from_value(v: &Value) -> Result<String, ConvertError> {
+        match v {
+            Value::String(v) => Ok(v.clone()),
+            _ => Err(ConvertError::expected("string", v)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(v: &Value) -> Result<Option<T>, ConvertError> {
+        match v {
+            Value::Null => Ok(None),
+            _ => T::from_value(v).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(v: &Value) -> Result<Vec<T>, ConvertError> {
+        match v {
+            Value::Array(v) => v
+                .iter()
+                .enumerate()
+                .map(|(i, x)| T::from_value(x).map_err(|e| e.within(&i.to_string())))
+                .collect(),
+            _ => Err(ConvertError::expected("array", v)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(v: &Value) -> Result<HashMap<String, T>, ConvertError> {
+        match v {
+            Value::Object(v) => v
+                .entries()
+                .map(|(k, x)| match T::from_value(x) {
+                    Ok(t) => Ok((k.to_string(), t)),
+                    Err(e) => Err(e.within(k)),
+                })
+                .collect(),
+            _ => Err(ConvertError::expected("object", v)),
+        }
+    }
+}
+
+// `TryFrom<Value>` for arrays and objects of `$t`, moving the elements out
+// with `$take` where `FromValue` would clone them.
+macro_rules! try_from_value {
+    ($t:ty, $take:expr) => {
+        impl TryFrom<Value> for Vec<$t> {
+            type Error = ConvertError;
+
+            fn try_from(v: Value) -> Result<Vec<$t>, ConvertError> {
+                let v = v
+                    .into_array()
+                    .map_err(|v| ConvertError::expected("array", &v))?;
+                v.into_iter()
+                    .enumerate()
+                    .map(|(i, x)| $take(x).map_err(|e| e.within(&i.to_string())))
+                    .collect()
+            }
+        }
+
+        impl TryFrom<Value> for BTreeMap<String, $t> {
+            type Error = ConvertError;
+
+            fn try_from(v: Value) -> Result<BTreeMap<String, $t>, ConvertError> {
+                take_members(v, $take)?.collect()
+            }
+        }
+
+        impl TryFrom<Value> for HashMap<String, $t> {
+            type Error = ConvertError;
+
+            fn try_from(v: Value) -> Result<HashMap<String, $t>, ConvertError> {
+                take_members(v, $take)?.collect()
+            }
+        }
+    };
+}
+
+try_from_value!(Value, Ok::<Value, ConvertError>);
+try_from_value!(String, take_string);
+
+fn take_string(v: Value) -> Result<String, ConvertError> {
+    v.into_string()
+        .map_err(|v| ConvertError::expected("string", &v))
+}
+
+// The members of object `v` converted by `take`, in `entries` order so a
+// later duplicate wins when collected, as in `Map::get`.
+fn take_members<T, F>(
+    v: Value,
+    take: F,
+) -> Result<impl Iterator<Item = Result<(String, T), ConvertError>>, ConvertError>
+where
+    F: Fn(Value) -> Result<T, ConvertError>,
+{
+    let members = v
+        .into_object()
+        .map_err(|v| ConvertError::expected("object", &v))?;
+    Ok(members.into_iter().map(move |(k, x)| match take(x) {
+        Ok(t) => Ok((k, t)),
+        Err(e) => Err(e.within(&k)),
+    }))
+}
+
+/// Failure of `parse_typed`: either the text is not JSON (with its
+/// position) or the value does not fit `T` (with its path).
+#[derive(Debug)]
+pub enum TypedParseError {
+    Parse(ParseError),
+    Convert(ConvertError),
+}
+
+impl fmt::Display for TypedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypedParseError::Parse(e) => e.fmt(f),
+            TypedParseError::Convert(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for TypedParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TypedParseError::Parse(e) => Some(e),
+            TypedParseError::Convert(e) => Some(e),
+        }
+    }
+}
+
+impl From<ParseError> for TypedParseError {
+    fn from(e: ParseError) -> TypedParseError {
+        TypedParseError::Parse(e)
+    }
+}
+
+impl From<ConvertError> for TypedParseError {
+    fn from(e: ConvertError) -> TypedParseError {
+        TypedParseError::Convert(e)
+    }
+}
+
+/// Parse `s` and convert it to `T` in one step.
+pub fn parse_typed<T: FromValue>(s: &str) -> Result<T, TypedParseError> {
+    let v = Reader::new(s).parse()?;
+    Ok(T::from_value(&v)?)
+}
+
+/// `parse_typed` for a reader, see `from_reader`.
+pub fn from_reader_typed<T: FromValue, R: io::Read>(r: R) -> Result<T, TypedParseError> {
+    let v = from_reader(r)?;
+    Ok(T::from_value(&v)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[derive(Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u64,
+    }
+
+    impl FromValue for Server {
+        fn from_value(v: &Value) -> Result<Server, ConvertError> {
+            Ok(Server {
+                host: field(v, "host")?,
+                port: field(v, "port")?,
+            })
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        name: String,
+        debug: bool,
+        timeout: Option<f64>,
+        servers: Vec<Server>,
+    }
+
+    impl FromValue for Config {
+        fn from_value(v: &Value) -> Result<Config, ConvertError> {
+            Ok(Config {
+                name: field(v, "name")?,
+                debug: field(v, "debug")?,
+                timeout: field(v, "timeout")?,
+                servers: field(v, "servers")?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_parse_typed() {
+        let src = "{\"name\":\"zxh\",\"debug\":false,\"servers\":[{\"host\":\"a\",\"port\":80}]}";
+        let c: Config = parse_typed(src).unwrap();
+        assert_eq!(
+            c,
+            Config {
+                name: String::from("zxh"),
+                debug: false,
+                timeout: None,
+                servers: vec![Server {
+                    host: String::from("a"),
+                    port: 80
+                }],
+            }
+        );
+
+        let c1: Config = from_reader_typed(src.as_bytes()).unwrap();
+        assert_eq!(c, c1);
+
+        let v: Vec<Option<i64>> = parse_typed("[1, null, -3]").unwrap();
+        assert_eq!(vec![Some(1), None, Some(-3)], v);
+    }
+
+    #[test]
+    fn test_parse_typed_error() {
+        let x = parse_typed::<Config>("{\"name\":\"zxh\",\n\"debug\":tru}");
+        match x.unwrap_err() {
+            TypedParseError::Parse(e) => {
+                assert_eq!(ErrorKind::Syntax, e.kind);
+                assert_eq!((2, 9), (e.row, e.column));
+            }
+            e => panic!("{}", e),
+        }
+
+        let src = "{\"name\":\"zxh\",\"debug\":true,\"servers\":[{\"host\":\"a\",\"port\":80},{\"host\":\"b\",\"port\":\"x\"}]}";
+        match parse_typed::<Config>(src).unwrap_err() {
+            TypedParseError::Convert(e) => {
+                assert_eq!("/servers/1/port", e.path);
+                assert_eq!("expect number, found string", e.desc);
+            }
+            e => panic!("{}", e),
+        }
+
+        let e = parse_typed::<Config>("{\"name\":\"zxh\"}").unwrap_err();
+        assert_eq!("convert missing member at '/debug'", e.to_string());
+
+        let e = parse_typed::<i64>("1.5").unwrap_err();
+        assert_eq!("convert 1.5 is not an i64 at ''", e.to_string());
+    }
+
+    #[test]
+    fn test_try_from_value() {
+        let parse = |s: &str| Reader::new(s).parse().unwrap();
+        let m = HashMap::<String, String>::try_from(parse("{\"a\": \"x\", \"b\": \"y\"}"));
+        let expect: HashMap<_, _> = [("a", "x"), ("b", "y")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(Ok(expect), m);
+
+        let e = HashMap::<String, String>::try_from(parse("{\"a\": \"x\", \"b\": 1}"));
+        assert_eq!(
+            "convert expect string, found number at '/b'",
+            e.unwrap_err().to_string()
+        );
+        let e = Vec::<String>::try_from(parse("[\"x\", null]")).unwrap_err();
+        assert_eq!("/1", e.path);
+        let e = BTreeMap::<String, Value>::try_from(parse("[1]")).unwrap_err();
+        assert_eq!("expect object, found array", e.desc);
+
+        let v = parse("{\"n\": 1, \"s\": [\"x\"], \"o\": {}}");
+        let m = BTreeMap::<String, Value>::try_from(v.clone()).unwrap();
+        let keys: Vec<&str> = m.keys().map(String::as_str).collect();
+        assert_eq!(vec!["n", "o", "s"], keys);
+        assert_eq!(Some(&Value::Number(1.0)), m.get("n"));
+        let arr = Vec::<Value>::try_from(m["s"].clone()).unwrap();
+        assert_eq!(vec![Value::String("x".to_string())], arr);
+        assert_eq!(
+            v,
+            Value::Object(
+                HashMap::<String, Value>::try_from(v.clone())
+                    .unwrap()
+                    .into()
+            )
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+
+use crate::{ErrorKind, ParseError, Reader, Value};
+
+/// How `from_slice_with_encoding` turns bytes into text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Strict UTF-8, invalid sequences are an error.
+    Utf8,
+    /// UTF-8 with invalid sequences replaced by U+FFFD.
+    Utf8Lossy,
+    /// ISO-8859-1: each byte of a string is the code point of its value.
+    Latin1,
+    /// Like `Latin1`, with 0x80..=0x9F mapped as Windows-1252 does, so
+    /// 0x80 is '€'. The five bytes it leaves undefined map as in Latin-1.
+    Windows1252,
+}
+
+// Windows-1252 for 0x80..=0x9F, 0 where it agrees with Latin-1.
+const CP1252: [u16; 32] = [
+    0x20AC, 0, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0, 0x017D, 0, 0, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, 0x02DC,
+    0x2122, 0x0161, 0x203A, 0x0153, 0, 0x017E, 0x0178,
+];
+
+/// Parse bytes from a producer that does not write UTF-8. With `Latin1`
+/// and `Windows1252` only string contents may use bytes above 0x7F, the
+/// rest of the document must be ASCII.
+pub fn from_slice_with_encoding(b: &[u8], enc: Encoding) -> Result<Value, ParseError> {
+    let text = match enc {
+        Encoding::Utf8 => match std::str::from_utf8(b) {
+            Ok(s) => Cow::Borrowed(s),
+            Err(e) => {
+                let valid = std::str::from_utf8(&b[..e.valid_up_to()]).unwrap_or_default();
+                return Err(error_at(
+                    valid,
+                    format!("invalid UTF-8 byte 0x{:02X}", b[valid.len()]),
+                ));
+            }
+        },
+        Encoding::Utf8Lossy => String::from_utf8_lossy(b),
+        Encoding::Latin1 | Encoding::Windows1252 => Cow::Owned(decode_single_byte(b, enc)?),
+    };
+    let mut r = Reader::new(&text);
+    r.parse()
+}
+
+fn decode_single_byte(b: &[u8], enc: Encoding) -> Result<String, ParseError> {
+    let mut s = String::with_capacity(b.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in b {
+        if byte < 0x80 {
+            if in_string && !escaped && byte == b'"' {
+                in_string = false;
+            } else if !in_string && byte == b'"' {
+                in_string = true;
+            }
+            escaped = in_string && !escaped && byte == b'\\';
+            s.push(byte as char);
+            continue;
+        }
+
+        if !in_string {
+            return Err(error_at(
+                &s,
+                format!("byte 0x{:02X} outside a string", byte),
+            ));
+        }
+        escaped = false;
+        let mapped = match enc {
+            Encoding::Windows1252 if byte < 0xA0 => CP1252[(byte - 0x80) as usize],
+            _ => 0,
+        };
+        match mapped {
+            0 => s.push(byte as char),
+            c => s.push(std::char::from_u32(c as u32).unwrap()),
+        }
+    }
+    Ok(s)
+}
+
+// An error just after `before`, the text decoded so far. Lines end as for
+// the reader, at `\r\n`, `\n` or a lone `\r`.
+pub(crate) fn error_at(before: &str, desc: String) -> ParseError {
+    let row = before.replace("\r\n", "\n").matches(['\n', '\r']).count() + 1;
+    let line = before.rsplit(['\n', '\r']).next().unwrap_or_default();
+    ParseError {
+        kind: ErrorKind::Syntax,
+        row,
+        column: line.chars().count() + 1,
+        desc,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Value {
+        Value::String(String::from(s))
+    }
+
+    #[test]
+    fn test_latin1() {
+        let b = b"{\"name\": \"caf\xe9\", \"q\": \"\\\"\xbf\"}";
+        let v = from_slice_with_encoding(b, Encoding::Latin1).unwrap();
+        assert_eq!(Some(&string("café")), v.get("name"));
+        assert_eq!(Some(&string("\"¿")), v.get("q"));
+
+        let e = from_slice_with_encoding(b, Encoding::Utf8).unwrap_err();
+        assert_eq!("invalid UTF-8 byte 0xE9", e.desc);
+        assert_eq!((1, 14), (e.row, e.column));
+        let v = from_slice_with_encoding(b, Encoding::Utf8Lossy).unwrap();
+        assert_eq!(Some(&string("caf\u{FFFD}")), v.get("name"));
+
+        let e = from_slice_with_encoding(b"[1,\n \xa02]", Encoding::Latin1).unwrap_err();
+        assert_eq!("byte 0xA0 outside a string", e.desc);
+        assert_eq!((2, 2), (e.row, e.column));
+        let e = from_slice_with_encoding(b"[1,\r\n2,\r3,\n\xa0]", Encoding::Latin1).unwrap_err();
+        assert_eq!((4, 1), (e.row, e.column));
+    }
+
+    #[test]
+    fn test_windows1252() {
+        let b = b"[\"\x80 5\", \"\x93hi\x94\", \"\x81\xe9\"]";
+        let v = from_slice_with_encoding(b, Encoding::Windows1252).unwrap();
+        let expect = vec![string("€ 5"), string("“hi”"), string("\u{81}é")];
+        assert_eq!(Value::Array(expect), v);
+
+        let v = from_slice_with_encoding(b, Encoding::Latin1).unwrap();
+        let expect = vec![
+            string("\u{80} 5"),
+            string("\u{93}hi\u{94}"),
+            string("\u{81}é"),
+        ];
+        assert_eq!(Value::Array(expect), v);
+    }
+
+    #[test]
+    fn test_utf8() {
+        let v = from_slice_with_encoding("[\"日本\"]".as_bytes(), Encoding::Utf8).unwrap();
+        assert_eq!(Value::Array(vec![string("日本")]), v);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::Value;
+
+/// Looser comparisons for `Value::equivalent`. `==` on `Value` is always
+/// strict, whatever these say.
+#[must_use]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EqOptions {
+    /// A member whose value is `null` equals the member being absent, on
+    /// either side and at any depth: `{"a":1}` is equivalent to
+    /// `{"a":1,"b":null}`. Array elements are never skipped.
+    pub null_equals_absent: bool,
+}
+
+impl EqOptions {
+    pub fn new() -> EqOptions {
+        EqOptions::default()
+    }
+
+    pub fn null_equals_absent(mut self, on: bool) -> EqOptions {
+        self.null_equals_absent = on;
+        self
+    }
+}
+
+impl Value {
+    /// `self == other` loosened by `opts`. This is not `PartialEq` and has
+    /// no matching `Hash`; to use the loose form as a map key, normalize the
+    /// values first, with `drop_nulls_deep` for `null_equals_absent`.
+    pub fn equivalent(&self, other: &Value, opts: &EqOptions) -> bool {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.equivalent(y, opts))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                let absent = |x: &Value| opts.null_equals_absent && matches!(x, Value::Null);
+                let a_in_b = a.visible().into_iter().all(|(k, x)| match b.get(k) {
+                    Some(y) => x.equivalent(y, opts),
+                    None => absent(x),
+                });
+                a_in_b
+                    && b.visible()
+                        .into_iter()
+                        .all(|(k, y)| a.contains_key(k) || absent(y))
+            }
+            _ => self == other,
+        }
+    }
+
+    /// A copy without the object members whose value is `null`, at any
+    /// depth. `null` array elements stay, they hold a position.
+    pub fn drop_nulls_deep(&self) -> Value {
+        let mut v = self.clone();
+        v.filter_nulls();
+        v
+    }
+
+    /// Remove the object members whose value is `null`, at any depth, in
+    /// place. `null` array elements stay.
+    pub fn filter_nulls(&mut self) {
+        self.filter_nulls_with(false);
+    }
+
+    /// `filter_nulls`, also removing `null` array elements if `elements`.
+    pub fn filter_nulls_with(&mut self, elements: bool) {
+        match self {
+            Value::Array(arr) => {
+                if elements {
+                    arr.retain(|x| !matches!(x, Value::Null));
+                }
+                arr.iter_mut().for_each(|x| x.filter_nulls_with(elements));
+            }
+            Value::Object(m) => {
+                let nulls: Vec<String> = m
+                    .visible()
+                    .into_iter()
+                    .filter(|(_, x)| matches!(x, Value::Null))
+                    .map(|(k, _)| k.to_string())
+                    .collect();
+                for k in nulls {
+                    m.remove(&k);
+                }
+                m.iter_mut()
+                    .for_each(|(_, x)| x.filter_nulls_with(elements));
+            }
+            _ => {}
+        }
+    }
+
+    /// Turn `-0.0` into `0.0` at any depth. `==` already holds them equal,
+    /// this makes them also print and hash alike.
+    pub fn normalize_numbers(&mut self) {
+        self.normalize_numbers_with(0.0);
+    }
+
+    /// `normalize_numbers`, also snapping a number within `snap` of an
+    /// integer to it: `2.9999999999` becomes `3` with a `snap` of `1e-6`.
+    pub fn normalize_numbers_with(&mut self, snap: f64) {
+        match self {
+            Value::Number(x) => {
+                let near = x.round();
+                if (*x - near).abs() <= snap {
+                    *x = near;
+                }
+                if *x == 0.0 {
+                    *x = 0.0;
+                }
+            }
+            Value::Array(arr) => arr.iter_mut().for_each(|x| x.normalize_numbers_with(snap)),
+            Value::Object(m) => m
+                .iter_mut()
+                .for_each(|(_, x)| x.normalize_numbers_with(snap)),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, Reader};
+
+    fn parse(s: &str) -> Value {
+        Reader::new(s).parse().unwrap()
+    }
+
+    #[test]
+    fn test_null_equals_absent() {
+        let loose = EqOptions::new().null_equals_absent(true);
+        let strict = EqOptions::new();
+        let a = parse("{\"a\": 1}");
+        let b = parse("{\"a\": 1, \"b\": null}");
+        assert!(a.equivalent(&b, &loose));
+        assert!(b.equivalent(&a, &loose));
+        assert!(!a.equivalent(&b, &strict));
+        assert!(!b.equivalent(&a, &strict));
+        assert_ne!(a, b);
+
+        // only a null stands in for a missing member
+        let c = parse("{\"a\": 1, \"b\": 0}");
+        assert!(!a.equivalent(&c, &loose));
+        assert!(!c.equivalent(&a, &loose));
+        assert!(!parse("{\"a\": null}").equivalent(&parse("{\"a\": 1}"), &loose));
+
+        let x = parse("[{\"id\": 1, \"tags\": [{\"t\": \"a\", \"note\": null}]}, null]");
+        let y = parse("[{\"id\": 1, \"extra\": null, \"tags\": [{\"t\": \"a\"}]}, null]");
+        assert!(x.equivalent(&y, &loose));
+        assert!(y.equivalent(&x, &loose));
+        assert!(!x.equivalent(&y, &strict));
+        // array elements are compared by position, nulls included
+        assert!(!parse("[1, null]").equivalent(&parse("[1]"), &loose));
+        assert!(x.equivalent(&x.clone(), &strict));
+    }
+
+    #[test]
+    fn test_drop_nulls_deep() {
+        let x = parse("[{\"id\": 1, \"tags\": [{\"t\": \"a\", \"note\": null}]}, null]");
+        let expect = parse("[{\"id\": 1, \"tags\": [{\"t\": \"a\"}]}, null]");
+        assert_eq!(expect, x.drop_nulls_deep());
+        let y = parse("[{\"id\": 1, \"extra\": null, \"tags\": [{\"t\": \"a\"}]}, null]");
+        assert_eq!(x.drop_nulls_deep(), y.drop_nulls_deep());
+
+        // a duplicate key hidden by a later null goes with it
+        let opts = ParseOptions::new().document_mode(true);
+        let v = Reader::with_options("{\"a\": 1, \"b\": 2, \"a\": null}", opts)
+            .parse()
+            .unwrap();
+        assert_eq!("{\"b\":2}", v.drop_nulls_deep().to_string());
+    }
+
+    #[test]
+    fn test_filter_nulls() {
+        let src =
+            "{\"a\": null, \"b\": {\"c\": null, \"d\": [null, {\"e\": null, \"f\": 1}]}, \"g\": 0}";
+        let mut v = parse(src);
+        v.filter_nulls();
+        assert_eq!(parse("{\"b\": {\"d\": [null, {\"f\": 1}]}, \"g\": 0}"), v);
+        assert_eq!(v, parse(src).drop_nulls_deep());
+
+        let mut v = parse(src);
+        v.filter_nulls_with(true);
+        assert_eq!(parse("{\"b\": {\"d\": [{\"f\": 1}]}, \"g\": 0}"), v);
+        let mut v = parse("[null, [null], {}]");
+        v.filter_nulls_with(true);
+        assert_eq!(parse("[[], {}]"), v);
+    }
+
+    #[test]
+    fn test_normalize_numbers() {
+        let mut v = parse("[-0.0, {\"a\": [-0]}, -1e-400, 2.9999999999, -3]");
+        v.normalize_numbers();
+        assert_eq!("[0,{\"a\":[0]},0,2.9999999999,-3]", v.to_string());
+        match v.as_array().map(|a| &a[0]) {
+            Some(Value::Number(x)) => assert!(x.is_sign_positive()),
+            x => panic!("{:?}", x),
+        }
+
+        let mut v = parse("[-0.0000001, 2.9999999999, 0.5, 12.25]");
+        v.normalize_numbers_with(1e-6);
+        assert_eq!("[0,3,0.5,12.25]", v.to_string());
+    }
+}
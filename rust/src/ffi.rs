@@ -0,0 +1,308 @@
+//! C interface, enabled with the `ffi` feature.
+//!
+//! Every function accepts null pointers and never unwinds into C: a null
+//! argument or a caught panic yields a null/zero result, and the reason is
+//! available from `sjp_last_error_message` on the same thread.
+//!
+//! Values returned by `sjp_parse` are owned by the caller and released with
+//! `sjp_free`. Values returned by `sjp_get` borrow from their root and must
+//! not be freed or used after the root is freed.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, UnwindSafe};
+use std::ptr;
+use std::slice;
+use std::str;
+
+use crate::{Reader, Value};
+
+/// Opaque handle to a parsed value.
+#[repr(transparent)]
+pub struct SjpValue(Value);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SjpType {
+    Invalid = -1,
+    Null = 0,
+    False = 1,
+    True = 2,
+    Number = 3,
+    String = 4,
+    Array = 5,
+    Object = 6,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(desc: &str) {
+    // interior NULs cannot be represented in a C string
+    let msg = CString::new(desc.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+}
+
+fn guard<T, F: FnOnce() -> T + UnwindSafe>(fallback: T, f: F) -> T {
+    match panic::catch_unwind(f) {
+        Ok(x) => x,
+        Err(_) => {
+            set_last_error("panic inside jsonrs");
+            fallback
+        }
+    }
+}
+
+/// Parse `len` bytes of UTF-8 JSON text at `s`.
+///
+/// Returns null on error, see `sjp_last_error_message`.
+///
+/// # Safety
+///
+/// `s` must be null or point to at least `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sjp_parse(s: *const c_char, len: usize) -> *mut SjpValue {
+    guard(ptr::null_mut(), || {
+        if s.is_null() {
+            set_last_error("null input");
+            return ptr::null_mut();
+        }
+
+        let bytes = slice::from_raw_parts(s as *const u8, len);
+        let text = match str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => {
+                set_last_error(&format!("input is not UTF-8: {}", e));
+                return ptr::null_mut();
+            }
+        };
+
+        match Reader::new(text).parse() {
+            Ok(v) => {
+                clear_last_error();
+                Box::into_raw(Box::new(SjpValue(v)))
+            }
+            Err(e) => {
+                set_last_error(&e.to_string());
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Release a value returned by `sjp_parse`. Null is ignored.
+///
+/// # Safety
+///
+/// `v` must be null or a pointer from `sjp_parse` that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn sjp_free(v: *mut SjpValue) {
+    guard((), || {
+        if !v.is_null() {
+            drop(Box::from_raw(v));
+        }
+    })
+}
+
+/// Look up a NUL-terminated JSON Pointer (RFC 6901) below `v`.
+///
+/// Returns null when the pointer does not resolve.
+///
+/// # Safety
+///
+/// `v` must be null or a live value, `pointer` null or a NUL-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn sjp_get(v: *const SjpValue, pointer: *const c_char) -> *const SjpValue {
+    guard(ptr::null(), || {
+        if v.is_null() || pointer.is_null() {
+            set_last_error("null argument");
+            return ptr::null();
+        }
+
+        let p = match CStr::from_ptr(pointer).to_str() {
+            Ok(p) => p,
+            Err(_) => {
+                set_last_error("pointer is not UTF-8");
+                return ptr::null();
+            }
+        };
+
+        match (*v).0.pointer(p) {
+            Some(x) => x as *const Value as *const SjpValue,
+            None => {
+                set_last_error(&format!("pointer '{}' not found", p));
+                ptr::null()
+            }
+        }
+    })
+}
+
+/// Type of `v`, `Invalid` for null.
+///
+/// # Safety
+///
+/// `v` must be null or a live value.
+#[no_mangle]
+pub unsafe extern "C" fn sjp_type(v: *const SjpValue) -> SjpType {
+    guard(SjpType::Invalid, || {
+        if v.is_null() {
+            return SjpType::Invalid;
+        }
+        match &(*v).0 {
+            Value::Null => SjpType::Null,
+            Value::Bool(false) => SjpType::False,
+            Value::Bool(true) => SjpType::True,
+            Value::Number(..) => SjpType::Number,
+            Value::String(..) => SjpType::String,
+            Value::Array(..) => SjpType::Array,
+            Value::Object(..) => SjpType::Object,
+        }
+    })
+}
+
+/// Store the number held by `v` in `out`. Returns 1 on success and 0 if
+/// `v` is not a number or an argument is null.
+///
+/// # Safety
+///
+/// `v` must be null or a live value, `out` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn sjp_as_double(v: *const SjpValue, out: *mut f64) -> c_int {
+    guard(0, || {
+        if v.is_null() || out.is_null() {
+            return 0;
+        }
+        match &(*v).0 {
+            Value::Number(v) => {
+                *out = *v;
+                1
+            }
+            _ => 0,
+        }
+    })
+}
+
+/// Borrow the UTF-8 bytes of a string value, storing the byte length in
+/// `len`. The bytes are not NUL-terminated and live as long as the root.
+/// Returns null if `v` is not a string or an argument is null.
+///
+/// # Safety
+///
+/// `v` must be null or a live value, `len` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn sjp_as_string(v: *const SjpValue, len: *mut usize) -> *const c_char {
+    guard(ptr::null(), || {
+        if v.is_null() || len.is_null() {
+            return ptr::null();
+        }
+        match &(*v).0 {
+            Value::String(v) => {
+                *len = v.len();
+                v.as_ptr() as *const c_char
+            }
+            _ => ptr::null(),
+        }
+    })
+}
+
+/// Message of the last failed call on this thread, or null. The pointer
+/// stays valid until the next `sjp_*` call on the same thread.
+#[no_mangle]
+pub extern "C" fn sjp_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|e| match &*e.borrow() {
+        Some(msg) => msg.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let p = sjp_last_error_message();
+        if p.is_null() {
+            return None;
+        }
+        Some(unsafe { CStr::from_ptr(p) }.to_str().unwrap().to_string())
+    }
+
+    fn parse(s: &str) -> *mut SjpValue {
+        unsafe { sjp_parse(s.as_ptr() as *const c_char, s.len()) }
+    }
+
+    #[test]
+    fn test_parse_get_free() {
+        let root = parse("{\"name\":\"zxh\",\"ports\":[80,443]}");
+        assert!(!root.is_null());
+        assert_eq!(None, last_error());
+
+        unsafe {
+            assert_eq!(SjpType::Object, sjp_type(root));
+
+            let name = sjp_get(root, b"/name\0".as_ptr() as *const c_char);
+            assert_eq!(SjpType::String, sjp_type(name));
+            let mut len = 0;
+            let s = sjp_as_string(name, &mut len);
+            assert_eq!(b"zxh", slice::from_raw_parts(s as *const u8, len));
+
+            let port = sjp_get(root, b"/ports/1\0".as_ptr() as *const c_char);
+            let mut d = 0.0;
+            assert_eq!(1, sjp_as_double(port, &mut d));
+            assert_eq!(443.0, d);
+            assert_eq!(0, sjp_as_double(name, &mut d));
+            assert!(sjp_as_string(port, &mut len).is_null());
+
+            let missing = sjp_get(root, b"/ports/2\0".as_ptr() as *const c_char);
+            assert!(missing.is_null());
+            assert_eq!(
+                Some("pointer '/ports/2' not found".to_string()),
+                last_error()
+            );
+
+            sjp_free(root);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(parse("[1,").is_null());
+        assert!(last_error().unwrap().starts_with("parse "));
+
+        let bad = [b'"', 0xff, b'"'];
+        assert!(unsafe { sjp_parse(bad.as_ptr() as *const c_char, bad.len()) }.is_null());
+        assert!(last_error().unwrap().starts_with("input is not UTF-8"));
+
+        let ok = parse("null");
+        assert_eq!(None, last_error());
+        unsafe { sjp_free(ok) };
+    }
+
+    #[test]
+    fn test_null_tolerant() {
+        unsafe {
+            assert!(sjp_parse(ptr::null(), 4).is_null());
+            assert_eq!(Some("null input".to_string()), last_error());
+            sjp_free(ptr::null_mut());
+            assert!(sjp_get(ptr::null(), b"\0".as_ptr() as *const c_char).is_null());
+            assert_eq!(SjpType::Invalid, sjp_type(ptr::null()));
+
+            let mut d = 0.0;
+            assert_eq!(0, sjp_as_double(ptr::null(), &mut d));
+            let mut len = 0;
+            assert!(sjp_as_string(ptr::null(), &mut len).is_null());
+
+            let root = parse("1");
+            assert!(sjp_get(root, ptr::null()).is_null());
+            assert_eq!(0, sjp_as_double(root, ptr::null_mut()));
+            sjp_free(root);
+        }
+    }
+}
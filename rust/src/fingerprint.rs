@@ -0,0 +1,79 @@
+use crate::ser::write_string;
+use crate::Value;
+
+impl Value {
+    /// The structure of the value without its scalars, equal for documents
+    /// of the same shape. Scalars become their type name, objects list
+    /// their keys sorted, and arrays the distinct shapes of their elements
+    /// sorted and joined by `|`, whatever the length:
+    /// `{"id":number,"tags":[string]}`.
+    pub fn schema_fingerprint(&self) -> String {
+        let mut s = String::new();
+        fingerprint(&mut s, self);
+        s
+    }
+}
+
+fn fingerprint(w: &mut String, v: &Value) {
+    match v {
+        Value::Null => w.push_str("null"),
+        Value::Bool(_) => w.push_str("boolean"),
+        Value::Number(_) => w.push_str("number"),
+        Value::String(_) => w.push_str("string"),
+        Value::Array(v) => {
+            let mut shapes: Vec<String> = v.iter().map(Value::schema_fingerprint).collect();
+            shapes.sort();
+            shapes.dedup();
+            w.push('[');
+            w.push_str(&shapes.join("|"));
+            w.push(']');
+        }
+        Value::Object(v) => {
+            let mut members: Vec<(&str, &Value)> = v.entries().collect();
+            members.sort_by_key(|m| m.0);
+            w.push('{');
+            for (i, (k, x)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    w.push(',');
+                }
+                let _ = write_string(w, k);
+                w.push(':');
+                fingerprint(w, x);
+            }
+            w.push('}');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Reader;
+
+    fn fingerprint(s: &str) -> String {
+        Reader::new(s).parse().unwrap().schema_fingerprint()
+    }
+
+    #[test]
+    fn test_schema_fingerprint() {
+        let a = fingerprint(r#"{"id": 1, "name": "a", "tags": ["x"], "owner": {"ok": true}}"#);
+        let b =
+            fingerprint(r#"{"owner": {"ok": false}, "tags": ["y", "z"], "name": "b", "id": 2}"#);
+        assert_eq!(a, b);
+        assert_eq!(
+            r#"{"id":number,"name":string,"owner":{"ok":boolean},"tags":[string]}"#,
+            a
+        );
+
+        assert_ne!(
+            a,
+            fingerprint(r#"{"id": "1", "name": "a", "tags": [], "owner": {}}"#)
+        );
+        assert_eq!("[]", fingerprint("[]"));
+        assert_eq!("[null|number]", fingerprint("[1, null, 2]"));
+        assert_eq!(
+            "[[number]|{\"a\\\"\":string}]",
+            fingerprint(r#"[{"a\"": "x"}, [1]]"#)
+        );
+        assert_eq!("string", fingerprint("\"s\""));
+    }
+}
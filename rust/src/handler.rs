@@ -0,0 +1,395 @@
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+
+use crate::map::{Key, KeyPool};
+use crate::{Map, ParseError, Reader, TransformFn, Value};
+
+/// Receives a document from `parse_with_handler` as events, in document
+/// order, instead of a `Value` tree. Every `begin_*` is matched by its
+/// `end_*` and every object member starts with `key`. Returning an error
+/// stops parsing right away.
+pub trait Handler {
+    type Error;
+
+    fn null(&mut self) -> Result<(), Self::Error>;
+    fn bool(&mut self, b: bool) -> Result<(), Self::Error>;
+    fn number(&mut self, v: f64) -> Result<(), Self::Error>;
+    fn string(&mut self, s: &str) -> Result<(), Self::Error>;
+    fn begin_array(&mut self) -> Result<(), Self::Error>;
+    fn end_array(&mut self) -> Result<(), Self::Error>;
+    fn begin_object(&mut self) -> Result<(), Self::Error>;
+    fn key(&mut self, k: &str) -> Result<(), Self::Error>;
+    fn end_object(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Why `parse_with_handler` stopped: bad input, or the handler said so.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandlerError<E> {
+    Parse(ParseError),
+    Handler(E),
+}
+
+impl<E: fmt::Display> fmt::Display for HandlerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandlerError::Parse(e) => e.fmt(f),
+            HandlerError::Handler(e) => write!(f, "handler error {}", e),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for HandlerError<E> {}
+
+/// Parse `input`, feeding each value to `h` as it is read. Nothing is kept
+/// apart from the path to the current value, so this is how to build
+/// something other than a `Value` without the tree in between.
+pub fn parse_with_handler<H: Handler>(
+    input: &str,
+    h: &mut H,
+) -> Result<(), HandlerError<H::Error>> {
+    Reader::new(input).parse_with_handler(h)
+}
+
+impl<'a> Reader<'a> {
+    /// `parse_with_handler` with this reader's options. A string transform
+    /// is not applied, it belongs to the `Value` tree.
+    pub fn parse_with_handler<H: Handler>(
+        &mut self,
+        h: &mut H,
+    ) -> Result<(), HandlerError<H::Error>> {
+        self.rewind();
+        let mut sink = Sink {
+            handler: h,
+            error: None,
+        };
+        let x = self.parse_element(&mut sink);
+        if let Some(e) = sink.error {
+            self.abort = None;
+            return Err(HandlerError::Handler(e));
+        }
+        if let Some(e) = self.abort.take() {
+            return Err(HandlerError::Parse(e));
+        }
+        x.and_then(|()| self.expect_end())
+            .map_err(HandlerError::Parse)
+    }
+
+    // Parse one element from the cursor into a `Value`.
+    pub(crate) fn parse_tree(&mut self) -> Result<Value, ParseError> {
+        let transform = self.transform.take();
+        let mut builder = TreeHandler {
+            stack: Vec::new(),
+            root: None,
+            keys: KeyPool::default(),
+            document_mode: self.options.document_mode,
+            transform: transform.as_deref(),
+        };
+        let x = self.parse_element(&mut Sink {
+            handler: &mut builder,
+            error: None,
+        });
+        let root = builder.root;
+        self.transform = transform;
+        x.map(|()| root.expect("a parsed element is a value"))
+    }
+
+    // Check the value at the cursor without building anything.
+    pub(crate) fn skip_value(&mut self) -> Result<(), ParseError> {
+        self.parse_value(&mut Sink {
+            handler: &mut Skip,
+            error: None,
+        })
+    }
+}
+
+// Where the parser sends events, keeping the handler's error for
+// `parse_with_handler` while the parser unwinds with a `ParseError`.
+pub(crate) struct Sink<'h, H: Handler> {
+    pub(crate) handler: &'h mut H,
+    pub(crate) error: Option<H::Error>,
+}
+
+enum Frame {
+    Array(Vec<Value>),
+    // the key of the member being parsed
+    Object(Map, Option<Key>),
+}
+
+// The handler behind `Reader::parse`: containers being built are kept on a
+// stack and each finished value goes into the one on top.
+struct TreeHandler<'t> {
+    stack: Vec<Frame>,
+    root: Option<Value>,
+    keys: KeyPool,
+    document_mode: bool,
+    transform: Option<&'t TransformFn<'t>>,
+}
+
+impl TreeHandler<'_> {
+    fn value(&mut self, v: Value) -> Result<(), Infallible> {
+        match self.stack.last_mut() {
+            Some(Frame::Array(arr)) => arr.push(v),
+            Some(Frame::Object(m, k)) => m.push(k.take().expect("key before a member value"), v),
+            None => self.root = Some(v),
+        }
+        Ok(())
+    }
+}
+
+impl Handler for TreeHandler<'_> {
+    type Error = Infallible;
+
+    fn null(&mut self) -> Result<(), Infallible> {
+        self.value(Value::Null)
+    }
+
+    fn bool(&mut self, b: bool) -> Result<(), Infallible> {
+        self.value(Value::Bool(b))
+    }
+
+    fn number(&mut self, v: f64) -> Result<(), Infallible> {
+        self.value(Value::Number(v))
+    }
+
+    fn string(&mut self, s: &str) -> Result<(), Infallible> {
+        match self.transform.and_then(|f| f(s)) {
+            Some(v) => self.value(v),
+            None => self.value(Value::String(s.to_string())),
+        }
+    }
+
+    fn begin_array(&mut self) -> Result<(), Infallible> {
+        self.stack.push(Frame::Array(Vec::new()));
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), Infallible> {
+        match self.stack.pop() {
+            Some(Frame::Array(arr)) => self.value(Value::Array(arr)),
+            _ => unreachable!("end_array without begin_array"),
+        }
+    }
+
+    fn begin_object(&mut self) -> Result<(), Infallible> {
+        let m = if self.document_mode {
+            Map::document()
+        } else {
+            Map::new()
+        };
+        self.stack.push(Frame::Object(m, None));
+        Ok(())
+    }
+
+    fn key(&mut self, k: &str) -> Result<(), Infallible> {
+        if let Some(Frame::Object(_, key)) = self.stack.last_mut() {
+            *key = Some(self.keys.key(k));
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Infallible> {
+        match self.stack.pop() {
+            Some(Frame::Object(m, _)) => self.value(Value::Object(m)),
+            _ => unreachable!("end_object without begin_object"),
+        }
+    }
+}
+
+// The handler behind `Reader::skip_value`, ignoring every event.
+struct Skip;
+
+impl Handler for Skip {
+    type Error = Infallible;
+
+    fn null(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn bool(&mut self, _: bool) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn number(&mut self, _: f64) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn string(&mut self, _: &str) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn begin_array(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn begin_object(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn key(&mut self, _: &str) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        scalars: usize,
+        containers: usize,
+        keys: usize,
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl Counter {
+        fn scalar(&mut self) -> Result<(), Infallible> {
+            self.scalars += 1;
+            Ok(())
+        }
+
+        fn begin(&mut self) -> Result<(), Infallible> {
+            self.containers += 1;
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+            Ok(())
+        }
+
+        fn end(&mut self) -> Result<(), Infallible> {
+            self.depth -= 1;
+            Ok(())
+        }
+    }
+
+    impl Handler for Counter {
+        type Error = Infallible;
+
+        fn null(&mut self) -> Result<(), Infallible> {
+            self.scalar()
+        }
+        fn bool(&mut self, _: bool) -> Result<(), Infallible> {
+            self.scalar()
+        }
+        fn number(&mut self, _: f64) -> Result<(), Infallible> {
+            self.scalar()
+        }
+        fn string(&mut self, _: &str) -> Result<(), Infallible> {
+            self.scalar()
+        }
+        fn begin_array(&mut self) -> Result<(), Infallible> {
+            self.begin()
+        }
+        fn end_array(&mut self) -> Result<(), Infallible> {
+            self.end()
+        }
+        fn begin_object(&mut self) -> Result<(), Infallible> {
+            self.begin()
+        }
+        fn key(&mut self, _: &str) -> Result<(), Infallible> {
+            self.keys += 1;
+            Ok(())
+        }
+        fn end_object(&mut self) -> Result<(), Infallible> {
+            self.end()
+        }
+    }
+
+    // Stops at the first string longer than `max` bytes.
+    struct ShortStrings {
+        max: usize,
+        seen: Vec<String>,
+    }
+
+    impl Handler for ShortStrings {
+        type Error = String;
+
+        fn null(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+        fn bool(&mut self, _: bool) -> Result<(), String> {
+            Ok(())
+        }
+        fn number(&mut self, _: f64) -> Result<(), String> {
+            Ok(())
+        }
+        fn string(&mut self, s: &str) -> Result<(), String> {
+            if s.len() > self.max {
+                return Err(format!("'{}' too long", s));
+            }
+            self.seen.push(s.to_string());
+            Ok(())
+        }
+        fn begin_array(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+        fn end_array(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+        fn begin_object(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+        fn key(&mut self, _: &str) -> Result<(), String> {
+            Ok(())
+        }
+        fn end_object(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_count_nodes() {
+        let src = "{\"a\": [1, \"x\", null, [true]], \"b\": {\"c\": {}}, \"d\": \"\\u00e9\"}";
+        let mut c = Counter::default();
+        parse_with_handler(src, &mut c).unwrap();
+        assert_eq!(
+            (5, 5, 4, 3, 0),
+            (c.scalars, c.containers, c.keys, c.max_depth, c.depth)
+        );
+
+        let mut c = Counter::default();
+        let e = parse_with_handler("[1, 2", &mut c).unwrap_err();
+        match e {
+            HandlerError::Parse(e) => assert_eq!("array end char expect ']'", e.desc),
+            HandlerError::Handler(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_handler_abort() {
+        let src = "[\"ab\", {\"k\": \"abc\"}, \"abcdef\", \"x\", \"abcdefgh\"]";
+        let mut h = ShortStrings {
+            max: 4,
+            seen: Vec::new(),
+        };
+        let e = parse_with_handler(src, &mut h).unwrap_err();
+        assert_eq!(HandlerError::Handler(String::from("'abcdef' too long")), e);
+        assert_eq!(vec!["ab", "abc"], h.seen);
+        assert_eq!("handler error 'abcdef' too long", e.to_string());
+    }
+
+    #[test]
+    fn test_handler_options() {
+        let opts = crate::ParseOptions::new().max_depth(2);
+        let mut c = Counter::default();
+        let e = Reader::with_options("[[[]]]", opts)
+            .parse_with_handler(&mut c)
+            .unwrap_err();
+        assert_eq!(
+            "nesting deeper than 2",
+            match e {
+                HandlerError::Parse(e) => e.desc,
+                HandlerError::Handler(_) => unreachable!(),
+            }
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::Value;
+
+/// `Value::json_path` could not parse its expression. `offset` is the byte
+/// offset of the problem in the expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPathError {
+    pub offset: usize,
+    pub desc: String,
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "json path {} at {}", self.desc, self.offset)
+    }
+}
+
+impl Error for JsonPathError {}
+
+impl Value {
+    /// Nodes matched by the JSONPath `expr`. Supported: the root `$`,
+    /// children `.name` and `['name']`, wildcards `.*` and `[*]`, recursive
+    /// descent `..`, indices `[2]` and `[-1]`, and slices `[start:end:step]`
+    /// where each part is optional and may be negative. Filters `[?(..)]`
+    /// are not.
+    ///
+    /// Matches are in document order, object members by key, and a node
+    /// reached twice is listed twice.
+    pub fn json_path(&self, expr: &str) -> Result<Vec<&Value>, JsonPathError> {
+        let steps = Parser { expr, pos: 0 }.parse()?;
+        let mut nodes = vec![self];
+        for step in &steps {
+            if step.descend {
+                nodes = nodes.into_iter().flat_map(descendants).collect();
+            }
+            let mut next = Vec::new();
+            for v in nodes {
+                step.selector.apply(v, &mut next);
+            }
+            nodes = next;
+        }
+        Ok(nodes)
+    }
+}
+
+struct Step {
+    // `..` before the selector
+    descend: bool,
+    selector: Selector,
+}
+
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, i64),
+}
+
+impl Selector {
+    fn apply<'v>(&self, v: &'v Value, out: &mut Vec<&'v Value>) {
+        match (self, v) {
+            (Selector::Name(k), Value::Object(m)) => out.extend(m.get(k)),
+            (Selector::Wildcard, Value::Object(_)) => out.extend(sorted_members(v)),
+            (Selector::Wildcard, Value::Array(arr)) => out.extend(arr),
+            (Selector::Index(i), Value::Array(arr)) => {
+                let i = if *i < 0 { *i + arr.len() as i64 } else { *i };
+                if i >= 0 {
+                    out.extend(arr.get(i as usize));
+                }
+            }
+            (Selector::Slice(start, end, step), Value::Array(arr)) => {
+                slice(arr, *start, *end, *step, out)
+            }
+            _ => {}
+        }
+    }
+}
+
+// Python's slice semantics: negative bounds count from the end and are
+// clamped to the array, a negative step walks backwards, step 0 selects
+// nothing.
+fn slice<'v>(
+    arr: &'v [Value],
+    start: Option<i64>,
+    end: Option<i64>,
+    step: i64,
+    out: &mut Vec<&'v Value>,
+) {
+    let len = arr.len() as i64;
+    let bound = |i: i64, lo: i64, hi: i64| {
+        let i = if i < 0 { i + len } else { i };
+        i.clamp(lo, hi)
+    };
+    if step > 0 {
+        let mut i = start.map_or(0, |s| bound(s, 0, len));
+        let end = end.map_or(len, |e| bound(e, 0, len));
+        while i < end {
+            out.push(&arr[i as usize]);
+            i = match i.checked_add(step) {
+                Some(i) => i,
+                None => break,
+            };
+        }
+    } else if step < 0 {
+        let mut i = start.map_or(len - 1, |s| bound(s, -1, len - 1));
+        let end = end.map_or(-1, |e| bound(e, -1, len - 1));
+        while i > end {
+            out.push(&arr[i as usize]);
+            i = match i.checked_add(step) {
+                Some(i) => i,
+                None => break,
+            };
+        }
+    }
+}
+
+// The children of objects in key order, for a deterministic result.
+fn sorted_members(v: &Value) -> Vec<&Value> {
+    match v {
+        Value::Object(m) => {
+            let mut members = m.visible();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+            members.into_iter().map(|(_, x)| x).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+// `v` and everything below it, parents first.
+fn descendants(v: &Value) -> Vec<&Value> {
+    let mut all = Vec::new();
+    let mut stack = vec![v];
+    while let Some(v) = stack.pop() {
+        all.push(v);
+        match v {
+            Value::Array(arr) => stack.extend(arr.iter().rev()),
+            Value::Object(_) => stack.extend(sorted_members(v).into_iter().rev()),
+            _ => {}
+        }
+    }
+    all
+}
+
+struct Parser<'e> {
+    expr: &'e str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn parse(mut self) -> Result<Vec<Step>, JsonPathError> {
+        if !self.eat('$') {
+            return self.error("expect '$'");
+        }
+        let mut steps = Vec::new();
+        while let Some(ch) = self.peek() {
+            let step = match ch {
+                '.' => {
+                    self.pos += 1;
+                    let descend = self.eat('.');
+                    let selector = match self.peek() {
+                        Some('[') if descend => self.bracket()?,
+                        Some('*') => {
+                            self.pos += 1;
+                            Selector::Wildcard
+                        }
+                        _ => Selector::Name(self.name()?),
+                    };
+                    Step { descend, selector }
+                }
+                '[' => Step {
+                    descend: false,
+                    selector: self.bracket()?,
+                },
+                _ => return self.error("expect '.' or '['"),
+            };
+            steps.push(step);
+        }
+        Ok(steps)
+    }
+
+    // A dot-notation member name.
+    fn name(&mut self) -> Result<String, JsonPathError> {
+        let rest = &self.expr[self.pos..];
+        let len = rest
+            .find(|c: char| c == '.' || c == '[' || c == ']' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return self.error("expect name");
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    // `[...]`, the cursor on the '['.
+    fn bracket(&mut self) -> Result<Selector, JsonPathError> {
+        self.pos += 1;
+        let selector = match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Selector::Wildcard
+            }
+            Some(q @ '\'') | Some(q @ '"') => Selector::Name(self.quoted(q)?),
+            Some('?') => return self.error("filters are not supported"),
+            _ => self.index_or_slice()?,
+        };
+        if !self.eat(']') {
+            return self.error("expect ']'");
+        }
+        Ok(selector)
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, JsonPathError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut s = String::new();
+        while let Some(ch) = self.peek() {
+            self.pos += ch.len_utf8();
+            match ch {
+                '\\' => match self.peek() {
+                    Some(c) => {
+                        self.pos += c.len_utf8();
+                        s.push(c);
+                    }
+                    None => break,
+                },
+                c if c == quote => return Ok(s),
+                c => s.push(c),
+            }
+        }
+        self.pos = start;
+        self.error("name not closed")
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, JsonPathError> {
+        let start = self.int()?;
+        if !self.eat(':') {
+            return match start {
+                Some(i) => Ok(Selector::Index(i)),
+                None => self.error("expect index"),
+            };
+        }
+        let end = self.int()?;
+        let step = if self.eat(':') { self.int()? } else { None };
+        Ok(Selector::Slice(start, end, step.unwrap_or(1)))
+    }
+
+    // An optional integer, `-` allowed.
+    fn int(&mut self) -> Result<Option<i64>, JsonPathError> {
+        let rest = &self.expr[self.pos..];
+        let digits = rest.strip_prefix('-').unwrap_or(rest);
+        let n = digits.bytes().take_while(u8::is_ascii_digit).count();
+        if n == 0 {
+            if digits.len() < rest.len() {
+                return self.error("expect digits after '-'");
+            }
+            return Ok(None);
+        }
+        let len = n + rest.len() - digits.len();
+        match rest[..len].parse() {
+            Ok(i) => {
+                self.pos += len;
+                Ok(Some(i))
+            }
+            Err(_) => self.error("index out of range"),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.expr[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += ch.len_utf8();
+            return true;
+        }
+        false
+    }
+
+    fn error<T>(&self, desc: &str) -> Result<T, JsonPathError> {
+        Err(JsonPathError {
+            offset: self.pos,
+            desc: desc.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    const STORE: &str = r#"{ "store": {
+        "book": [
+          { "category": "reference", "author": "Nigel Rees",
+            "title": "Sayings of the Century", "price": 8.95 },
+          { "category": "fiction", "author": "Evelyn Waugh",
+            "title": "Sword of Honour", "price": 12.99 },
+          { "category": "fiction", "author": "Herman Melville",
+            "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99 },
+          { "category": "fiction", "author": "J. R. R. Tolkien",
+            "title": "The Lord of the Rings", "isbn": "0-395-19395-8",
+            "price": 22.99 }
+        ],
+        "bicycle": { "color": "red", "price": 19.95 }
+      }
+    }"#;
+
+    fn query(v: &Value, expr: &str) -> Vec<String> {
+        let found = v.json_path(expr).unwrap();
+        found.iter().map(|x| x.to_string()).collect()
+    }
+
+    fn authors(v: &Value, expr: &str) -> Vec<String> {
+        let found = v.json_path(expr).unwrap();
+        found
+            .iter()
+            .map(|x| {
+                x.get("author")
+                    .and_then(Value::coerce_to_string)
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bookstore() {
+        let v = Reader::new(STORE).parse().unwrap();
+        let all = vec![
+            "\"Nigel Rees\"",
+            "\"Evelyn Waugh\"",
+            "\"Herman Melville\"",
+            "\"J. R. R. Tolkien\"",
+        ];
+        assert_eq!(all, query(&v, "$.store.book[*].author"));
+        assert_eq!(all, query(&v, "$..author"));
+        assert_eq!(all, query(&v, "$['store']['book'][*][\"author\"]"));
+
+        // bicycle sorts before book
+        assert_eq!(
+            vec!["19.95", "8.95", "12.99", "8.99", "22.99"],
+            query(&v, "$..price")
+        );
+        assert_eq!(vec!["19.95"], query(&v, "$.store.bicycle.price"));
+        assert_eq!(2, v.json_path("$.store.*").unwrap().len());
+        assert_eq!(vec![&v], v.json_path("$").unwrap());
+
+        assert_eq!(vec!["Herman Melville"], authors(&v, "$..book[2]"));
+        assert_eq!(vec!["J. R. R. Tolkien"], authors(&v, "$..book[-1]"));
+        assert_eq!(
+            vec!["Nigel Rees", "Evelyn Waugh"],
+            authors(&v, "$..book[:2]")
+        );
+        assert_eq!(
+            vec!["Herman Melville", "J. R. R. Tolkien"],
+            authors(&v, "$..book[-2:]")
+        );
+        assert_eq!(
+            vec!["J. R. R. Tolkien", "Evelyn Waugh"],
+            authors(&v, "$..book[::-2]")
+        );
+        assert!(v.json_path("$..book[9]").unwrap().is_empty());
+        assert!(v.json_path("$.store.nope.x").unwrap().is_empty());
+        assert_eq!(27, v.json_path("$..*").unwrap().len());
+    }
+
+    #[test]
+    fn test_slices() {
+        let v = Reader::new("[0,1,2,3,4,5,6,7]").parse().unwrap();
+        let q = |e| query(&v, e).join(",");
+        assert_eq!("2,3,4", q("$[2:5]"));
+        assert_eq!("1,3,5", q("$[1:6:2]"));
+        assert_eq!("5,6", q("$[-3:-1]"));
+        assert_eq!("7,6,5,4,3,2,1,0", q("$[::-1]"));
+        assert_eq!("6,4", q("$[6:2:-2]"));
+        assert_eq!("0,1,2,3,4,5,6,7", q("$[-100:100]"));
+        assert_eq!("", q("$[5:2]"));
+        assert_eq!("", q("$[::0]"));
+        assert_eq!("1", q("$[1::9223372036854775807]"));
+        assert_eq!("6", q("$[6::-9223372036854775808]"));
+        assert_eq!("7", q("$[7:0:-9223372036854775807]"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let v = Value::Null;
+        let e = |expr| v.json_path(expr).unwrap_err();
+        assert_eq!(
+            JsonPathError {
+                offset: 0,
+                desc: String::from("expect '$'")
+            },
+            e("store")
+        );
+        assert_eq!(8, e("$.store[?(@.price)]").offset);
+        assert_eq!("filters are not supported", e("$.store[?(@.price)]").desc);
+        assert_eq!("expect ']'", e("$.a[1").desc);
+        assert_eq!("expect index", e("$[]").desc);
+        assert_eq!("expect name", e("$.").desc);
+        assert_eq!("expect name", e("$..").desc);
+        assert_eq!("expect digits after '-'", e("$[-:]").desc);
+        assert_eq!("expect '.' or '['", e("$a").desc);
+        let x = e("$['abc");
+        assert_eq!((2, "name not closed"), (x.offset, x.desc.as_str()));
+        assert_eq!("json path expect ']' at 3", e("$[1x]").to_string());
+    }
+}
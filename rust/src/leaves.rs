@@ -0,0 +1,223 @@
+use std::convert::Infallible;
+
+use crate::{pointer_of, Handler, HandlerError, ParseError, PathSegment, Reader};
+
+/// A leaf from `scan_leaves`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalarValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+/// Every scalar in `input` with its JSON Pointer, in document order, read
+/// off the parser's events without building a tree. Empty arrays and
+/// objects yield nothing, and every member is yielded, duplicate keys
+/// included. The leaves before an error come first, then the error.
+///
+/// The leaves are collected before the first is yielded; for memory
+/// bounded by the nesting depth use `scan_leaves_with`.
+pub fn scan_leaves(
+    input: &str,
+) -> impl Iterator<Item = Result<(String, ScalarValue), ParseError>> + '_ {
+    let mut leaves = Vec::new();
+    if let Err(e) = scan_leaves_with(input, |pointer, v| leaves.push(Ok((pointer, v)))) {
+        leaves.push(Err(e));
+    }
+    leaves.into_iter()
+}
+
+/// `scan_leaves` handing each leaf to `f` as it is parsed. Only the path
+/// to the current value is kept.
+pub fn scan_leaves_with<F: FnMut(String, ScalarValue)>(
+    input: &str,
+    f: F,
+) -> Result<(), ParseError> {
+    let mut h = LeafHandler {
+        path: Vec::new(),
+        f,
+    };
+    match Reader::new(input).parse_with_handler(&mut h) {
+        Ok(()) => Ok(()),
+        Err(HandlerError::Parse(e)) => Err(e),
+        Err(HandlerError::Handler(never)) => match never {},
+    }
+}
+
+struct LeafHandler<F> {
+    // one segment per open container, an object's holding "" until its
+    // first key
+    path: Vec<PathSegment>,
+    f: F,
+}
+
+impl<F: FnMut(String, ScalarValue)> LeafHandler<F> {
+    fn leaf(&mut self, v: ScalarValue) -> Result<(), Infallible> {
+        (self.f)(pointer_of(&self.path), v);
+        self.next();
+        Ok(())
+    }
+
+    fn begin(&mut self, seg: PathSegment) -> Result<(), Infallible> {
+        self.path.push(seg);
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), Infallible> {
+        self.path.pop();
+        self.next();
+        Ok(())
+    }
+
+    // A value is done, the next element of an array is one further on.
+    fn next(&mut self) {
+        if let Some(PathSegment::Index(i)) = self.path.last_mut() {
+            *i += 1;
+        }
+    }
+}
+
+impl<F: FnMut(String, ScalarValue)> Handler for LeafHandler<F> {
+    type Error = Infallible;
+
+    fn null(&mut self) -> Result<(), Infallible> {
+        self.leaf(ScalarValue::Null)
+    }
+
+    fn bool(&mut self, b: bool) -> Result<(), Infallible> {
+        self.leaf(ScalarValue::Bool(b))
+    }
+
+    fn number(&mut self, v: f64) -> Result<(), Infallible> {
+        self.leaf(ScalarValue::Number(v))
+    }
+
+    fn string(&mut self, s: &str) -> Result<(), Infallible> {
+        self.leaf(ScalarValue::String(s.to_string()))
+    }
+
+    fn begin_array(&mut self) -> Result<(), Infallible> {
+        self.begin(PathSegment::Index(0))
+    }
+
+    fn end_array(&mut self) -> Result<(), Infallible> {
+        self.end()
+    }
+
+    fn begin_object(&mut self) -> Result<(), Infallible> {
+        self.begin(PathSegment::Key(String::new()))
+    }
+
+    fn key(&mut self, k: &str) -> Result<(), Infallible> {
+        if let Some(seg) = self.path.last_mut() {
+            *seg = PathSegment::Key(k.to_string());
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Infallible> {
+        self.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    // The scalars of the tree `parse` builds, with their pointers.
+    fn tree_leaves(src: &str) -> Vec<(String, ScalarValue)> {
+        let v = Reader::new(src).parse().unwrap();
+        let mut leaves = Vec::new();
+        v.walk(&mut |path, x| {
+            let s = match x {
+                Value::Null => ScalarValue::Null,
+                Value::Bool(b) => ScalarValue::Bool(*b),
+                Value::Number(f) => ScalarValue::Number(*f),
+                Value::String(s) => ScalarValue::String(s.clone()),
+                _ => return,
+            };
+            leaves.push((pointer_of(path), s));
+        });
+        leaves
+    }
+
+    #[test]
+    fn test_scan_leaves() {
+        let docs = [
+            "null",
+            "\"a\\u00e9\"",
+            "[]",
+            "{}",
+            "[1, [2, [3, []]], {}, true]",
+            "{\"a\": {\"b/c\": [null, false, -1.5e3]}, \"~\": \"x\\ny\", \"e\": {}}",
+            " [ {\"k\" : [ {\"k\": 1} ] } , \"\" ]\n",
+        ];
+        for src in docs.iter() {
+            let mut scanned: Vec<_> = scan_leaves(src).collect::<Result<_, _>>().unwrap();
+            let mut expect = tree_leaves(src);
+            // objects are walked in map order, scanned in document order
+            scanned.sort_by(|a, b| a.0.cmp(&b.0));
+            expect.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(expect, scanned, "{}", src);
+        }
+
+        let scanned: Vec<_> = scan_leaves("{\"b\": [1, 2], \"a\": \"s\"}")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            vec![
+                (String::from("/b/0"), ScalarValue::Number(1.0)),
+                (String::from("/b/1"), ScalarValue::Number(2.0)),
+                (String::from("/a"), ScalarValue::String(String::from("s"))),
+            ],
+            scanned
+        );
+    }
+
+    #[test]
+    fn test_scan_leaves_error() {
+        let docs = [
+            "",
+            "[1, 2",
+            "[1 2]",
+            "[1,]",
+            "{\"a\" 1}",
+            "{\"a\": 1,}",
+            "{1: 2}",
+            "{\"a\": 1]",
+            "[1]]",
+            "[1,\n \"x\\q\"]",
+            "[tru]",
+        ];
+        for src in docs.iter() {
+            let scanned: Vec<_> = scan_leaves(src).collect();
+            let last = scanned.last().unwrap();
+            assert_eq!(
+                Reader::new(src).parse().as_ref().unwrap_err(),
+                last.as_ref().unwrap_err(),
+                "{}",
+                src
+            );
+            assert!(scanned[..scanned.len() - 1].iter().all(Result::is_ok));
+        }
+
+        // the parser's depth limit holds
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        let e = scan_leaves(&deep).last().unwrap().unwrap_err();
+        assert_eq!(Reader::new(&deep).parse().unwrap_err(), e);
+    }
+
+    #[test]
+    fn test_scan_leaves_with() {
+        let src = "{\"a\": [true, {\"b\": null}], \"c\": [[], 2]}";
+        let mut pointers = Vec::new();
+        scan_leaves_with(src, |p, _| pointers.push(p)).unwrap();
+        assert_eq!(vec!["/a/0", "/a/1/b", "/c/1"], pointers);
+
+        let mut n = 0;
+        let e = scan_leaves_with("[1, 2, x]", |_, _| n += 1).unwrap_err();
+        assert_eq!((2, "unexpected character 'x'"), (n, e.desc.as_str()));
+    }
+}
@@ -0,0 +1,843 @@
+use std::borrow::Cow;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::{ErrorKind, NumberInfo, OverflowPolicy, ParseError, ParseOptions, Reader, Value};
+
+/// Kind of a token from `tokenize` or `Tokens`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    BeginObject,
+    EndObject,
+    BeginArray,
+    EndArray,
+    Colon,
+    Comma,
+    String,
+    Number,
+    True,
+    False,
+    Null,
+    /// A run of space, tab, `\n` and `\r`.
+    Whitespace,
+    /// Text that is not a valid token, such as an unterminated string or a
+    /// stray character.
+    Error,
+}
+
+/// A token from `tokenize` or `Tokens`, `span` being its byte range in the
+/// input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+/// Split `input` into tokens for highlighting and the like. Never fails:
+/// invalid text becomes `TokenKind::Error` tokens and lexing picks up again
+/// after it. The spans cover the whole input in order, whitespace included.
+///
+/// Tokens are checked one by one, not how they are arranged, so `}1:`
+/// tokenizes fine; use `Reader::parse` for that.
+pub fn tokenize(input: &str) -> impl Iterator<Item = Token> + '_ {
+    Lexer {
+        r: Reader::new(input),
+    }
+}
+
+/// The tokens of `input` one at a time, without whitespace. Unlike
+/// `tokenize` this stops at the first invalid token, yielding the error the
+/// parser would report there.
+///
+/// This is the parser's own lexer: `Reader` reads the document through the
+/// same token scanners, so a token here is one to the parser too.
+pub struct Tokens<'a> {
+    lexer: Lexer<'a>,
+    done: bool,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(input: &'a str) -> Tokens<'a> {
+        Tokens::with_options(input, ParseOptions::default())
+    }
+
+    /// Tokens as `Reader::with_options` reads them: comments are part of
+    /// `TokenKind::Whitespace`, and single-quoted strings, hex numbers and
+    /// the like are accepted when enabled. An unquoted key is not a token,
+    /// the parser reads it only where a key goes.
+    pub fn with_options(input: &'a str, options: ParseOptions) -> Tokens<'a> {
+        Tokens {
+            lexer: Lexer {
+                r: Reader::with_options(input, options),
+            },
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<Token, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Token, ParseError>> {
+        if self.done {
+            return None;
+        }
+        loop {
+            match self.lexer.lex()? {
+                Ok(t) if t.kind == TokenKind::Whitespace => continue,
+                Ok(t) => return Some(Ok(t)),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+// The lexer the parser reads through: `peek_token` tells what comes next
+// and the `parse_*` scanners read one token of that kind. `Tokens` and
+// `tokenize` are this same lexer, with the values dropped.
+impl<'a> Reader<'a> {
+    // The kind of the token at the cursor, from its first character, `None`
+    // at the end of the input. Nothing is read; the scanner for the kind
+    // checks the rest. A character starting no token is `TokenKind::Error`.
+    pub(crate) fn peek_token(&mut self) -> Option<TokenKind> {
+        let ch = self.peek()?;
+        let comment = || matches!(self.context.as_bytes().get(1), Some(b'/' | b'*'));
+        Some(match ch {
+            '{' => TokenKind::BeginObject,
+            '}' => TokenKind::EndObject,
+            '[' => TokenKind::BeginArray,
+            ']' => TokenKind::EndArray,
+            ':' => TokenKind::Colon,
+            ',' => TokenKind::Comma,
+            'n' => TokenKind::Null,
+            't' => TokenKind::True,
+            'f' => TokenKind::False,
+            '-' | '0'..='9' => TokenKind::Number,
+            '+' if self.options.allow_plus_sign => TokenKind::Number,
+            'N' | 'I' if self.options.allow_nan => TokenKind::Number,
+            '/' if self.options.allow_comments && comment() => TokenKind::Whitespace,
+            c if self.is_quote(c) => TokenKind::String,
+            c if c.is_whitespace() => TokenKind::Whitespace,
+            _ => TokenKind::Error,
+        })
+    }
+
+    // Read the one-character token at the cursor, punctuation.
+    pub(crate) fn bump(&mut self) {
+        self.next();
+    }
+
+    pub(crate) fn parse_literal(&mut self, v: Value, literal: &str) -> Result<Value, ParseError> {
+        if self.context.len() < literal.len() {
+            return parse_value_error!(
+                self,
+                format!("literal '{}' expect '{}'", self.context, literal)
+            );
+        }
+
+        // not sliced by length, the input may hold a multibyte char there
+        if self.context.starts_with(literal) {
+            for _ in literal.chars() {
+                self.next();
+            }
+            return Ok(v);
+        }
+
+        parse_value_error!(self, format!("literal not eq {}", literal))
+    }
+
+    /// Parse an input holding a single number, and also report how it was
+    /// written, say to warn about more digits than an `f64` keeps.
+    pub fn parse_number_info(&mut self) -> Result<(Value, NumberInfo), ParseError> {
+        self.rewind();
+        self.parse_whitespace()?;
+        let mut info = NumberInfo::default();
+        let x = self.parse_number_parts(&mut info)?;
+        self.parse_whitespace()?;
+        self.expect_end()?;
+        Ok((x, info))
+    }
+
+    pub(crate) fn parse_number(&mut self) -> Result<Value, ParseError> {
+        self.parse_number_parts(&mut NumberInfo::default())
+    }
+
+    fn parse_number_parts(&mut self, info: &mut NumberInfo) -> Result<Value, ParseError> {
+        let orig = self.context;
+        let (row, column) = (self.row, self.column);
+
+        // sign
+        let negative = self.peek() == Some('-');
+        if negative || (self.options.allow_plus_sign && self.peek() == Some('+')) {
+            self.next();
+            if self.options.allow_nan && self.peek() == Some('I') {
+                let inf = if negative {
+                    f64::NEG_INFINITY
+                } else {
+                    f64::INFINITY
+                };
+                return self.parse_literal(Value::Number(inf), "Infinity");
+            }
+        }
+
+        if self.options.allow_hex_numbers
+            && (self.context.starts_with("0x") || self.context.starts_with("0X"))
+        {
+            return self.parse_hex(orig, (row, column), negative, info);
+        }
+
+        // integer, [1-9][0-9]+ | 0
+        match self.peek() {
+            Some('0') => {
+                self.next();
+                info.integer_digits = 1;
+            }
+            Some('1'..='9') => info.integer_digits = self.parse_digits(),
+            _ => {
+                return parse_value_error!(self, String::from("number integer expect '0..9'"));
+            }
+        }
+
+        // fractional part, '.' [0-9]+
+        if self.peek() == Some('.') {
+            info.has_fraction = true;
+            self.next();
+            if !self.peek().is_some_and(|d| d.is_ascii_digit()) {
+                return parse_value_error!(self, String::from("number fraction expect '0..9'"));
+            }
+            self.parse_digits();
+        }
+
+        // exponent part, ('e' | 'E') ['+' | '-'] [0-9]+
+        if let Some('e') | Some('E') = self.peek() {
+            info.has_exponent = true;
+            self.next();
+            if let Some('+') | Some('-') = self.peek() {
+                self.next();
+            }
+            if !self.peek().is_some_and(|d| d.is_ascii_digit()) {
+                return parse_value_error!(self, String::from("number exponent expect '0..9'"));
+            }
+            self.parse_digits();
+        }
+
+        self.expect_number_end()?;
+
+        let len = orig.len() - self.context.len();
+        if self.options.numbers_as_strings {
+            let literal = orig[..len].strip_prefix('+').unwrap_or(&orig[..len]);
+            return Ok(Value::String(literal.to_string()));
+        }
+        match f64::from_str(&orig[..len]) {
+            Ok(f) => {
+                self.note_number(f, &orig[..len], row, column);
+                self.number_in_range(f, &orig[..len], row, column)
+            }
+            Err(e) => parse_value_error!(self, format!("'{}' to number {} error", &orig[..len], e)),
+        }
+    }
+
+    // '0' ('x' | 'X') [0-9a-fA-F]+ after the sign of the number starting at
+    // `orig` and `start`, with `ParseOptions::allow_hex_numbers`.
+    fn parse_hex(
+        &mut self,
+        orig: &'a str,
+        (row, column): (usize, usize),
+        negative: bool,
+        info: &mut NumberInfo,
+    ) -> Result<Value, ParseError> {
+        self.next();
+        self.next();
+        let mut f = 0.0;
+        while let Some(d) = self.peek().and_then(|ch| ch.to_digit(16)) {
+            f = f * 16.0 + f64::from(d);
+            info.integer_digits += 1;
+            self.next();
+        }
+        if info.integer_digits == 0 {
+            return parse_value_error!(self, String::from("number hex expect '0..9a..f'"));
+        }
+        self.expect_number_end()?;
+        let len = orig.len() - self.context.len();
+        let f = if negative { -f } else { f };
+        self.number_in_range(f, &orig[..len], row, column)
+    }
+
+    // `123abc` is a typo in the number, not a number and then garbage
+    fn expect_number_end(&mut self) -> Result<(), ParseError> {
+        if let Some(ch) = self.peek().filter(|ch| ch.is_alphanumeric()) {
+            return parse_value_error!(
+                self,
+                ErrorKind::UnexpectedToken,
+                format!("unexpected character '{}' after number", ch)
+            );
+        }
+        Ok(())
+    }
+
+    // `f` read from `literal` at `row`:`column`, or what
+    // `ParseOptions::on_number_overflow` says if it is infinite.
+    fn number_in_range(
+        &self,
+        f: f64,
+        literal: &str,
+        row: usize,
+        column: usize,
+    ) -> Result<Value, ParseError> {
+        if !f.is_infinite() {
+            return Ok(Value::Number(f));
+        }
+        match self.options.on_number_overflow {
+            OverflowPolicy::Error => Err(ParseError {
+                kind: ErrorKind::NumberOutOfRange,
+                row,
+                column,
+                desc: format!("number '{}' out of range", literal),
+            }),
+            OverflowPolicy::Clamp => Ok(Value::Number(f64::MAX.copysign(f))),
+            OverflowPolicy::Raw => Ok(Value::Number(f)),
+        }
+    }
+
+    // Skip a run of digits, returning how many there were.
+    fn parse_digits(&mut self) -> usize {
+        let mut n = 0;
+        while let Some(d) = self.peek() {
+            if !d.is_ascii_digit() {
+                break;
+            }
+            self.next();
+            n += 1;
+        }
+        n
+    }
+
+    // A member key, a string or with `allow_unquoted_keys` an identifier.
+    pub(crate) fn parse_key(&mut self) -> Result<Cow<'a, str>, ParseError> {
+        let ident = |ch: char| ch.is_alphanumeric() || ch == '_' || ch == '$';
+        match self.peek() {
+            Some(ch) if self.options.allow_unquoted_keys && ident(ch) && !ch.is_ascii_digit() => {
+                let start = self.context;
+                while self.peek().is_some_and(ident) {
+                    self.next();
+                }
+                let key = &start[..start.len() - self.context.len()];
+                if self
+                    .options
+                    .max_string_length
+                    .is_some_and(|max| key.len() > max)
+                {
+                    return parse_value_error!(self, String::from("string exceeds maximum length"));
+                }
+                Ok(Cow::Borrowed(key))
+            }
+            Some(ch) if !self.is_quote(ch) => {
+                parse_value_error!(self, String::from("object key must be a string"))
+            }
+            _ => self.parse_str(),
+        }
+    }
+
+    // Whether `ch` opens a string.
+    pub(crate) fn is_quote(&self, ch: char) -> bool {
+        ch == '"' || (ch == '\'' && self.options.allow_single_quotes)
+    }
+
+    // The content of a string token, borrowed from the input until the first
+    // escape forces a copy.
+    pub(crate) fn parse_str(&mut self) -> Result<Cow<'a, str>, ParseError> {
+        let quote = match self.peek() {
+            Some(ch) if self.is_quote(ch) => ch,
+            _ => return parse_value_error!(self, String::from("string start char expect '\"'")),
+        };
+
+        self.next();
+        let start = self.context;
+        let mut owned: Option<String> = None;
+
+        while self.peek().is_some() {
+            if let Some(max) = self.options.max_string_length {
+                let len = owned
+                    .as_ref()
+                    .map_or(start.len() - self.context.len(), String::len);
+                if len > max {
+                    return parse_value_error!(self, String::from("string exceeds maximum length"));
+                }
+            }
+            match self.peek() {
+                Some(ch) if ch == quote => break,
+                Some('\\') => {
+                    let read = start.len() - self.context.len();
+                    let s = owned.get_or_insert_with(|| start[..read].to_string());
+                    let (row, column) = (self.row, self.column);
+                    match self.next() {
+                        Some('\"') => s.push('\"'),
+                        Some('\'') if quote == '\'' => s.push('\''),
+                        Some('\\') => s.push('\\'),
+                        Some('/') => s.push('/'),
+                        Some('b') => s.push('\x08'),
+                        Some('f') => s.push('\x0C'),
+                        Some('n') => s.push('\n'),
+                        Some('r') => s.push('\r'),
+                        Some('t') => s.push('\t'),
+                        Some('u') => s.push(self.parse_unicode_escape()?),
+                        Some(ch) => {
+                            return parse_value_error!(
+                                self,
+                                format!("string unknown escape '\\{}'", ch)
+                            );
+                        }
+                        None => {
+                            return Err(ParseError::new(
+                                row,
+                                column,
+                                "incomplete escape sequence at end of input",
+                            ));
+                        }
+                    }
+                }
+                // only structural whitespace is free, in a string a raw tab
+                // or line break must be escaped
+                Some(ch) if ch < '\x20' => {
+                    return parse_value_error!(
+                        self,
+                        format!("string control char '\\u{:04x}' must be escaped", ch as u32)
+                    );
+                }
+                Some(ch) => {
+                    if let Some(s) = owned.as_mut() {
+                        s.push(ch);
+                    }
+                }
+                _ => {}
+            }
+            self.next();
+        }
+
+        if self.peek() != Some(quote) {
+            return parse_value_error!(self, format!("string end char expect '{}'", quote));
+        }
+
+        let s = match owned {
+            Some(s) => Cow::Owned(s),
+            None => Cow::Borrowed(&start[..start.len() - self.context.len()]),
+        };
+        self.next();
+
+        Ok(s)
+    }
+
+    // 'u' hex hex hex hex, a high surrogate must be followed by an escaped
+    // low one. Lone surrogates can't be held by a `String` and are
+    // rejected. Leaves the last hex digit current, like the other escapes.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let hi = self.parse_hex4()?;
+        if !(0xD800..0xE000).contains(&hi) {
+            return Ok(char::from_u32(hi).unwrap());
+        }
+        if hi >= 0xDC00 || !self.context[1..].starts_with("\\u") {
+            return parse_value_error!(self, format!("string lone surrogate '\\u{:04x}'", hi));
+        }
+        self.next();
+        self.next();
+        let lo = self.parse_hex4()?;
+        if !(0xDC00..0xE000).contains(&lo) {
+            return parse_value_error!(self, format!("string lone surrogate '\\u{:04x}'", hi));
+        }
+        Ok(char::from_u32(0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)).unwrap())
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut n = 0;
+        for _ in 0..4 {
+            match self.next().and_then(|ch| ch.to_digit(16)) {
+                Some(d) => n = n * 16 + d,
+                None => {
+                    return parse_value_error!(
+                        self,
+                        String::from("string escape expect 4 hex digits")
+                    );
+                }
+            }
+        }
+        Ok(n)
+    }
+
+    pub(crate) fn parse_whitespace(&mut self) -> Result<(), ParseError> {
+        // for trivia: newlines seen in this run and whether the current
+        // line has only whitespace so far
+        let mut newlines = 0;
+        let mut blank = true;
+
+        while let Some(ch) = self.peek() {
+            match ch {
+                // RFC 8259 whitespace, form feed is not one. `\r\n` is one
+                // line break, counted at the '\n'.
+                '\t' | ' ' => {}
+                '\r' if self.context[1..].starts_with('\n') => {}
+                '\r' | '\n' => {
+                    if newlines > 0 && blank {
+                        self.pending.blank_lines += 1;
+                    }
+                    newlines += 1;
+                    blank = true;
+                }
+                '/' if self.options.allow_comments => {
+                    if !self.parse_comment()? {
+                        break;
+                    }
+                    blank = false;
+                    continue;
+                }
+                c if c.is_whitespace() && self.options.allow_extra_whitespace => {}
+                // named, it would otherwise show as a confusing blank
+                c if c.is_whitespace() => {
+                    let desc = format!("unexpected whitespace-like character U+{:04X}", c as u32);
+                    let e = self.error(&desc);
+                    // recovery skips it, so an open container still sees
+                    // its end
+                    if !self.recovering() {
+                        return Err(e);
+                    }
+                    self.push_error(e);
+                }
+                _ => break,
+            }
+            self.next();
+        }
+        Ok(())
+    }
+
+    // '//' to end of line or '/*' to '*/', false if not at a comment. A
+    // block comment must be closed.
+    fn parse_comment(&mut self) -> Result<bool, ParseError> {
+        let start = self.context;
+        let (row, column) = (self.row, self.column);
+        let block = match start.as_bytes().get(1) {
+            Some(b'/') => false,
+            Some(b'*') => true,
+            _ => return Ok(false),
+        };
+        self.next();
+        self.next();
+
+        let mut closed = !block;
+        while let Some(ch) = self.peek() {
+            if !block && (ch == '\n' || ch == '\r') {
+                break;
+            }
+            if block && self.context.starts_with("*/") {
+                self.next();
+                self.next();
+                closed = true;
+                break;
+            }
+            self.next();
+        }
+        if !closed {
+            return Err(ParseError::new(row, column, "block comment not closed"));
+        }
+
+        if self.trivia.is_some() {
+            let len = start.len() - self.context.len();
+            let comment = &start[..len];
+            self.pending.comments.push(comment.to_string());
+        }
+        Ok(true)
+    }
+
+    // null, a boolean or a number, and the errors for what starts no value
+    pub(crate) fn parse_scalar(&mut self) -> Result<Value, ParseError> {
+        let ch = self.peek();
+        match self.peek_token() {
+            Some(TokenKind::Null) => self.parse_literal(Value::Null, "null"),
+            Some(TokenKind::False) => self.parse_literal(Value::Bool(false), "false"),
+            Some(TokenKind::True) => self.parse_literal(Value::Bool(true), "true"),
+            Some(TokenKind::Number) => match ch {
+                Some('N') => self.parse_literal(Value::Number(f64::NAN), "NaN"),
+                Some('I') => self.parse_literal(Value::Number(f64::INFINITY), "Infinity"),
+                _ => self.parse_number(),
+            },
+            Some(TokenKind::String) => self.parse_str().map(|s| Value::String(s.into_owned())),
+            Some(
+                TokenKind::Comma | TokenKind::Colon | TokenKind::EndArray | TokenKind::EndObject,
+            ) => parse_value_error!(
+                self,
+                ErrorKind::UnexpectedToken,
+                format!("unexpected '{}' — expected a value", ch.unwrap_or_default())
+            ),
+            Some(_) => parse_value_error!(
+                self,
+                ErrorKind::UnexpectedToken,
+                format!("unexpected character '{}'", ch.unwrap_or_default())
+            ),
+            None => parse_value_error!(self, String::from("unexpected end of input")),
+        }
+    }
+}
+
+struct Lexer<'a> {
+    r: Reader<'a>,
+}
+
+impl Lexer<'_> {
+    // The next token, or the error for the invalid text at the cursor. On
+    // error the cursor is left inside that text.
+    fn lex(&mut self) -> Option<Result<Token, ParseError>> {
+        let r = &mut self.r;
+        let start = r.offset();
+        let kind = match r.peek_token()? {
+            TokenKind::Whitespace => r.parse_whitespace().map(|()| TokenKind::Whitespace),
+            TokenKind::String => r.parse_str().map(|_| TokenKind::String),
+            kind @ (TokenKind::BeginObject
+            | TokenKind::EndObject
+            | TokenKind::BeginArray
+            | TokenKind::EndArray
+            | TokenKind::Colon
+            | TokenKind::Comma) => {
+                r.bump();
+                Ok(kind)
+            }
+            kind => r.parse_scalar().map(|_| kind),
+        };
+        Some(kind.map(|kind| Token {
+            kind,
+            span: start..r.offset(),
+        }))
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let start = self.r.offset();
+        let kind = match self.lex()? {
+            Ok(t) => return Some(t),
+            Err(_) => TokenKind::Error,
+        };
+        let r = &mut self.r;
+        r.context = &r.origin[error_end(r.origin, start)..];
+        Some(Token {
+            kind,
+            span: start..r.offset(),
+        })
+    }
+}
+
+// Where an invalid token at `start` ends: a string at its closing quote or
+// the end of the line, anything else before the next whitespace,
+// punctuation or quote.
+fn error_end(input: &str, start: usize) -> usize {
+    let mut chars = input[start..].char_indices();
+    if let Some((_, '"')) = chars.next() {
+        while let Some((i, ch)) = chars.next() {
+            match ch {
+                '"' => return start + i + 1,
+                '\n' | '\r' => return start + i,
+                '\\' => {
+                    if let Some((i, '\n')) | Some((i, '\r')) = chars.next() {
+                        return start + i;
+                    }
+                }
+                _ => {}
+            }
+        }
+        return input.len();
+    }
+    for (i, ch) in chars {
+        if let ' ' | '\t' | '\n' | '\r' | '{' | '}' | '[' | ']' | ':' | ',' | '"' = ch {
+            return start + i;
+        }
+    }
+    input.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    // kinds and texts, checking the spans cover `src` in order
+    fn lex(src: &str) -> Vec<(TokenKind, &str)> {
+        let mut end = 0;
+        let mut tokens = Vec::new();
+        for t in tokenize(src) {
+            assert_eq!(end, t.span.start, "{:?}", src);
+            assert!(t.span.start < t.span.end, "{:?}", src);
+            end = t.span.end;
+            tokens.push((t.kind, &src[t.span]));
+        }
+        assert_eq!(src.len(), end);
+        tokens
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let src = "{\"a\": [1.5, true],\n \"日\":null}";
+        let kinds: Vec<_> = lex(src).into_iter().map(|t| t.0).collect();
+        assert_eq!(
+            vec![
+                BeginObject,
+                String,
+                Colon,
+                Whitespace,
+                BeginArray,
+                Number,
+                Comma,
+                Whitespace,
+                True,
+                EndArray,
+                Comma,
+                Whitespace,
+                String,
+                Colon,
+                Null,
+                EndObject
+            ],
+            kinds
+        );
+        let tokens: Vec<_> = tokenize(src).collect();
+        assert_eq!(1..4, tokens[1].span);
+        assert_eq!(7..10, tokens[5].span);
+        assert_eq!(
+            (String, "\"日\""),
+            (tokens[12].kind, &src[tokens[12].span.clone()])
+        );
+
+        assert_eq!(
+            vec![
+                (Whitespace, " "),
+                (EndArray, "]"),
+                (Number, "1"),
+                (Colon, ":")
+            ],
+            lex(" ]1:")
+        );
+        assert!(lex("").is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_error() {
+        let src = "{\"a\": \"oops,\n \"b\": 1}";
+        assert_eq!(
+            vec![
+                (BeginObject, "{"),
+                (String, "\"a\""),
+                (Colon, ":"),
+                (Whitespace, " "),
+                (Error, "\"oops,"),
+                (Whitespace, "\n "),
+                (String, "\"b\""),
+                (Colon, ":"),
+                (Whitespace, " "),
+                (Number, "1"),
+                (EndObject, "}")
+            ],
+            lex(src)
+        );
+
+        assert_eq!(
+            vec![
+                (BeginArray, "["),
+                (Error, "\"a\\q\""),
+                (Comma, ","),
+                (Error, "tru"),
+                (Comma, ","),
+                (Error, "@@"),
+                (Whitespace, " "),
+                (Error, "-"),
+                (EndArray, "]"),
+                (Error, "\"open")
+            ],
+            lex("[\"a\\q\",tru,@@ -]\"open")
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        let src = "{\"a\": [1.5, true],\n \"日\":null}";
+        let tokens: Vec<Token> = Tokens::new(src).collect::<Result<_, _>>().unwrap();
+        let expect = vec![
+            (BeginObject, 0..1),
+            (String, 1..4),
+            (Colon, 4..5),
+            (BeginArray, 6..7),
+            (Number, 7..10),
+            (Comma, 10..11),
+            (True, 12..16),
+            (EndArray, 16..17),
+            (Comma, 17..18),
+            (String, 20..25),
+            (Colon, 25..26),
+            (Null, 26..30),
+            (EndObject, 30..31),
+        ];
+        let expect: Vec<Token> = expect
+            .into_iter()
+            .map(|(kind, span)| Token { kind, span })
+            .collect();
+        assert_eq!(expect, tokens);
+        let lossless: Vec<Token> = tokenize(src).filter(|t| t.kind != Whitespace).collect();
+        assert_eq!(lossless, tokens);
+
+        assert_eq!(0, Tokens::new(" \n ").count());
+    }
+
+    #[test]
+    fn test_tokens_error() {
+        let mut tokens = Tokens::new("[1,\n @, 2]");
+        assert_eq!(BeginArray, tokens.next().unwrap().unwrap().kind);
+        assert_eq!(Number, tokens.next().unwrap().unwrap().kind);
+        assert_eq!(Comma, tokens.next().unwrap().unwrap().kind);
+        let e = tokens.next().unwrap().unwrap_err();
+        assert_eq!((2, 2), (e.row, e.column));
+        assert_eq!("unexpected character '@'", e.desc);
+        assert!(tokens.next().is_none());
+
+        let src = "[\"a\\q\"]";
+        let e = Tokens::new(src).find_map(Result::err).unwrap();
+        assert_eq!(Reader::new(src).parse().unwrap_err(), e);
+    }
+
+    #[test]
+    fn test_tokens_with_options() {
+        let opts = || {
+            ParseOptions::new()
+                .allow_comments(true)
+                .allow_single_quotes(true)
+                .allow_hex_numbers(true)
+                .allow_nan(true)
+        };
+        let src = "[/* a */ 'x', 0x1F, NaN, -Infinity // end\n]";
+        let kinds: Vec<TokenKind> = Tokens::with_options(src, opts())
+            .map(|t| t.unwrap().kind)
+            .collect();
+        assert_eq!(
+            vec![BeginArray, String, Comma, Number, Comma, Number, Comma, Number, EndArray],
+            kinds
+        );
+        assert!(Reader::with_options(src, opts()).parse().is_ok());
+
+        // the same input, strict
+        let e = Tokens::new(src).find_map(Result::err).unwrap();
+        assert_eq!("unexpected character '/'", e.desc);
+    }
+
+    #[test]
+    fn test_tokenize_garbage() {
+        let mut seed: u32 = 7;
+        let bytes: Vec<u8> = (0..4096)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        let src = std::string::String::from_utf8_lossy(&bytes);
+        let tokens = lex(&src);
+        assert!(tokens.iter().any(|t| t.0 == Error));
+    }
+}
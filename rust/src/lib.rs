@@ -1,10 +1,141 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
-use std::str::FromStr;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::Instant;
 
-#[derive(Debug)]
+use handler::Sink;
+
+// An error at the reader's position. Use `ParseError::new` outside the
+// parser.
+macro_rules! parse_value_error {
+    ($v:expr, $desc:expr) => {
+        parse_value_error!($v, ErrorKind::Syntax, $desc)
+    };
+    ($v:expr, $kind:expr, $desc:expr) => {
+        Err(ParseError::with_kind($kind, $v.row, $v.column, $desc))
+    };
+}
+
+#[cfg(feature = "tokio")]
+mod async_read;
+mod batch;
+mod builder;
+mod coerce;
+mod convert;
+mod encoding;
+mod equivalent;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fingerprint;
+mod handler;
+mod jsonpath;
+mod leaves;
+mod lexer;
+mod lines;
+mod map;
+mod notes;
+mod order;
+mod patch;
+mod pointer;
+mod push;
+mod records;
+mod report;
+mod select;
+mod ser;
+mod shared;
+mod span;
+mod stream;
+mod summary;
+mod trivia;
+mod value_ref;
+
+#[cfg(feature = "tokio")]
+pub use async_read::parse_async;
+pub use batch::{parse_batch, parse_batch_with};
+pub use builder::{ArrayBuilder, BuildError, ObjectBuilder, TreeBuilder};
+pub use convert::{
+    field, from_reader_typed, parse_typed, ConvertError, FromValue, TypedParseError,
+};
+pub use encoding::{from_slice_with_encoding, Encoding};
+pub use equivalent::EqOptions;
+pub use handler::{parse_with_handler, Handler, HandlerError};
+pub use jsonpath::JsonPathError;
+pub use leaves::{scan_leaves, scan_leaves_with, ScalarValue};
+pub use lexer::{tokenize, Token, TokenKind, Tokens};
+pub use lines::{parse_lines, parse_lines_collect, JsonLines, LineParseError};
+pub use map::{Entry, Map};
+pub use notes::Note;
+pub use patch::create_patch;
+pub use pointer::{CreateMode, PointerError};
+pub use push::PushParser;
+pub use records::ShapeError;
+pub use ser::{CompactJson, Formatter, Newline, NonFinite, PrettyJson, SolidusMode, WriteOptions};
+pub use shared::SharedValue;
+pub use span::{Span, SpanMap};
+pub use stream::ArrayStream;
+pub use summary::SummaryOptions;
+pub use trivia::{Leading, Trivia};
+pub use value_ref::ValueRef;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input is not valid JSON.
+    Syntax,
+    /// A value was expected but a structural or foreign character came.
+    UnexpectedToken,
+    /// The progress callback asked to stop.
+    Cancelled,
+    /// Reading the input failed, the position is 0:0.
+    Io,
+    /// The input ran past `ParseOptions::max_bytes`.
+    LimitExceeded,
+    /// `ParseOptions::deadline` passed during the parse.
+    DeadlineExceeded,
+    /// The document is a scalar and `ParseOptions::require_container_root`
+    /// is set, the position is 1:1.
+    InvalidRootType,
+    /// A number literal too large for `f64`, at the start of the literal.
+    NumberOutOfRange,
+    /// A key repeated in one object under `DuplicateKeyPolicy::Error`, at
+    /// the second occurrence. Boxed to keep `ParseError` small, it passes
+    /// through every level of the recursive parser.
+    DuplicateKey(Box<DuplicateKey>),
+    /// A key `ParseOptions::key_validator` rejected, at the key.
+    InvalidKey(Box<InvalidKey>),
+}
+
+/// The details of `ErrorKind::DuplicateKey`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    pub key: String,
+    pub first: Position,
+    pub second: Position,
+}
+
+/// The details of `ErrorKind::InvalidKey`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidKey {
+    pub key: String,
+    pub position: Position,
+}
+
+/// A place in the input, both 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub row: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParseError {
+    pub kind: ErrorKind,
     pub row: usize,
     pub column: usize,
     pub desc: String,
@@ -18,483 +149,2513 @@ impl fmt::Display for ParseError {
 
 impl Error for ParseError {}
 
-// #[macro_export]
-macro_rules! parse_value_error {
-    ($v:expr, $desc:expr) => {
-        Err(ParseError {
-            row: $v.row,
-            column: $v.column,
-            desc: $desc,
-        })
-    };
+impl ParseError {
+    /// An `ErrorKind::Syntax` error at `row`:`column`, both 1-based, for
+    /// code reporting problems in the same shape as the parser.
+    pub fn new<S: Into<String>>(row: usize, column: usize, desc: S) -> ParseError {
+        ParseError::with_kind(ErrorKind::Syntax, row, column, desc)
+    }
+
+    pub fn with_kind<S: Into<String>>(
+        kind: ErrorKind,
+        row: usize,
+        column: usize,
+        desc: S,
+    ) -> ParseError {
+        ParseError {
+            kind,
+            row,
+            column,
+            desc: desc.into(),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[must_use]
+#[derive(Clone)]
 pub enum Value {
     Null,
-    False,
-    True,
-    Number { v: f64 },
-    String { v: String },
-    Array { v: Vec<Value> },
-    Object { v: HashMap<String, Value> },
+    Bool(bool),
+    /// Numbers compare numerically, so `-0.0 == 0.0`. NaN is equal to NaN
+    /// to keep `Eq` and `Hash` lawful; it can only come from parsing with
+    /// `ParseOptions::allow_nan`.
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Map),
 }
 
 fn eq_value(lhs: &Value, rhs: &Value) -> bool {
     match lhs {
         Value::Null => eq_null(rhs),
-        Value::False => eq_false(rhs),
-        Value::True => eq_true(rhs),
-        Value::Number { v } => eq_number(v, rhs),
-        Value::String { v } => eq_string(v, rhs),
-        Value::Array { v } => eq_array(v, rhs),
-        Value::Object { v } => eq_object(v, rhs),
+        Value::Bool(b) => eq_bool(*b, rhs),
+        Value::Number(v) => eq_number(v, rhs),
+        Value::String(v) => eq_string(v, rhs),
+        Value::Array(v) => eq_array(v, rhs),
+        Value::Object(v) => eq_object(v, rhs),
     }
 }
 
 fn eq_null(v: &Value) -> bool {
-    match v {
-        Value::Null => true,
-        _ => false,
-    }
+    matches!(v, Value::Null)
 }
 
-fn eq_false(v: &Value) -> bool {
-    match v {
-        Value::False => true,
-        _ => false,
-    }
+fn eq_bool(b: bool, v: &Value) -> bool {
+    matches!(v, Value::Bool(x) if *x == b)
 }
 
-fn eq_true(v: &Value) -> bool {
+fn eq_number(f: &f64, v: &Value) -> bool {
     match v {
-        Value::True => true,
+        Value::Number(v) => same_number(*v, *f),
         _ => false,
     }
 }
 
-fn eq_number(f: &f64, v: &Value) -> bool {
-    match v {
-        Value::Number { v } => v.eq(f),
-        _ => false,
-    }
+// Number equality for `Eq`: `-0.0 == 0.0` and NaN equal to NaN.
+pub(crate) fn same_number(a: f64, b: f64) -> bool {
+    a == b || (a.is_nan() && b.is_nan())
 }
 
 fn eq_string(s: &String, v: &Value) -> bool {
     match v {
-        Value::String { v } => v.eq(s),
+        Value::String(v) => v.eq(s),
         _ => false,
     }
 }
 
 fn eq_array(arr: &Vec<Value>, v: &Value) -> bool {
     match v {
-        Value::Array { v } => v.eq(arr),
+        Value::Array(v) => v.eq(arr),
         _ => false,
     }
 }
 
-fn eq_object(obj: &HashMap<String, Value>, v: &Value) -> bool {
+fn eq_object(obj: &Map, v: &Value) -> bool {
     match v {
-        Value::Object { v } => v.eq(obj),
+        Value::Object(v) => v.eq(obj),
         _ => false,
     }
 }
 
+/// Strict structural equality: `{"a":1}` and `{"a":1,"b":null}` differ.
+/// `Value::equivalent` compares with `EqOptions` instead.
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         eq_value(self, other)
     }
 }
 
-pub fn type_name(v: Value) -> Option<&'static str> {
-    match v {
-        Value::Null => Some("null"),
-        Value::False => Some("false"),
-        Value::True => Some("true"),
-        Value::Number { v: _ } => Some("number"),
-        Value::String { v: _ } => Some("string"),
-        Value::Array { v: _ } => Some("array"),
-        Value::Object { v: _ } => Some("object"),
-    }
-}
+impl Eq for Value {}
 
-fn value_string(v: Value) -> Option<String> {
-    match v {
-        Value::String { v } => Some(v),
-        _ => None,
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Value::Null => 0u8.hash(state),
+            Value::Bool(b) => {
+                1u8.hash(state);
+                b.hash(state);
+            }
+            Value::Number(v) => {
+                3u8.hash(state);
+                number_bits(*v).hash(state);
+            }
+            Value::String(v) => {
+                4u8.hash(state);
+                v.hash(state);
+            }
+            Value::Array(v) => {
+                5u8.hash(state);
+                v.hash(state);
+            }
+            Value::Object(v) => {
+                6u8.hash(state);
+                let members = v.visible();
+                members.len().hash(state);
+                // members hashed independently and summed, key order free
+                let mut sum = 0u64;
+                for (k, v) in members {
+                    let mut h = DefaultHasher::new();
+                    k.hash(&mut h);
+                    v.hash(&mut h);
+                    sum = sum.wrapping_add(h.finish());
+                }
+                sum.hash(state);
+            }
+        }
     }
 }
 
-pub struct Reader<'a> {
-    context: &'a str,
-    origin: &'a str,
-    row: usize,
-    column: usize,
+// Without this a deeply nested value, which `TreeBuilder` or a parse with a
+// raised `max_depth` can make, would overflow the stack dropping itself
+// recursively. Nested containers are emptied onto a heap stack instead.
+impl Drop for Value {
+    fn drop(&mut self) {
+        let mut stack = match self {
+            Value::Array(arr) if arr.iter().any(Value::is_container) => std::mem::take(arr),
+            Value::Object(m) if m.iter().any(|(_, x)| x.is_container()) => {
+                let mut stack = Vec::new();
+                m.take_values(&mut stack);
+                stack
+            }
+            _ => return,
+        };
+        while let Some(mut v) = stack.pop() {
+            match &mut v {
+                Value::Array(arr) => stack.append(arr),
+                Value::Object(m) => m.take_values(&mut stack),
+                _ => {}
+            }
+        }
+    }
 }
 
-impl<'a> Reader<'a> {
-    pub fn new(c: &'a str) -> Reader {
-        Reader {
-            context: c,
-            origin: c,
-            row: 1,
-            column: 1,
+// Stable for test failures: object members sorted by key and scalars kept
+// on one line, also with `{:#?}`.
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("Null"),
+            Value::Bool(b) => write!(f, "Bool({})", b),
+            Value::Number(v) => write!(f, "Number({:?})", v),
+            Value::String(v) => write!(f, "String({:?})", v),
+            Value::Array(v) => {
+                f.write_str("Array(")?;
+                f.debug_list().entries(v).finish()?;
+                f.write_str(")")
+            }
+            Value::Object(v) => {
+                f.write_str("Object(")?;
+                v.fmt(f)?;
+                f.write_str(")")
+            }
         }
     }
+}
 
-    pub fn parse(&mut self) -> Result<Value, ParseError> {
-        self.context = self.origin;
-        let x = self.parse_element()?;
-        if self.context.len() != 0 {
-            return parse_value_error!(self, format!("value not finished '{}'", self.context));
-        }
-        Ok(x)
+// Like `Value::extend_array`, nothing happens unless `self` is an array.
+impl Extend<Value> for Value {
+    fn extend<I: IntoIterator<Item = Value>>(&mut self, iter: I) {
+        self.extend_array(iter);
     }
+}
 
-    fn parse_literal(&mut self, v: Value, literal: &str) -> Result<Value, ParseError> {
-        if self.context.len() < literal.len() {
-            return parse_value_error!(
-                self,
-                format!("literal '{}' expect '{}'", self.context, literal)
-            );
-        }
+// Bit pattern agreeing with `eq_number`: one zero and one NaN.
+fn number_bits(f: f64) -> u64 {
+    if f == 0.0 {
+        0.0f64.to_bits()
+    } else if f.is_nan() {
+        f64::NAN.to_bits()
+    } else {
+        f.to_bits()
+    }
+}
 
-        let l = &self.context[..literal.len()];
-        if literal.eq(l) {
-            self.context = &self.context[literal.len()..];
-            return Ok(v);
-        }
+/// One step from a parent container to a child.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
 
-        parse_value_error!(self, format!("literal not eq {}", literal))
+impl Value {
+    /// Visit every node depth-first, parents before children, with the path
+    /// from `self`. `self` itself is visited with an empty path.
+    pub fn walk<F: FnMut(&[PathSegment], &Value)>(&self, f: &mut F) {
+        let mut path = Vec::new();
+        walk_value(self, &mut path, f);
     }
 
-    fn parse_number(&mut self) -> Result<Value, ParseError> {
-        let orig = self.context;
-
-        // sign
-        if self.peek() == Some('-') {
-            self.next();
-        }
+    /// Replace every string value below and including `self` with `f` of
+    /// it, and with `keys` every member key too. Where `f` maps two keys of
+    /// an object to the same string only one member is kept, unless the map
+    /// is in document mode.
+    pub fn map_strings<F: FnMut(&str) -> String>(&mut self, keys: bool, mut f: F) {
+        map_strings(self, keys, &mut f);
+    }
 
-        // integer, [1-9][0-9]+ | 0
-        if let Some(ch) = self.peek() {
-            match ch {
-                '0' => {
-                    self.next();
-                }
-                '1'..='9' => {
-                    while let Some(d) = self.peek() {
-                        if !d.is_ascii_digit() {
-                            break;
-                        }
-                        self.next();
-                    }
-                }
-                _ => {
-                    return parse_value_error!(self, String::from("number integer expect '0..9'"));
-                }
+    /// Estimated heap bytes owned by `self` and everything below it, by
+    /// capacity rather than length, not counting `self`'s own
+    /// `size_of::<Value>()`. Keys shared with `intern-keys` are counted in
+    /// every object holding them.
+    pub fn deep_size(&self) -> usize {
+        match self {
+            Value::String(s) => s.capacity(),
+            Value::Array(arr) => {
+                let own = arr.capacity() * std::mem::size_of::<Value>();
+                own + arr.iter().map(Value::deep_size).sum::<usize>()
             }
+            Value::Object(m) => m.heap_size() + m.iter().map(|(_, x)| x.deep_size()).sum::<usize>(),
+            _ => 0,
         }
+    }
 
-        // fractional part
-        if self.peek() == Some('.') {
-            while let Some(d) = self.next() {
-                if !d.is_ascii_digit() {
-                    break;
-                }
+    /// How many nodes of each type `self` holds, itself included, keyed by
+    /// the names `type_name` gives. Types not present are left out.
+    pub fn count_by_type(&self) -> HashMap<&'static str, usize> {
+        let mut counts = HashMap::new();
+        let mut stack = vec![self];
+        while let Some(v) = stack.pop() {
+            *counts.entry(convert::type_name_ref(v)).or_insert(0) += 1;
+            match v {
+                Value::Array(arr) => stack.extend(arr),
+                Value::Object(m) => stack.extend(m.iter().map(|(_, x)| x)),
+                _ => {}
             }
         }
+        counts
+    }
 
-        // exponent part
-        match self.peek() {
-            Some('e') | Some('E') => self.next(),
-            Some('+') | Some('-') => self.next(),
-            _ => self.peek(),
-        };
-        while let Some(d) = self.peek() {
-            if !d.is_ascii_digit() {
-                break;
-            }
-            self.next();
+    /// True for arrays and objects.
+    pub fn is_container(&self) -> bool {
+        matches!(self, Value::Array(_) | Value::Object(_))
+    }
+
+    /// True for null, booleans, numbers and strings.
+    pub fn is_scalar(&self) -> bool {
+        !self.is_container()
+    }
+
+    /// True for a number holding `-0.0`. It compares equal to `0.0` and is
+    /// written as `0`.
+    pub fn is_negative_zero(&self) -> bool {
+        match self {
+            Value::Number(v) => *v == 0.0 && v.is_sign_negative(),
+            _ => false,
         }
+    }
 
-        let len = orig.len() - self.context.len();
-        match f64::from_str(&orig[..len]) {
-            Ok(f) => Ok(Value::Number { v: f }),
-            Err(e) => parse_value_error!(self, format!("'{}' to number {} error", &orig[..len], e)),
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
         }
     }
 
-    fn parse_string(&mut self) -> Result<Value, ParseError> {
-        if self.peek() != Some('\"') {
-            return parse_value_error!(self, String::from("string start char expect '\"'"));
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
         }
+    }
 
-        self.next();
-        let mut s = String::new();
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(v) => Some(v),
+            _ => None,
+        }
+    }
 
-        while self.peek() != None {
-            match self.peek() {
-                Some('\"') => break,
-                Some('\\') => match self.next() {
-                    Some('\"') => s.push('\"'),
-                    Some('\\') => s.push('\\'),
-                    Some('/') => s.push('/'),
-                    // Some('b') => s.push('\b'), // TODO
-                    // Some('f') => s.push('\f'),
-                    Some('n') => s.push('\n'),
-                    Some('r') => s.push('\r'),
-                    Some('t') => s.push('\t'),
-                    _ => break, // TODO 4 hex digits.
-                },
-                Some(ch) => {
-                    s.push(ch);
-                }
-                _ => {}
-            }
-            self.next();
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::Array(v) => Some(v),
+            _ => None,
         }
+    }
 
-        if self.peek() != Some('\"') {
-            return parse_value_error!(self, String::from("string end char expect '\"'"));
+    pub fn as_object(&self) -> Option<&Map> {
+        match self {
+            Value::Object(m) => Some(m),
+            _ => None,
         }
+    }
 
-        self.next();
+    pub fn as_object_mut(&mut self) -> Option<&mut Map> {
+        match self {
+            Value::Object(m) => Some(m),
+            _ => None,
+        }
+    }
 
-        Ok(Value::String { v: s })
+    /// Text of a scalar for lenient extraction: the string itself, a number
+    /// as it would be serialized, `"true"` or `"false"`. `None` for `null`
+    /// and containers.
+    pub fn coerce_to_string(&self) -> Option<String> {
+        match self {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(_) | Value::Bool(_) => Some(self.to_string()),
+            _ => None,
+        }
     }
 
-    // '[' ws | elements ']'
-    fn parse_array(&mut self) -> Result<Value, ParseError> {
-        self.next();
+    /// The number as `i64` if it is integral and in range, so nothing is
+    /// silently truncated.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            // i64::MAX as f64 rounds up to 2^63, which is out of range
+            Value::Number(v)
+                if v.fract() == 0.0 && *v >= i64::MIN as f64 && *v < i64::MAX as f64 =>
+            {
+                Some(*v as i64)
+            }
+            _ => None,
+        }
+    }
 
-        let mut arr: Vec<Value> = Vec::new();
-        self.parse_whitespace();
-        if self.peek() != Some(']') {
-            self.parse_elements(&mut arr)?;
+    /// Like `as_i64`, for non-negative integers up to `u64::MAX`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(v) if v.fract() == 0.0 && *v >= 0.0 && *v < u64::MAX as f64 => {
+                Some(*v as u64)
+            }
+            _ => None,
         }
+    }
 
-        if self.peek() != Some(']') {
-            return parse_value_error!(self, String::from("array end char expect ']'"));
+    /// The string without copying it, or `self` back if it is not one;
+    /// `.ok()` when only the string matters.
+    pub fn into_string(mut self) -> Result<String, Value> {
+        match &mut self {
+            Value::String(s) => Ok(std::mem::take(s)),
+            _ => Err(self),
         }
-        self.next();
+    }
 
-        Ok(Value::Array { v: arr })
+    /// The elements, or `self` back if it is not an array.
+    pub fn into_array(mut self) -> Result<Vec<Value>, Value> {
+        match &mut self {
+            Value::Array(v) => Ok(std::mem::take(v)),
+            _ => Err(self),
+        }
     }
 
-    // '{ ws | members '}'
-    fn parse_object(&mut self) -> Result<Value, ParseError> {
-        self.next(); // '{'
+    /// The members, or `self` back if it is not an object. `HashMap::from`
+    /// turns them into a plain map.
+    pub fn into_object(mut self) -> Result<Map, Value> {
+        match &mut self {
+            Value::Object(m) => Ok(std::mem::take(m)),
+            _ => Err(self),
+        }
+    }
 
-        let mut members = HashMap::new();
+    /// `into_array`, under the name the `try_into_*` family uses.
+    pub fn try_into_array(self) -> Result<Vec<Value>, Value> {
+        self.into_array()
+    }
 
-        self.parse_whitespace();
-        if self.peek() != Some('}') {
-            self.parse_members(&mut members)?;
-        }
+    /// The members as a plain map, or `self` back if it is not an object. A
+    /// later duplicate key wins, as in `get`.
+    pub fn try_into_object(self) -> Result<HashMap<String, Value>, Value> {
+        self.into_object().map(HashMap::from)
+    }
 
-        if self.peek() != Some('}') {
-            return parse_value_error!(self, String::from("object end char expect '}'"));
+    /// Move the string out, leaving `null` in its place. Other types are
+    /// left alone and give `None`.
+    pub fn take_string(&mut self) -> Option<String> {
+        match self {
+            Value::String(_) => std::mem::replace(self, Value::Null).into_string().ok(),
+            _ => None,
         }
-        self.next();
-
-        Ok(Value::Object { v: members })
     }
 
-    fn parse_whitespace(&mut self) {
-        let mut p = self.context;
-        for ch in p.chars() {
-            match ch {
-                '\t' | '\x0C' | ' ' => {
-                    p = &p[1..];
-                    self.column += 1;
-                }
-                '\n' | '\r' => {
-                    p = &p[1..];
-                    self.column = 1;
-                    self.row += 1;
-                }
-                _ => break,
+    /// Push the values of `iter` onto an array. Returns false, dropping
+    /// `iter` unread, if `self` is not an array.
+    pub fn extend_array<I: IntoIterator<Item = Value>>(&mut self, iter: I) -> bool {
+        match self {
+            Value::Array(arr) => {
+                arr.extend(iter);
+                true
             }
+            _ => false,
         }
-        self.context = p;
     }
 
-    // element ',' element
-    fn parse_elements(&mut self, arr: &mut Vec<Value>) -> Result<(), ParseError> {
-        let elem = self.parse_element()?;
-        arr.push(elem);
-        if self.peek() == Some(',') {
-            self.next();
-            return self.parse_elements(arr);
+    /// Move the elements or members of `other` to the end of `self`,
+    /// leaving `other` empty, without cloning. A member of `other` replaces
+    /// one with the same key, except in document mode. Returns false and
+    /// changes nothing unless both are arrays or both are objects.
+    pub fn append(&mut self, other: &mut Value) -> bool {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) => a.append(b),
+            (Value::Object(a), Value::Object(b)) => a.append(b),
+            _ => return false,
         }
-        Ok(())
+        true
     }
 
-    // ws value ws
-    fn parse_element(&mut self) -> Result<Value, ParseError> {
-        self.parse_whitespace();
-        let v = self.parse_value()?;
-        self.parse_whitespace();
+    /// Member `key` of an object, `None` for other types.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(v) => v.get(key),
+            _ => None,
+        }
+    }
 
-        Ok(v)
+    /// Member `key` of an object, or `default` when it is absent or `self`
+    /// is not an object. `&Value::Null` makes a handy default.
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a Value) -> &'a Value {
+        self.get(key).unwrap_or(default)
     }
 
-    // ws string ws ':' element
-    fn parse_member(&mut self) -> Result<(Value, Value), ParseError> {
-        self.parse_whitespace();
-        let k = self.parse_string()?;
-        self.parse_whitespace();
-        if self.peek() != Some(':') {
-            return parse_value_error!(self, String::from("member expect ':'"));
+    /// Look up a value by JSON Pointer (RFC 6901), e.g. `/servers/0/port`.
+    /// The empty pointer refers to the whole document.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        if !pointer.starts_with('/') {
+            return None;
         }
-        self.next();
-        let v = self.parse_element()?;
 
-        Ok((k, v))
+        let mut target = self;
+        for token in pointer[1..].split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            target = match target {
+                Value::Object(v) => v.get(&token)?,
+                Value::Array(v) => v.get(pointer_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
     }
 
-    // member ',' members
-    fn parse_members(&mut self, objs: &mut HashMap<String, Value>) -> Result<(), ParseError> {
-        let (k, v) = self.parse_member()?;
-        let key = value_string(k).unwrap();
-        objs.insert(key, v);
-
-        if self.peek() == Some(',') {
-            self.next();
-            return self.parse_members(objs);
+    /// The member `key`, inserted as `null` if absent. A `null` `self`
+    /// becomes an empty object first, so nested objects can be built with
+    /// `root.entry("a").entry("b")`.
+    ///
+    /// # Panics
+    ///
+    /// If `self` is neither `null` nor an object.
+    pub fn entry(&mut self, key: &str) -> &mut Value {
+        if let Value::Null = self {
+            *self = Value::Object(Map::new());
+        }
+        match self {
+            Value::Object(m) => {
+                if m.get(key).is_none() {
+                    m.insert(key.to_string(), Value::Null);
+                }
+                m.get_mut(key).unwrap()
+            }
+            v => panic!("entry '{}' on a non-object value {}", key, v),
         }
-
-        Ok(())
     }
+}
 
-    fn parse_value(&mut self) -> Result<Value, ParseError> {
-        match self.peek() {
-            Some('n') => self.parse_literal(Value::Null, "null"),
-            Some('f') => self.parse_literal(Value::False, "false"),
-            Some('t') => self.parse_literal(Value::True, "true"),
-            Some('\"') => self.parse_string(),
-            Some('[') => self.parse_array(),
-            Some('{') => self.parse_object(),
-            _ => self.parse_number(),
+fn walk_value<F: FnMut(&[PathSegment], &Value)>(v: &Value, path: &mut Vec<PathSegment>, f: &mut F) {
+    f(path, v);
+    match v {
+        Value::Array(v) => {
+            for (i, x) in v.iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                walk_value(x, path, f);
+                path.pop();
+            }
+        }
+        Value::Object(v) => {
+            for (k, x) in v {
+                path.push(PathSegment::Key(k.to_string()));
+                walk_value(x, path, f);
+                path.pop();
+            }
         }
+        _ => {}
     }
+}
 
-    fn peek(&mut self) -> Option<char> {
-        let mut c = self.context.chars();
-        if let Some(ch) = c.next() {
-            return Some(ch);
+fn map_strings<F: FnMut(&str) -> String>(v: &mut Value, keys: bool, f: &mut F) {
+    match v {
+        Value::String(s) => *s = f(s),
+        Value::Array(arr) => {
+            for x in arr {
+                map_strings(x, keys, f);
+            }
+        }
+        Value::Object(m) => {
+            if keys {
+                m.map_keys(&mut *f);
+            }
+            for (_, x) in m.iter_mut() {
+                map_strings(x, keys, f);
+            }
         }
+        _ => {}
+    }
+}
 
-        None
+// Array index token, no leading zeros and no sign.
+fn pointer_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
     }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
 
-    fn next(&mut self) -> Option<char> {
-        let mut c = self.context.chars();
-        if let Some(_) = c.next() {
-            self.context = c.as_str();
-            self.column += 1;
-            return self.peek();
+// JSON Pointer of `path`, the inverse of `Value::pointer`.
+fn pointer_of(path: &[PathSegment]) -> String {
+    let mut s = String::new();
+    for seg in path {
+        s.push('/');
+        match seg {
+            PathSegment::Key(k) => s.push_str(&k.replace('~', "~0").replace('/', "~1")),
+            PathSegment::Index(i) => s.push_str(&i.to_string()),
         }
+    }
+    s
+}
 
-        None
+pub fn type_name(v: Value) -> Option<&'static str> {
+    match v {
+        Value::Null => Some("null"),
+        Value::Bool(_) => Some("boolean"),
+        Value::Number(_) => Some("number"),
+        Value::String(_) => Some("string"),
+        Value::Array(_) => Some("array"),
+        Value::Object(_) => Some("object"),
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// Errors `parse_with_recovery` collects before giving up on the document.
+const MAX_RECOVERED_ERRORS: usize = 100;
+
+/// Parse `input` without stopping at the first error, for linters that
+/// want to report every problem.
+///
+/// Missing or trailing commas are reported and skipped. A broken element
+/// or member value becomes `null` and parsing resumes at the next ',', ']'
+/// or '}' of its container. The error list is the point; the value is only
+/// a best-effort guess at what was meant, `None` when nothing could be
+/// built or after 100 errors.
+pub fn parse_with_recovery(input: &str) -> (Option<Value>, Vec<ParseError>) {
+    let mut r = Reader::new(input);
+    r.errors = Some(Vec::new());
+
+    let v = match r.parse_tree() {
+        Ok(v) => {
+            if !r.context.is_empty() {
+                let desc = format!("value not finished '{}'", r.context);
+                r.record(desc);
+            }
+            Some(v)
+        }
+        Err(e) => {
+            r.push_error(e);
+            None
+        }
+    };
 
-    #[test]
-    fn test_value_name() {
-        assert_eq!(Some("null"), type_name(Value::Null));
-        assert_eq!(Some("false"), type_name(Value::False));
-        assert_eq!(Some("true"), type_name(Value::True));
-        assert_eq!(Some("number"), type_name(Value::Number { v: 64.0 }));
-        assert_eq!(
-            Some("string"),
-            type_name(Value::String {
-                v: String::from("str")
-            })
-        );
-        assert_eq!(
-            Some("array"),
-            type_name(Value::Array {
-                v: vec![Value::Null, Value::False]
-            })
-        );
-        assert_eq!(
-            Some("object"),
-            type_name(Value::Object { v: HashMap::new() })
-        );
+    (v, r.errors.unwrap_or_default())
+}
+
+/// Read all of `r` and parse it. The input must be UTF-8.
+///
+/// For a tokio `AsyncRead`, see `parse_async` under the `tokio` feature.
+pub fn from_reader<R: io::Read>(mut r: R) -> Result<Value, ParseError> {
+    let mut s = String::new();
+    if let Err(e) = r.read_to_string(&mut s) {
+        return Err(ParseError {
+            kind: ErrorKind::Io,
+            row: 0,
+            column: 0,
+            desc: e.to_string(),
+        });
     }
+    let mut reader = Reader::new(&s);
+    reader.parse()
+}
 
-    #[test]
-    fn test_eq() {
-        let n = Value::Null;
-        // assert!(eq_literal(Value::Null, &n));
-        assert!(eq_value(&Value::Null, &n));
-        assert_eq!(&Value::Null, &n);
+/// What to do with a number literal too large for `f64`, like `1e500`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Fail with a `ParseError`.
+    #[default]
+    Error,
+    /// Use `f64::MAX` with the literal's sign.
+    Clamp,
+    /// Keep the infinity `f64::from_str` produced.
+    Raw,
+}
 
-        let f = Value::False;
-        // assert!(eq_literal(Value::False, &f));
-        assert!(eq_value(&Value::False, &f));
-        assert_eq!(&Value::False, &f);
+/// What to do with a key already in the object being parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the last value, or every member in document mode.
+    #[default]
+    LastWins,
+    /// Fail with `ErrorKind::DuplicateKey`.
+    Error,
+}
 
-        let t = Value::True;
-        // assert!(eq_literal(Value::True, &f));
-        assert!(eq_value(&Value::True, &t));
-        assert_eq!(&Value::True, &t);
+/// What a column in `ParseError` and `Span` counts, to match the consuming
+/// editor. Columns are 1-based in every unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnUnit {
+    #[default]
+    Chars,
+    Utf8Bytes,
+    /// As in the Language Server Protocol, astral-plane chars count two.
+    Utf16CodeUnits,
+}
 
-        let num = Value::Number { v: 3.14159 };
-        assert!(eq_number(&3.14159, &num));
-        assert!(eq_value(&Value::Number { v: 3.14159 }, &num));
-        assert_eq!(&Value::Number { v: 3.14159 }, &num);
+struct Progress {
+    callback: Box<dyn FnMut(usize) -> ControlFlow<()>>,
+    every: usize,
+}
 
-        let arr = Value::Array {
-            v: vec![Value::Null, Value::False],
-        };
-        assert!(eq_array(&vec![Value::Null, Value::False], &arr));
-        assert!(eq_value(
-            &Value::Array {
-                v: vec![Value::Null, Value::False]
-            },
-            &arr
-        ));
-        assert_eq!(
-            &Value::Array {
-                v: vec![Value::Null, Value::False]
-            },
-            &arr
-        );
+// Shared rather than boxed so `parse_batch_with` can hand it to every
+// thread.
+#[derive(Clone)]
+struct KeyValidator(Arc<dyn Fn(&str) -> bool + Send + Sync>);
 
-        let obj = Value::Object { v: HashMap::new() };
-        assert!(eq_object(&HashMap::new(), &obj));
-        assert!(eq_value(&Value::Object { v: HashMap::new() }, &obj));
-        assert_eq!(&Value::Object { v: HashMap::new() }, &obj);
+impl fmt::Debug for KeyValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KeyValidator")
     }
+}
 
-    #[test]
-    fn test_peek_next() {
-        let mut r = Reader::new("{\"n\":1}");
-        assert_eq!(Some('{'), r.peek());
-        assert_eq!(Some('\"'), r.next());
-        assert_eq!(Some('n'), r.next());
-        assert_eq!(Some('\"'), r.next());
-        assert_eq!(Some(':'), r.next());
-        assert_eq!(Some('1'), r.next());
-        assert_eq!(Some('}'), r.next());
-        assert_eq!(None, r.next());
-        assert_eq!(None, r.next());
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Progress {{ every: {} }}", self.every)
     }
+}
 
-    #[test]
-    fn test_parse_whitespace() {
-        let mut r = Reader::new("  {}");
-        r.parse_whitespace();
-        assert_eq!("{}", r.context);
-    }
+/// Reader settings, the default is strict RFC 8259.
+#[must_use]
+#[derive(Debug)]
+pub struct ParseOptions {
+    /// Accept `NaN`, `Infinity` and `-Infinity` as numbers.
+    pub allow_nan: bool,
+    pub on_number_overflow: OverflowPolicy,
+    /// Keep object members in source order, duplicates included, see `Map`.
+    pub document_mode: bool,
+    pub on_duplicate_key: DuplicateKeyPolicy,
+    /// Skip `// line` and `/* block */` comments like whitespace.
+    pub allow_comments: bool,
+    /// Accept identifier keys without quotes, JSON5 style: `{name: "x"}`.
+    /// An identifier is letters, digits, `_` and `$`, not starting with a
+    /// digit.
+    pub allow_unquoted_keys: bool,
+    /// Accept strings and keys in single quotes, JSON5 style: `'it\'s'`.
+    /// Inside, `"` needs no escape and `\'` is allowed.
+    pub allow_single_quotes: bool,
+    /// Accept hexadecimal integers, JSON5 style: `0x1F`, `-0XFF`.
+    pub allow_hex_numbers: bool,
+    /// Accept a leading `+` on numbers, JSON5 style: `+5`.
+    pub allow_plus_sign: bool,
+    /// Skip any Unicode whitespace between tokens, form feed, vertical tab,
+    /// U+00A0 and U+2028 among them, not only the four RFC 8259 allows.
+    /// They don't count as line breaks for positions.
+    pub allow_extra_whitespace: bool,
+    /// Reject documents whose root is not an array or object.
+    pub require_container_root: bool,
+    /// Reject `""` as a member key, valid JSON though it is.
+    pub reject_empty_keys: bool,
+    /// Keep each decimal number literal as a `Value::String` of its exact
+    /// text, less a leading `+`, instead of converting it to `f64`, so that
+    /// digits `f64` can't hold survive a read-modify-write. Numbers and
+    /// strings can then no longer be told apart; write the value back with
+    /// `WriteOptions::numbers_as_strings`. Hex numbers, `NaN` and
+    /// `Infinity` are still converted.
+    pub numbers_as_strings: bool,
+    /// Arrays and objects nested deeper than this are an error, which keeps
+    /// hostile input from overflowing the stack. 512 by default.
+    pub max_depth: usize,
+    /// Longest string or key accepted, in bytes after unescaping.
+    /// Unlimited by default.
+    pub max_string_length: Option<usize>,
+    /// Most elements of one array or members of one object. Unlimited by
+    /// default.
+    pub max_elements: Option<usize>,
+    pub column_unit: ColumnUnit,
+    /// Stop once the cursor is past this many bytes of input. Unlimited by
+    /// default.
+    pub max_bytes: Option<usize>,
+    /// Stop once this instant has passed. Checked with the progress
+    /// callback, or every 4096 bytes without one.
+    pub deadline: Option<Instant>,
+    progress: Option<Progress>,
+    key_validator: Option<KeyValidator>,
+}
+
+// How many bytes apart `ParseOptions::deadline` is checked when there is no
+// progress callback to set the interval.
+const DEADLINE_CHECK_BYTES: usize = 4096;
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            allow_nan: false,
+            on_number_overflow: OverflowPolicy::default(),
+            document_mode: false,
+            on_duplicate_key: DuplicateKeyPolicy::default(),
+            allow_comments: false,
+            allow_unquoted_keys: false,
+            allow_single_quotes: false,
+            allow_hex_numbers: false,
+            allow_plus_sign: false,
+            allow_extra_whitespace: false,
+            require_container_root: false,
+            reject_empty_keys: false,
+            numbers_as_strings: false,
+            max_depth: 512,
+            max_string_length: None,
+            max_elements: None,
+            column_unit: ColumnUnit::default(),
+            max_bytes: None,
+            deadline: None,
+            progress: None,
+            key_validator: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    pub fn allow_nan(mut self, allow: bool) -> ParseOptions {
+        self.allow_nan = allow;
+        self
+    }
+
+    pub fn on_number_overflow(mut self, policy: OverflowPolicy) -> ParseOptions {
+        self.on_number_overflow = policy;
+        self
+    }
+
+    /// Shorthand for `OverflowPolicy::Raw` when `allow`, else
+    /// `OverflowPolicy::Error`.
+    pub fn allow_overflow_to_infinity(self, allow: bool) -> ParseOptions {
+        self.on_number_overflow(if allow {
+            OverflowPolicy::Raw
+        } else {
+            OverflowPolicy::Error
+        })
+    }
+
+    pub fn document_mode(mut self, on: bool) -> ParseOptions {
+        self.document_mode = on;
+        self
+    }
+
+    pub fn on_duplicate_key(mut self, policy: DuplicateKeyPolicy) -> ParseOptions {
+        self.on_duplicate_key = policy;
+        self
+    }
+
+    pub fn allow_comments(mut self, allow: bool) -> ParseOptions {
+        self.allow_comments = allow;
+        self
+    }
+
+    pub fn allow_unquoted_keys(mut self, allow: bool) -> ParseOptions {
+        self.allow_unquoted_keys = allow;
+        self
+    }
+
+    pub fn allow_single_quotes(mut self, allow: bool) -> ParseOptions {
+        self.allow_single_quotes = allow;
+        self
+    }
+
+    pub fn allow_hex_numbers(mut self, allow: bool) -> ParseOptions {
+        self.allow_hex_numbers = allow;
+        self
+    }
+
+    pub fn allow_plus_sign(mut self, allow: bool) -> ParseOptions {
+        self.allow_plus_sign = allow;
+        self
+    }
+
+    pub fn allow_extra_whitespace(mut self, allow: bool) -> ParseOptions {
+        self.allow_extra_whitespace = allow;
+        self
+    }
+
+    pub fn require_container_root(mut self, require: bool) -> ParseOptions {
+        self.require_container_root = require;
+        self
+    }
+
+    pub fn reject_empty_keys(mut self, reject: bool) -> ParseOptions {
+        self.reject_empty_keys = reject;
+        self
+    }
+
+    pub fn numbers_as_strings(mut self, on: bool) -> ParseOptions {
+        self.numbers_as_strings = on;
+        self
+    }
+
+    pub fn max_depth(mut self, depth: usize) -> ParseOptions {
+        self.max_depth = depth;
+        self
+    }
+
+    pub fn max_string_length(mut self, len: usize) -> ParseOptions {
+        self.max_string_length = Some(len);
+        self
+    }
+
+    pub fn max_elements(mut self, n: usize) -> ParseOptions {
+        self.max_elements = Some(n);
+        self
+    }
+
+    pub fn column_unit(mut self, unit: ColumnUnit) -> ParseOptions {
+        self.column_unit = unit;
+        self
+    }
+
+    pub fn max_bytes(mut self, n: usize) -> ParseOptions {
+        self.max_bytes = Some(n);
+        self
+    }
+
+    pub fn deadline(mut self, at: Instant) -> ParseOptions {
+        self.deadline = Some(at);
+        self
+    }
+
+    /// Call `callback` with the number of bytes consumed so far, about every
+    /// `every_n_bytes`. Returning `ControlFlow::Break` stops parsing with an
+    /// `ErrorKind::Cancelled` error at the current position.
+    pub fn progress(
+        mut self,
+        callback: Box<dyn FnMut(usize) -> ControlFlow<()>>,
+        every_n_bytes: usize,
+    ) -> ParseOptions {
+        self.progress = Some(Progress {
+            callback,
+            every: every_n_bytes.max(1),
+        });
+        self
+    }
+
+    /// Call `valid` with every member key right after it is read, at any
+    /// depth. Returning false stops parsing with an `ErrorKind::InvalidKey`
+    /// error at the key, before its value is read.
+    pub fn key_validator(mut self, valid: Box<dyn Fn(&str) -> bool + Send + Sync>) -> ParseOptions {
+        self.key_validator = Some(KeyValidator(Arc::from(valid)));
+        self
+    }
+}
+
+/// The shape of a number literal, from `Reader::parse_number_info`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberInfo {
+    /// Digits before the '.' or exponent, `-0.5` has one.
+    pub integer_digits: usize,
+    pub has_fraction: bool,
+    pub has_exponent: bool,
+}
+
+type TransformFn<'a> = dyn Fn(&str) -> Option<Value> + 'a;
+type StringTransform<'a> = Box<TransformFn<'a>>;
+
+pub struct Reader<'a> {
+    context: &'a str,
+    origin: &'a str,
+    row: usize,
+    column: usize,
+    errors: Option<Vec<ParseError>>,
+    options: ParseOptions,
+    // offset of the next `tick` check
+    tick_at: usize,
+    abort: Option<ParseError>,
+    path: Vec<PathSegment>,
+    trivia: Option<Trivia>,
+    pending: Leading,
+    spans: Option<SpanMap>,
+    notes: Option<Vec<Note>>,
+    depth: usize,
+    // keys of the objects being parsed, with `DuplicateKeyPolicy::Error`
+    keys_seen: Vec<HashMap<String, Position>>,
+    // offset of the last element or member started, each one must start
+    // further on or a loop is not consuming input
+    item_at: usize,
+    transform: Option<StringTransform<'a>>,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(c: &'a str) -> Reader<'a> {
+        Reader::with_options(c, ParseOptions::default())
+    }
+
+    pub fn with_options(c: &'a str, options: ParseOptions) -> Reader<'a> {
+        let mut r = Reader {
+            context: c,
+            origin: c,
+            row: 1,
+            column: 1,
+            errors: None,
+            options,
+            tick_at: 0,
+            abort: None,
+            path: Vec::new(),
+            trivia: None,
+            pending: Leading::default(),
+            spans: None,
+            notes: None,
+            depth: 0,
+            keys_seen: Vec::new(),
+            item_at: 0,
+            transform: None,
+        };
+        r.tick_at = r.next_tick(0);
+        r
+    }
+
+    /// Set `ParseOptions::deadline` on a reader already made, to bound the
+    /// time spent on untrusted input.
+    pub fn with_deadline(mut self, at: Instant) -> Reader<'a> {
+        self.options.deadline = Some(at);
+        self.tick_at = self.next_tick(0);
+        self
+    }
+
+    /// Pass every string value, not member keys, to `f` once unescaped and
+    /// use what it returns in place of `Value::String`, say to turn ISO-8601
+    /// dates into numbers. `None` keeps the string. Not applied by
+    /// `parse_ref`.
+    pub fn with_string_transform<F>(mut self, f: F) -> Reader<'a>
+    where
+        F: Fn(&str) -> Option<Value> + 'a,
+    {
+        self.transform = Some(Box::new(f));
+        self
+    }
+
+    pub fn parse(&mut self) -> Result<Value, ParseError> {
+        self.rewind();
+        let x = self.parse_tree();
+        if let Some(e) = self.abort.take() {
+            return Err(e);
+        }
+        let x = x?;
+        self.expect_end()?;
+        self.check_root(convert::type_name_ref(&x))?;
+        Ok(x)
+    }
+
+    /// Parse and also collect the comments and blank lines preceding each
+    /// element and member, for `Value::to_string_pretty_with_trivia`.
+    /// Comments are only recognized with `ParseOptions::allow_comments`.
+    pub fn parse_with_trivia(&mut self) -> Result<(Value, Trivia), ParseError> {
+        self.trivia = Some(Trivia::default());
+        let x = self.parse();
+        let trivia = self.trivia.take().unwrap_or_default();
+        Ok((x?, trivia))
+    }
+
+    /// Parse and also record the source span of every value and every
+    /// member key, by JSON Pointer.
+    pub fn parse_with_spans(&mut self) -> Result<(Value, SpanMap), ParseError> {
+        self.spans = Some(SpanMap::default());
+        let x = self.parse();
+        let spans = self.spans.take().unwrap_or_default();
+        Ok((x?, spans))
+    }
+
+    /// Current row and column, both 1-based, and byte offset into the input.
+    /// After `parse` this is the end of the input, or where the error was.
+    pub fn position(&self) -> (usize, usize, usize) {
+        (self.row, self.column, self.offset())
+    }
+
+    /// Parse without copying strings that need no unescaping, see
+    /// `ValueRef`. Recovery, trivia and spans are not available here.
+    pub fn parse_ref(&mut self) -> Result<ValueRef<'a>, ParseError> {
+        self.rewind();
+        self.parse_whitespace()?;
+        let x = self.parse_value_ref();
+        if let Some(e) = self.abort.take() {
+            return Err(e);
+        }
+        let x = x?;
+        self.parse_whitespace()?;
+        self.expect_end()?;
+        self.check_root(x.type_name())?;
+        Ok(x)
+    }
+
+    /// The source text of the document's value, without the whitespace
+    /// around it. The text is checked like `parse` would but no tree is
+    /// built, say to forward a fragment byte for byte.
+    pub fn parse_raw_value(&mut self) -> Result<&'a str, ParseError> {
+        self.rewind();
+        self.parse_whitespace()?;
+        let start = self.offset();
+        let x = self.skip_value();
+        if let Some(e) = self.abort.take() {
+            return Err(e);
+        }
+        x?;
+        let raw = &self.origin[start..self.offset()];
+        self.parse_whitespace()?;
+        self.expect_end()?;
+        let kind = match raw.as_bytes()[0] {
+            b'[' => "array",
+            b'{' => "object",
+            b'n' => "null",
+            b't' | b'f' => "boolean",
+            b'"' | b'\'' => "string",
+            _ => "number",
+        };
+        self.check_root(kind)?;
+        Ok(raw)
+    }
+
+    // With `require_container_root`, a well-formed document of scalar type
+    // `kind` is an error.
+    fn check_root(&self, kind: &str) -> Result<(), ParseError> {
+        if !self.options.require_container_root || kind == "array" || kind == "object" {
+            return Ok(());
+        }
+        Err(ParseError::with_kind(
+            ErrorKind::InvalidRootType,
+            1,
+            1,
+            format!("root expect array or object, found {}", kind),
+        ))
+    }
+
+    // Back to the start of the input, for each `parse*` entry point.
+    fn rewind(&mut self) {
+        self.context = self.origin;
+        self.row = 1;
+        self.column = 1;
+        self.depth = 0;
+        self.keys_seen.clear();
+        self.item_at = 0;
+        self.tick_at = self.next_tick(0);
+    }
+
+    // After the document, only whitespace may be left.
+    fn expect_end(&mut self) -> Result<(), ParseError> {
+        if !self.context.is_empty() {
+            return parse_value_error!(self, format!("value not finished '{}'", self.context));
+        }
+        Ok(())
+    }
+
+    // A syntax error at the cursor, for modules that can't use
+    // `parse_value_error!`.
+    fn error(&self, desc: &str) -> ParseError {
+        ParseError::new(self.row, self.column, desc)
+    }
+
+    // Whether the path to the current node is needed.
+    fn tracking(&self) -> bool {
+        self.trivia.is_some() || self.spans.is_some()
+    }
+
+    fn offset(&self) -> usize {
+        self.origin.len() - self.context.len()
+    }
+
+    fn span_from(&self, start: usize, line: usize, col: usize) -> Span {
+        Span {
+            start,
+            end: self.offset(),
+            line,
+            col,
+        }
+    }
+
+    // '[' ws | elements ']'
+    fn parse_array<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        self.bump(); // '['
+        self.emit(out, |h| h.begin_array())?;
+
+        self.parse_whitespace()?;
+        if self.peek_token() != Some(TokenKind::EndArray) {
+            self.parse_elements(out)?;
+        }
+
+        if self.peek_token() == Some(TokenKind::EndArray) {
+            self.bump();
+        } else if self.recovering() {
+            // closed anyway, keeping what was parsed
+            self.record(String::from("array end char expect ']'"));
+        } else {
+            return parse_value_error!(self, String::from("array end char expect ']'"));
+        }
+        // comments before ']' belong to no element, dropped for now
+        self.pending = Leading::default();
+
+        self.emit(out, |h| h.end_array())
+    }
+
+    // '{ ws | members '}'
+    fn parse_object<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        self.bump(); // '{'
+        self.emit(out, |h| h.begin_object())?;
+
+        self.parse_whitespace()?;
+        if self.peek_token() != Some(TokenKind::EndObject) {
+            self.parse_members(out)?;
+        }
+
+        if self.peek_token() == Some(TokenKind::EndObject) {
+            self.bump();
+        } else if self.recovering() {
+            self.record(String::from("object end char expect '}'"));
+        } else {
+            return parse_value_error!(self, String::from("object end char expect '}'"));
+        }
+        self.pending = Leading::default();
+
+        self.emit(out, |h| h.end_object())
+    }
+
+    // Hand the trivia read so far to the node at the current path.
+    fn attach_trivia(&mut self) {
+        if let Some(trivia) = self.trivia.as_mut() {
+            if !self.pending.is_empty() {
+                let leading = std::mem::take(&mut self.pending);
+                trivia.attach(pointer_of(&self.path), leading);
+            }
+        }
+    }
+
+    // element ',' element
+    fn parse_elements<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        let mut n = 0;
+        loop {
+            self.check_elements(n, "array", "elements")?;
+            let tracking = self.tracking();
+            if tracking {
+                self.path.push(PathSegment::Index(n));
+            }
+            let elem = self.parse_element(out);
+            if tracking {
+                self.path.pop();
+            }
+
+            if let Err(e) = elem {
+                self.recover(e)?;
+                self.emit(out, |h| h.null())?;
+            }
+            n += 1;
+            if !self.separator(TokenKind::EndArray, "array", "elements")? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // ws value ws
+    fn parse_element<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        self.parse_whitespace()?;
+        self.attach_trivia();
+        let (start, line, col) = (self.offset(), self.row, self.column);
+        self.parse_value(out)?;
+        if self.spans.is_some() {
+            self.record_value_span(start, line, col);
+        }
+        self.parse_whitespace()
+    }
+
+    // Out of `parse_element` to keep its stack frame small.
+    fn record_value_span(&mut self, start: usize, line: usize, col: usize) {
+        let span = self.span_from(start, line, col);
+        let pointer = pointer_of(&self.path);
+        if let Some(spans) = self.spans.as_mut() {
+            spans.values.insert(pointer, span);
+        }
+    }
+
+    // ws string ws ':' element
+    fn parse_member<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        self.parse_whitespace()?;
+        let (start, line, col) = (self.offset(), self.row, self.column);
+        let k = self.parse_key()?;
+        self.validate_key(&k, line, col)?;
+        self.check_duplicate(&k, line, col)?;
+        let key_span = self.span_from(start, line, col);
+        self.parse_whitespace()?;
+        if self.peek_token() != Some(TokenKind::Colon) {
+            return parse_value_error!(self, String::from("member expect ':'"));
+        }
+        self.bump();
+        self.emit(out, |h| h.key(&k))?;
+
+        if !self.tracking() {
+            return self.parse_member_value(out);
+        }
+        self.path.push(PathSegment::Key(k.into_owned()));
+        if self.spans.is_some() {
+            let pointer = pointer_of(&self.path);
+            if let Some(spans) = self.spans.as_mut() {
+                spans.keys.insert(pointer, key_span);
+            }
+        }
+        let v = self.parse_member_value(out);
+        self.path.pop();
+        v
+    }
+
+    // A broken value still keeps its key when recovering.
+    fn parse_member_value<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        if let Err(e) = self.parse_element(out) {
+            self.recover(e)?;
+            self.emit(out, |h| h.null())?;
+        }
+        Ok(())
+    }
+
+    // member ',' members
+    fn parse_members<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        let mut n = 0;
+        self.open_keys();
+        loop {
+            self.check_elements(n, "object", "members")?;
+            match self.parse_member(out) {
+                Ok(()) => n += 1,
+                Err(e) => self.recover(e)?,
+            }
+            if !self.separator(TokenKind::EndObject, "object", "members")? {
+                break;
+            }
+        }
+        self.close_keys();
+        Ok(())
+    }
+
+    // After an element or member, consume the ',' and tell whether another
+    // one follows. When recovering, a missing ',' is recorded and a trailing
+    // one before `close` ends the container.
+    fn separator(
+        &mut self,
+        close: TokenKind,
+        container: &str,
+        items: &str,
+    ) -> Result<bool, ParseError> {
+        match self.peek_token() {
+            Some(TokenKind::Comma) => {
+                self.bump();
+                if !self.recovering() {
+                    return Ok(true);
+                }
+                self.parse_whitespace()?;
+                if self.peek_token() != Some(close) {
+                    return Ok(true);
+                }
+                self.record(format!("{} trailing ','", container));
+                Ok(false)
+            }
+            Some(TokenKind::EndArray | TokenKind::EndObject) | None => Ok(false),
+            Some(_) => {
+                if self.recovering() {
+                    self.record(format!("{} {} expect ','", container, items));
+                }
+                Ok(self.recovering())
+            }
+        }
+    }
+
+    // Start collecting the keys of an object for `check_duplicate`, until
+    // `close_keys`. Kept on the reader, not in the parser's stack frames,
+    // which must stay small for `max_depth` nesting.
+    fn open_keys(&mut self) {
+        if self.options.on_duplicate_key == DuplicateKeyPolicy::Error {
+            self.keys_seen.push(HashMap::new());
+        }
+    }
+
+    fn close_keys(&mut self) {
+        if self.options.on_duplicate_key == DuplicateKeyPolicy::Error {
+            self.keys_seen.pop();
+        }
+    }
+
+    // Check key `k` found at `row`:`column` against
+    // `ParseOptions::reject_empty_keys` and `key_validator`. A key the
+    // validator rejects is fatal, even when recovering.
+    fn validate_key(&mut self, k: &str, row: usize, column: usize) -> Result<(), ParseError> {
+        if k.is_empty() && self.options.reject_empty_keys {
+            return Err(ParseError::new(row, column, "member key is empty"));
+        }
+        match &self.options.key_validator {
+            Some(KeyValidator(valid)) if !valid(k) => {}
+            _ => return Ok(()),
+        }
+        let kind = ErrorKind::InvalidKey(Box::new(InvalidKey {
+            key: k.to_string(),
+            position: Position { row, column },
+        }));
+        let desc = format!("invalid key '{}'", k);
+        self.fatal(Err(ParseError::with_kind(kind, row, column, desc)))
+    }
+
+    // Record key `k` of the innermost object, found at `row`:`column`,
+    // failing if it is there already.
+    fn check_duplicate(&mut self, k: &str, row: usize, column: usize) -> Result<(), ParseError> {
+        let seen = match self.keys_seen.last_mut() {
+            Some(seen) => seen,
+            None => return Ok(()),
+        };
+        let second = Position { row, column };
+        let first = match seen.get(k) {
+            Some(first) => *first,
+            None => {
+                seen.insert(k.to_string(), second);
+                return Ok(());
+            }
+        };
+        let desc = format!(
+            "duplicate key '{}' at {}:{}, first at {}:{}",
+            k, row, column, first.row, first.column
+        );
+        let kind = ErrorKind::DuplicateKey(Box::new(DuplicateKey {
+            key: k.to_string(),
+            first,
+            second,
+        }));
+        Err(ParseError::with_kind(kind, row, column, desc))
+    }
+
+    // Before adding one more to a container holding `n`. Not recoverable,
+    // the limit is there to stop work on hostile input.
+    fn check_elements(&mut self, n: usize, container: &str, items: &str) -> Result<(), ParseError> {
+        debug_assert!(
+            self.offset() > self.item_at,
+            "{} {} stopped consuming input at {}",
+            container,
+            items,
+            self.offset()
+        );
+        self.item_at = self.offset();
+        match self.options.max_elements {
+            Some(max) if n >= max => self.fatal(parse_value_error!(
+                self,
+                format!("{} exceeds {} {}", container, max, items)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // End the parse for good, recovery included.
+    fn fatal<T>(&mut self, x: Result<T, ParseError>) -> Result<T, ParseError> {
+        if let Err(e) = &x {
+            self.abort = Some(e.clone());
+        }
+        x
+    }
+
+    // Send one event to `out`, an error from the handler is fatal.
+    fn emit<H, F>(&mut self, out: &mut Sink<H>, f: F) -> Result<(), ParseError>
+    where
+        H: Handler,
+        F: FnOnce(&mut H) -> Result<(), H::Error>,
+    {
+        match f(out.handler) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                out.error = Some(e);
+                self.fatal(parse_value_error!(
+                    self,
+                    ErrorKind::Cancelled,
+                    String::from("handler stopped")
+                ))
+            }
+        }
+    }
+
+    fn recovering(&self) -> bool {
+        self.errors.is_some()
+    }
+
+    fn record(&mut self, desc: String) {
+        let e = ParseError {
+            kind: ErrorKind::Syntax,
+            row: self.row,
+            column: self.column,
+            desc,
+        };
+        self.push_error(e);
+    }
+
+    // Remember `e` when recovering, false once there is no room left.
+    fn push_error(&mut self, e: ParseError) -> bool {
+        match self.errors.as_mut() {
+            Some(errors) if errors.len() < MAX_RECOVERED_ERRORS => {
+                errors.push(e);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Keep going after an error in recovery mode: remember it and skip to
+    // the next ',', ']' or '}' of the current container, stepping over
+    // nested containers and strings, so the container can resume. This
+    // scans characters, not tokens: the text after an error need not lex.
+    fn recover(&mut self, e: ParseError) -> Result<(), ParseError> {
+        let full = self.errors.as_ref().map_or(0, Vec::len) >= MAX_RECOVERED_ERRORS;
+        if !self.recovering() || self.abort.is_some() || full {
+            return Err(e);
+        }
+        self.push_error(e);
+
+        let mut depth = 0;
+        while let Some(ch) = self.peek() {
+            match ch {
+                ',' if depth == 0 => break,
+                ']' | '}' if depth == 0 => break,
+                ']' | '}' => depth -= 1,
+                '[' | '{' => depth += 1,
+                quote if self.is_quote(quote) => {
+                    self.next();
+                    while let Some(ch) = self.peek() {
+                        if ch == quote || ch == '\n' {
+                            break;
+                        }
+                        if ch == '\\' {
+                            self.next();
+                        }
+                        self.next();
+                    }
+                }
+                _ => {}
+            }
+            self.next();
+        }
+        Ok(())
+    }
+
+    fn parse_value<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        match self.peek_token() {
+            Some(TokenKind::BeginArray) => self.parse_nested(|r| r.parse_array(out)),
+            Some(TokenKind::BeginObject) => self.parse_nested(|r| r.parse_object(out)),
+            _ => self.parse_leaf(out),
+        }
+    }
+
+    // A string or scalar, apart from `parse_value` whose stack frame is
+    // repeated for every level of nesting.
+    fn parse_leaf<H: Handler>(&mut self, out: &mut Sink<H>) -> Result<(), ParseError> {
+        match self.peek_token() {
+            Some(TokenKind::String) => {
+                let s = self.parse_str()?;
+                self.emit(out, |h| h.string(&s))
+            }
+            _ => match self.parse_scalar()? {
+                Value::Null => self.emit(out, |h| h.null()),
+                Value::Bool(b) => self.emit(out, |h| h.bool(b)),
+                Value::Number(v) => self.emit(out, |h| h.number(v)),
+                // `ParseOptions::numbers_as_strings`
+                Value::String(ref s) => self.emit(out, |h| h.string(s)),
+                _ => unreachable!("containers handled above"),
+            },
+        }
+    }
+
+    fn parse_nested<T, F>(&mut self, f: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&mut Reader<'a>) -> Result<T, ParseError>,
+    {
+        if self.depth >= self.options.max_depth {
+            let max = self.options.max_depth;
+            return self.fatal(parse_value_error!(
+                self,
+                format!("nesting deeper than {}", max)
+            ));
+        }
+        self.depth += 1;
+        let x = f(self);
+        self.depth -= 1;
+        x
+    }
+
+    fn parse_value_ref(&mut self) -> Result<ValueRef<'a>, ParseError> {
+        match self.peek_token() {
+            Some(TokenKind::String) => Ok(ValueRef::String(self.parse_str()?)),
+            Some(TokenKind::BeginArray) => self.parse_nested(Reader::parse_array_ref),
+            Some(TokenKind::BeginObject) => self.parse_nested(Reader::parse_object_ref),
+            _ => match self.parse_scalar()? {
+                Value::Null => Ok(ValueRef::Null),
+                Value::Bool(b) => Ok(ValueRef::Bool(b)),
+                Value::Number(v) => Ok(ValueRef::Number(v)),
+                Value::String(ref mut s) => Ok(ValueRef::String(Cow::Owned(std::mem::take(s)))),
+                _ => unreachable!("containers handled above"),
+            },
+        }
+    }
+
+    fn parse_array_ref(&mut self) -> Result<ValueRef<'a>, ParseError> {
+        self.bump(); // '['
+
+        let mut arr = Vec::new();
+        self.parse_whitespace()?;
+        if self.peek_token() != Some(TokenKind::EndArray) {
+            loop {
+                self.parse_whitespace()?;
+                self.check_elements(arr.len(), "array", "elements")?;
+                arr.push(self.parse_value_ref()?);
+                self.parse_whitespace()?;
+                if self.peek_token() != Some(TokenKind::Comma) {
+                    break;
+                }
+                self.bump();
+            }
+        }
+
+        if self.peek_token() != Some(TokenKind::EndArray) {
+            return parse_value_error!(self, String::from("array end char expect ']'"));
+        }
+        self.bump();
+        self.pending = Leading::default();
+
+        Ok(ValueRef::Array(arr))
+    }
+
+    fn parse_object_ref(&mut self) -> Result<ValueRef<'a>, ParseError> {
+        self.bump(); // '{'
+
+        let mut members = Vec::new();
+        self.open_keys();
+        self.parse_whitespace()?;
+        if self.peek_token() != Some(TokenKind::EndObject) {
+            loop {
+                self.check_elements(members.len(), "object", "members")?;
+                self.parse_whitespace()?;
+                let (line, col) = (self.row, self.column);
+                let k = self.parse_key()?;
+                self.validate_key(&k, line, col)?;
+                self.check_duplicate(&k, line, col)?;
+                self.parse_whitespace()?;
+                if self.peek_token() != Some(TokenKind::Colon) {
+                    return parse_value_error!(self, String::from("member expect ':'"));
+                }
+                self.bump();
+                self.parse_whitespace()?;
+                members.push((k, self.parse_value_ref()?));
+                self.parse_whitespace()?;
+                if self.peek_token() != Some(TokenKind::Comma) {
+                    break;
+                }
+                self.bump();
+            }
+        }
+        self.close_keys();
+
+        if self.peek_token() != Some(TokenKind::EndObject) {
+            return parse_value_error!(self, String::from("object end char expect '}'"));
+        }
+        self.bump();
+        self.pending = Leading::default();
+
+        Ok(ValueRef::Object(members))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let mut c = self.context.chars();
+        if let Some(ch) = c.next() {
+            return Some(ch);
+        }
+
+        None
+    }
+
+    // The only place the cursor moves, so row and column always agree with
+    // the offset. `\n`, `\r\n` and a lone `\r` each end a line.
+    fn next(&mut self) -> Option<char> {
+        let mut c = self.context.chars();
+        if let Some(ch) = c.next() {
+            self.context = c.as_str();
+            if ch == '\n' || (ch == '\r' && !self.context.starts_with('\n')) {
+                self.row += 1;
+                self.column = 1;
+            } else {
+                self.column += match self.options.column_unit {
+                    ColumnUnit::Chars => 1,
+                    ColumnUnit::Utf8Bytes => ch.len_utf8(),
+                    ColumnUnit::Utf16CodeUnits => ch.len_utf16(),
+                };
+            }
+            self.tick();
+            return self.peek();
+        }
+
+        None
+    }
+
+    // Check the limits and report progress once per interval, and right
+    // past `max_bytes`. Stopping empties the remaining input so every parse
+    // loop ends promptly, `parse` then returns the saved error instead of
+    // whatever the truncation caused.
+    fn tick(&mut self) {
+        let offset = self.origin.len() - self.context.len();
+        if offset < self.tick_at {
+            return;
+        }
+
+        if let Some(max) = self.options.max_bytes.filter(|&max| offset > max) {
+            return self.stop(
+                ErrorKind::LimitExceeded,
+                format!("input longer than {} bytes", max),
+            );
+        }
+        self.tick_at = self.next_tick(offset);
+        if self.options.deadline.is_some_and(|at| Instant::now() >= at) {
+            return self.stop(
+                ErrorKind::DeadlineExceeded,
+                format!("deadline exceeded after {} bytes", offset),
+            );
+        }
+        if let Some(progress) = self.options.progress.as_mut() {
+            if (progress.callback)(offset).is_break() {
+                self.stop(
+                    ErrorKind::Cancelled,
+                    format!("cancelled after {} bytes", offset),
+                );
+            }
+        }
+    }
+
+    // The offset of the first check after `offset`.
+    fn next_tick(&self, offset: usize) -> usize {
+        let every = match (&self.options.progress, self.options.deadline) {
+            (Some(p), _) => Some(p.every),
+            (None, Some(_)) => Some(DEADLINE_CHECK_BYTES),
+            (None, None) => None,
+        };
+        let at = every.map_or(usize::MAX, |n| offset.saturating_add(n));
+        match self.options.max_bytes {
+            Some(max) => at.min(max.saturating_add(1)),
+            None => at,
+        }
+    }
+
+    fn stop(&mut self, kind: ErrorKind, desc: String) {
+        self.abort = Some(ParseError::with_kind(kind, self.row, self.column, desc));
+        self.context = "";
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::approx_constant)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_value_name() {
+        assert_eq!(Some("null"), type_name(Value::Null));
+        assert_eq!(Some("boolean"), type_name(Value::Bool(false)));
+        assert_eq!(Some("boolean"), type_name(Value::Bool(true)));
+        assert_eq!(Some("number"), type_name(Value::Number(64.0)));
+        assert_eq!(
+            Some("string"),
+            type_name(Value::String(String::from("str")))
+        );
+        assert_eq!(
+            Some("array"),
+            type_name(Value::Array(vec![Value::Null, Value::Bool(false)]))
+        );
+        assert_eq!(Some("object"), type_name(Value::Object(Map::new())));
+    }
+
+    #[test]
+    fn test_count_by_type() {
+        let src = "{\"a\": [1, 2.5, \"x\", null, [true, false]], \"b\": {\"c\": {}}, \"d\": 3}";
+        let counts = Reader::new(src).parse().unwrap().count_by_type();
+        let expect = [
+            ("object", 3),
+            ("array", 2),
+            ("number", 3),
+            ("string", 1),
+            ("null", 1),
+            ("boolean", 2),
+        ];
+        assert_eq!(expect.len(), counts.len());
+        for (name, n) in expect.iter() {
+            assert_eq!(Some(n), counts.get(name), "{}", name);
+        }
+        assert_eq!(Some(&1), Value::Null.count_by_type().get("null"));
+        assert_eq!(1, Value::Array(vec![]).count_by_type().len());
+    }
+
+    #[test]
+    fn test_eq() {
+        let n = Value::Null;
+        // assert!(eq_literal(Value::Null, &n));
+        assert!(eq_value(&Value::Null, &n));
+        assert_eq!(&Value::Null, &n);
+
+        let f = Value::Bool(false);
+        assert!(eq_value(&Value::Bool(false), &f));
+        assert_eq!(&Value::Bool(false), &f);
+
+        let t = Value::Bool(true);
+        assert!(eq_value(&Value::Bool(true), &t));
+        assert_eq!(&Value::Bool(true), &t);
+        assert_ne!(f, t);
+
+        let num = Value::Number(3.14159);
+        assert!(eq_number(&3.14159, &num));
+        assert!(eq_value(&Value::Number(3.14159), &num));
+        assert_eq!(&Value::Number(3.14159), &num);
+
+        let arr = Value::Array(vec![Value::Null, Value::Bool(false)]);
+        assert!(eq_array(&vec![Value::Null, Value::Bool(false)], &arr));
+        assert!(eq_value(
+            &Value::Array(vec![Value::Null, Value::Bool(false)]),
+            &arr
+        ));
+        assert_eq!(&Value::Array(vec![Value::Null, Value::Bool(false)]), &arr);
+
+        let obj = Value::Object(Map::new());
+        assert!(eq_object(&Map::new(), &obj));
+        assert!(eq_value(&Value::Object(Map::new()), &obj));
+        assert_eq!(&Value::Object(Map::new()), &obj);
+    }
+
+    #[test]
+    fn test_peek_next() {
+        let mut r = Reader::new("{\"n\":1}");
+        assert_eq!(Some('{'), r.peek());
+        assert_eq!(Some('\"'), r.next());
+        assert_eq!(Some('n'), r.next());
+        assert_eq!(Some('\"'), r.next());
+        assert_eq!(Some(':'), r.next());
+        assert_eq!(Some('1'), r.next());
+        assert_eq!(Some('}'), r.next());
+        assert_eq!(None, r.next());
+        assert_eq!(None, r.next());
+    }
+
+    #[test]
+    fn test_coerce_to_string() {
+        let s = |v: Value| v.coerce_to_string();
+        assert_eq!(
+            Some(String::from("a \"b\"")),
+            s(Value::String(String::from("a \"b\"")))
+        );
+        assert_eq!(Some(String::from("80")), s(Value::Number(80.0)));
+        assert_eq!(Some(String::from("-1.5")), s(Value::Number(-1.5)));
+        assert_eq!(Some(String::from("0")), s(Value::Number(-0.0)));
+        assert_eq!(Some(String::from("true")), s(Value::Bool(true)));
+        assert_eq!(Some(String::from("false")), s(Value::Bool(false)));
+        assert_eq!(None, s(Value::Null));
+        assert_eq!(None, s(Value::Array(vec![])));
+        assert_eq!(None, s(Value::Object(Map::new())));
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut root = Value::Null;
+        *root.entry("server").entry("host") = Value::String(String::from("a"));
+        *root.entry("server").entry("port") = Value::Number(80.0);
+        *root.entry("debug") = Value::Bool(true);
+        let expect = "{\"server\":{\"host\":\"a\",\"port\":80},\"debug\":true}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), root);
+
+        // existing members are kept
+        assert_eq!(&Value::Number(80.0), root.entry("server").entry("port"));
+        assert_eq!(&Value::Null, root.entry("server").entry("none"));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-object")]
+    fn test_entry_not_object() {
+        Value::Number(1.0).entry("a");
+    }
+
+    #[test]
+    fn test_append_pages() {
+        let mut pages: Vec<Value> = (0..3)
+            .map(|p| {
+                let items: Vec<String> =
+                    (0..4).map(|i| format!("{{\"n\":{}}}", p * 4 + i)).collect();
+                let src = format!("{{\"items\":[{}],\"page{}\":true}}", items.join(","), p);
+                let mut r = Reader::new(&src);
+                r.parse().unwrap()
+            })
+            .collect();
+
+        let mut all = Value::Object(Map::new());
+        let mut items = Value::Array(Vec::new());
+        for page in pages.iter_mut() {
+            let page_items = match page {
+                Value::Object(m) => m.get_mut("items").unwrap(),
+                _ => unreachable!(),
+            };
+            assert!(items.append(page_items));
+            assert!(all.append(page));
+        }
+        *all.entry("items") = items;
+
+        match all.get("items") {
+            Some(Value::Array(arr)) => assert_eq!(12, arr.len()),
+            x => panic!("{:?}", x),
+        }
+        assert_eq!(Some(&Value::Number(11.0)), all.pointer("/items/11/n"));
+        assert_eq!(Some(&Value::Bool(true)), all.get("page2"));
+        assert!(pages.iter().all(|p| p == &Value::Object(Map::new())));
+
+        // mismatched containers are left alone
+        let mut arr = Value::Array(vec![Value::Null]);
+        let mut obj = all.clone();
+        assert!(!arr.append(&mut obj));
+        assert_eq!(all, obj);
+        assert!(!Value::Null.append(&mut arr));
+        assert_eq!(Value::Array(vec![Value::Null]), arr);
+
+        arr.extend(vec![Value::Bool(true), Value::Number(1.0)]);
+        assert!(arr.extend_array(Some(Value::Null)));
+        assert_eq!(Reader::new("[null,true,1,null]").parse().unwrap(), arr);
+        assert!(!obj.extend_array(vec![Value::Null]));
+    }
+
+    #[test]
+    fn test_require_container_root() {
+        let cases = [
+            ("\"x\"", "string"),
+            (" 1.5 ", "number"),
+            ("true", "boolean"),
+            ("false", "boolean"),
+            ("\nnull", "null"),
+        ];
+        let strict = || ParseOptions::new().require_container_root(true);
+        for (src, kind) in cases.iter() {
+            assert!(Reader::new(src).parse().is_ok());
+            let e = Reader::with_options(src, strict()).parse().unwrap_err();
+            assert_eq!(ErrorKind::InvalidRootType, e.kind);
+            assert_eq!(
+                format!("root expect array or object, found {}", kind),
+                e.desc
+            );
+            assert_eq!((1, 1), (e.row, e.column));
+            let e = Reader::with_options(src, strict()).parse_ref().unwrap_err();
+            assert_eq!(ErrorKind::InvalidRootType, e.kind);
+            let e = Reader::with_options(src, strict())
+                .parse_raw_value()
+                .unwrap_err();
+            assert_eq!(ErrorKind::InvalidRootType, e.kind);
+        }
+
+        for src in ["[1]", " {} "].iter() {
+            assert!(Reader::with_options(src, strict()).parse().is_ok());
+            assert!(Reader::with_options(src, strict()).parse_ref().is_ok());
+        }
+        // a broken scalar is a syntax error first
+        let e = Reader::with_options("tru", strict()).parse().unwrap_err();
+        assert_eq!(ErrorKind::Syntax, e.kind);
+    }
+
+    #[test]
+    fn test_duplicate_key() {
+        let src = format!("{{\n  \"id\": 1,\n{}    \"id\": 2\n}}", "\n".repeat(199));
+        let strict = || ParseOptions::new().on_duplicate_key(DuplicateKeyPolicy::Error);
+        let v = Reader::new(&src).parse().unwrap();
+        assert_eq!(Some(&Value::Number(2.0)), v.get("id"));
+
+        let e = Reader::with_options(&src, strict()).parse().unwrap_err();
+        let kind = ErrorKind::DuplicateKey(Box::new(DuplicateKey {
+            key: String::from("id"),
+            first: Position { row: 2, column: 3 },
+            second: Position {
+                row: 202,
+                column: 5,
+            },
+        }));
+        assert_eq!(kind, e.kind);
+        assert_eq!((202, 5), (e.row, e.column));
+        assert_eq!("duplicate key 'id' at 202:5, first at 2:3", e.desc);
+        let opts = strict().document_mode(true);
+        assert_eq!(e, Reader::with_options(&src, opts).parse().unwrap_err());
+        assert_eq!(
+            e,
+            Reader::with_options(&src, strict())
+                .parse_ref()
+                .unwrap_err()
+        );
+
+        // only keys of the same object clash
+        let src = "{\"a\": {\"a\": 1}, \"b\": [{\"a\": 2}, {\"a\": 3}]}";
+        assert!(Reader::with_options(src, strict()).parse().is_ok());
+        assert!(Reader::with_options(src, strict()).parse_ref().is_ok());
+        let e = Reader::with_options("[{\"a\": {}, \"a\": 1}]", strict())
+            .parse()
+            .unwrap_err();
+        assert_eq!((1, 12), (e.row, e.column));
+    }
+
+    #[test]
+    fn test_reject_empty_keys() {
+        let v = Reader::new("{\"\":1}").parse().unwrap();
+        assert_eq!(Some(&Value::Number(1.0)), v.get(""));
+        let opts = || ParseOptions::new().reject_empty_keys(true);
+        for src in ["{\"\":1}", "[0, {\"a\": {\"\": 1}}]"].iter() {
+            let e = Reader::with_options(src, opts()).parse().unwrap_err();
+            assert_eq!("member key is empty", e.desc);
+            assert_eq!(ErrorKind::Syntax, e.kind);
+            let e2 = Reader::with_options(src, opts()).parse_ref().unwrap_err();
+            assert_eq!(e, e2);
+        }
+        let e = Reader::with_options("[0, {\"a\": {\"\": 1}}]", opts())
+            .parse()
+            .unwrap_err();
+        assert_eq!((1, 12), (e.row, e.column));
+        let v = Reader::with_options("{\" \": 1, \"a\": {}}", opts()).parse();
+        assert!(v.is_ok());
+    }
+
+    #[test]
+    fn test_key_validator() {
+        let opts = || ParseOptions::new().key_validator(Box::new(|k: &str| !k.contains(' ')));
+        let src = "{\"a\": 1, \"b_c\": {\"d\": [{\"ok\": null}]}}";
+        assert!(Reader::with_options(src, opts()).parse().is_ok());
+
+        let cases = [
+            ("{\"bad key\": 1}", "bad key", 1, 2),
+            ("{\"a\": {\"b\": {\"c d\": []}}}", "c d", 1, 14),
+            ("[1, {\"a\": [\n  {\"x\": 1,\n   \" y\": 2}]}]", " y", 3, 4),
+        ];
+        for (src, key, row, column) in cases.iter() {
+            let e = Reader::with_options(src, opts()).parse().unwrap_err();
+            let kind = ErrorKind::InvalidKey(Box::new(InvalidKey {
+                key: key.to_string(),
+                position: Position {
+                    row: *row,
+                    column: *column,
+                },
+            }));
+            assert_eq!(kind, e.kind, "{}", src);
+            assert_eq!((*row, *column), (e.row, e.column));
+            assert_eq!(format!("invalid key '{}'", key), e.desc);
+            let e2 = Reader::with_options(src, opts()).parse_ref().unwrap_err();
+            assert_eq!(e, e2);
+        }
+
+        // called once per key, in order, before the value is read
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let opts = ParseOptions::new().key_validator(Box::new(move |k: &str| {
+            log.lock().unwrap().push(k.to_string());
+            k != "stop"
+        }));
+        let e = Reader::with_options("{\"a\": {\"b\": 1}, \"stop\": [, \"c\": 2}", opts)
+            .parse()
+            .unwrap_err();
+        assert_eq!("invalid key 'stop'", e.desc);
+        assert_eq!(vec!["a", "b", "stop"], *seen.lock().unwrap());
+    }
+
+    #[test]
+    fn test_parse_raw_value() {
+        let src = "{\"a\":[1,2]}";
+        assert_eq!(Ok(src), Reader::new(src).parse_raw_value());
+        let src = " \n{ \"a\" : [ 1 ,2 ],\"b\":\"\\u00e9\" }\t";
+        assert_eq!(
+            Ok(&src[2..src.len() - 1]),
+            Reader::new(src).parse_raw_value()
+        );
+        let src = "[/* kept */ 1e400] // not kept";
+        let opts = ParseOptions::new()
+            .allow_comments(true)
+            .allow_overflow_to_infinity(true);
+        let raw = Reader::with_options(src, opts).parse_raw_value().unwrap();
+        assert_eq!("[/* kept */ 1e400]", raw);
+        assert_eq!(Ok("\"x\""), Reader::new("\"x\"").parse_raw_value());
+
+        for src in ["{\"a\":[1,2}", "[1] 2", "", "[1e400]"].iter() {
+            let e = Reader::new(src).parse_raw_value().unwrap_err();
+            assert_eq!(Reader::new(src).parse().unwrap_err(), e);
+        }
+        let opts = || ParseOptions::new().max_depth(1);
+        let e = Reader::with_options("[[]]", opts()).parse_raw_value();
+        let expect = Reader::with_options("[[]]", opts()).parse();
+        assert_eq!(expect.unwrap_err(), e.unwrap_err());
+    }
+
+    #[test]
+    fn test_deep_size() {
+        let value = std::mem::size_of::<Value>();
+        assert_eq!(0, Value::Null.deep_size());
+        assert_eq!(0, Value::String(String::new()).deep_size());
+        assert_eq!(32, Value::String(String::with_capacity(32)).deep_size());
+
+        let mut arr = Vec::with_capacity(4);
+        arr.push(Value::String(String::with_capacity(10)));
+        arr.push(Value::Array(Vec::with_capacity(2)));
+        arr.push(Value::Number(1.0));
+        assert_eq!(4 * value + 10 + 2 * value, Value::Array(arr).deep_size());
+
+        // three members with their keys, and two 1000 byte strings
+        let long = "x".repeat(1000);
+        let src = format!("{{\"a\": \"{0}\", \"b\": [\"{0}\"], \"c\": null}}", long);
+        for document_mode in [false, true].iter() {
+            let opts = ParseOptions::new().document_mode(*document_mode);
+            let v = Reader::with_options(&src, opts).parse().unwrap();
+            let size = v.deep_size();
+            let least = 2000 + 4 * value;
+            assert!(least <= size && size <= least + 64 * value, "{}", size);
+        }
+    }
+
+    #[test]
+    fn test_map_strings() {
+        let src = "{\"name\": \"ann\", \"tags\": [\"a\", 1, {\"k\": \"é\"}], \"n\": null}";
+        let mut v = Reader::new(src).parse().unwrap();
+        v.map_strings(false, str::to_uppercase);
+        let expect = "{\"name\": \"ANN\", \"tags\": [\"A\", 1, {\"k\": \"É\"}], \"n\": null}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), v);
+
+        v.map_strings(true, |s| format!("_{}", s));
+        let expect =
+            "{\"_name\": \"_ANN\", \"_tags\": [\"_A\", 1, {\"_k\": \"_É\"}], \"_n\": null}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), v);
+
+        let mut s = Value::String(String::from("x"));
+        let mut calls = 0;
+        s.map_strings(true, |s| {
+            calls += 1;
+            s.repeat(2)
+        });
+        assert_eq!((Value::String(String::from("xx")), 1), (s, calls));
+
+        // keys mapped to one string collide
+        let mut v = Reader::new("{\"a\": 1, \"A\": 2}").parse().unwrap();
+        v.map_strings(true, str::to_lowercase);
+        assert_eq!(1, v.into_object().unwrap().len());
+    }
+
+    #[test]
+    fn test_as_mut() {
+        let mut v = Reader::new("{\"a\": [1, 2, 3], \"b\": {}}")
+            .parse()
+            .unwrap();
+        let m = v.as_object_mut().unwrap();
+        m.insert(String::from("c"), Value::Bool(true));
+        let a = m.get_mut("a").and_then(Value::as_array_mut).unwrap();
+        a.retain(|x| x != &Value::Number(2.0));
+        a.push(Value::Null);
+        let b = v.entry("b").as_object_mut().unwrap();
+        b.insert(String::from("x"), Value::Number(0.0));
+
+        let expect = "{\"a\": [1, 3, null], \"b\": {\"x\": 0}, \"c\": true}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), v);
+        assert_eq!(Some(3), v.get("a").and_then(Value::as_array).map(Vec::len));
+        assert_eq!(Some(3), v.as_object().map(Map::len));
+
+        assert!(v.as_array_mut().is_none());
+        assert!(v.entry("a").as_object_mut().is_none());
+        assert!(Value::Null.as_object().is_none());
+        assert!(Value::String(String::new()).as_array().is_none());
+    }
+
+    #[test]
+    fn test_get_or() {
+        static PORT: Value = Value::Number(8080.0);
+        let v = Reader::new("{\"host\":\"a\",\"port\":null}")
+            .parse()
+            .unwrap();
+        let host = Value::String(String::from("a"));
+        assert_eq!(&host, v.get_or("host", &Value::Null));
+        // present but null is still present
+        assert_eq!(&Value::Null, v.get_or("port", &PORT));
+        assert_eq!(&PORT, v.get_or("timeout", &PORT));
+        assert_eq!(&Value::Null, Value::Bool(true).get_or("host", &Value::Null));
+    }
+
+    #[test]
+    fn test_is_container() {
+        let v = Reader::new("[null, true, 1, \"s\", [], {}]")
+            .parse()
+            .unwrap();
+        let kinds: Vec<_> = match &v {
+            Value::Array(a) => a
+                .iter()
+                .map(|x| (x.is_container(), x.is_scalar()))
+                .collect(),
+            _ => unreachable!(),
+        };
+        let (c, s) = ((true, false), (false, true));
+        assert_eq!(vec![s, s, s, s, c, c], kinds);
+        assert!(v.is_container());
+    }
+
+    #[test]
+    fn test_into() {
+        let big = "x".repeat(1 << 20);
+        let src = format!("{{\"a\": [1, {{\"s\": \"{}\"}}], \"b\": true}}", big);
+        let mut v = Reader::new(&src).parse().unwrap();
+        let s = match v.entry("a") {
+            Value::Array(a) => a[1].entry("s").take_string(),
+            _ => None,
+        };
+        assert_eq!(Some(big), s);
+        let rest = Reader::new("{\"a\": [1, {\"s\": null}], \"b\": true}")
+            .parse()
+            .unwrap();
+        assert_eq!(rest, v);
+        assert_eq!(None, v.entry("b").take_string());
+        assert_eq!(Some(&Value::Bool(true)), v.get("b"));
+
+        let m = v.clone().into_object().unwrap();
+        assert_eq!(2, m.len());
+        assert_eq!(Err(v.clone()), v.clone().into_array());
+        assert_eq!(Err(v.clone()), v.into_string());
+        let arr = Value::Array(vec![Value::Null]).into_array();
+        assert_eq!(Ok(vec![Value::Null]), arr);
+        let arr = Value::Array(vec![Value::Null]);
+        assert_eq!(Err(arr.clone()), arr.clone().into_object());
+        assert_eq!(Err(Value::Null), Value::Null.into_object());
+        let m: HashMap<String, Value> = Reader::new("{\"k\": 1}")
+            .parse()
+            .unwrap()
+            .into_object()
+            .unwrap()
+            .into();
+        assert_eq!(Some(&Value::Number(1.0)), m.get("k"));
+
+        let opts = ParseOptions::new().document_mode(true);
+        let v = Reader::with_options("{\"k\": 1, \"k\": [2]}", opts)
+            .parse()
+            .unwrap();
+        let m = v.clone().try_into_object().unwrap();
+        assert_eq!(1, m.len());
+        let k = m["k"].clone().try_into_array();
+        assert_eq!(Ok(vec![Value::Number(2.0)]), k);
+        assert_eq!(Err(v.clone()), v.clone().try_into_array());
+        let arr = Value::Array(vec![Value::Null]);
+        assert_eq!(Err(arr.clone()), arr.clone().try_into_object());
+        assert_eq!(Err(Value::Null), Value::Null.try_into_array());
+
+        // the same buffer comes back
+        let s = String::from("moved, not cloned");
+        let ptr = s.as_ptr();
+        let s = Value::String(s).into_string().ok().unwrap();
+        assert_eq!(ptr, s.as_ptr());
+        assert_eq!(None, Value::Null.into_string().ok());
+        assert_eq!(Some("s"), Value::String(String::from("s")).as_str());
+        assert_eq!(None, Value::Bool(true).as_str());
+        assert_eq!(
+            Ok(String::from("s")),
+            Value::String(String::from("s")).into_string()
+        );
+    }
+
+    #[test]
+    fn test_column_unit() {
+        let src = "[\"é\", \"中\", \"😀\", x]";
+        let column = |unit| {
+            let opts = ParseOptions::new().column_unit(unit);
+            Reader::with_options(src, opts).parse().unwrap_err().column
+        };
+        assert_eq!(17, column(ColumnUnit::Chars));
+        assert_eq!(23, column(ColumnUnit::Utf8Bytes));
+        assert_eq!(18, column(ColumnUnit::Utf16CodeUnits));
+        assert_eq!(23, src.find('x').unwrap() + 1);
+
+        let opts = ParseOptions::new().column_unit(ColumnUnit::Utf16CodeUnits);
+        let (_, spans) = Reader::with_options("{\"😀\": 1}", opts)
+            .parse_with_spans()
+            .unwrap();
+        assert_eq!(8, spans.get("/😀").unwrap().col);
+    }
+
+    #[test]
+    fn test_unexpected_token() {
+        let cases = [
+            ("[,1]", "unexpected ',' — expected a value", 2),
+            ("{\"a\":,}", "unexpected ',' — expected a value", 6),
+            ("{\"a\"::1}", "unexpected ':' — expected a value", 6),
+            ("[1,]", "unexpected ']' — expected a value", 4),
+            ("{\"a\":}", "unexpected '}' — expected a value", 6),
+            ("}", "unexpected '}' — expected a value", 1),
+            ("[1, *]", "unexpected character '*'", 5),
+            ("=", "unexpected character '='", 1),
+        ];
+        for (src, desc, column) in cases.iter() {
+            let e = Reader::new(src).parse().unwrap_err();
+            assert_eq!(ErrorKind::UnexpectedToken, e.kind, "{}", src);
+            assert_eq!((*desc, 1, *column), (e.desc.as_str(), e.row, e.column));
+        }
+        let e = Reader::new("[-x]").parse().unwrap_err();
+        assert_eq!(ErrorKind::Syntax, e.kind);
+
+        let cases = [
+            ("", "unexpected end of input", 1),
+            ("[1,", "unexpected end of input", 4),
+            ("{\"a\": ", "unexpected end of input", 7),
+            ("-", "number integer expect '0..9'", 2),
+        ];
+        for (src, desc, column) in cases.iter() {
+            let e = Reader::new(src).parse().unwrap_err();
+            assert_eq!(ErrorKind::Syntax, e.kind, "{}", src);
+            assert_eq!((*desc, 1, *column), (e.desc.as_str(), e.row, e.column));
+        }
+        let e = Reader::new("  \n").parse().unwrap_err();
+        assert_eq!(
+            ("unexpected end of input", 2, 1),
+            (e.desc.as_str(), e.row, e.column)
+        );
+    }
+
+    #[test]
+    fn test_number_followed_by_letters() {
+        let e = Reader::new("123abc").parse().unwrap_err();
+        assert_eq!("unexpected character 'a' after number", e.desc);
+        assert_eq!(
+            (ErrorKind::UnexpectedToken, 1, 4),
+            (e.kind, e.row, e.column)
+        );
+
+        let e = Reader::new("[1x]").parse().unwrap_err();
+        assert_eq!("unexpected character 'x' after number", e.desc);
+        assert_eq!((1, 3), (e.row, e.column));
+        let e = Reader::new("{\"a\": -1.5e3é}").parse().unwrap_err();
+        assert_eq!("unexpected character 'é' after number", e.desc);
+        assert_eq!((1, 13), (e.row, e.column));
+
+        // punctuation is left to the container
+        let e = Reader::new("[1@]").parse().unwrap_err();
+        assert_eq!("array end char expect ']'", e.desc);
+    }
+
+    #[test]
+    fn test_parse_number_info() {
+        let (v, info) = Reader::new("1.5e10").parse_number_info().unwrap();
+        assert_eq!(Value::Number(1.5e10), v);
+        let expect = NumberInfo {
+            integer_digits: 1,
+            has_fraction: true,
+            has_exponent: true,
+        };
+        assert_eq!(expect, info);
+
+        let (_, info) = Reader::new(" -12345678901234567890 ")
+            .parse_number_info()
+            .unwrap();
+        assert_eq!(20, info.integer_digits);
+        assert!(!info.has_fraction && !info.has_exponent);
+        let (_, info) = Reader::new("-0.25").parse_number_info().unwrap();
+        assert_eq!(
+            (1, true, false),
+            (info.integer_digits, info.has_fraction, info.has_exponent)
+        );
+
+        assert!(Reader::new("1 2").parse_number_info().is_err());
+        assert!(Reader::new("\"1\"").parse_number_info().is_err());
+    }
+
+    #[test]
+    fn test_position() {
+        let mut r = Reader::new("{\"日\":\n  [1, 2]}");
+        assert_eq!((1, 1, 0), r.position());
+        r.next();
+        r.next();
+        assert_eq!((1, 3, 2), r.position());
+        r.next();
+        r.next();
+        r.next();
+        r.parse_whitespace().unwrap();
+        assert_eq!(Some('['), r.peek());
+        assert_eq!((2, 3, 10), r.position());
+
+        assert!(r.parse().is_ok());
+        assert_eq!((2, 10, 17), r.position());
+
+        let mut r = Reader::new("[1,\n tru]");
+        assert!(r.parse().is_err());
+        assert_eq!((2, 2, 5), r.position());
+    }
+
+    #[test]
+    fn test_whitespace_position() {
+        let e = Reader::new("  \n  true x").parse().unwrap_err();
+        assert_eq!((2, 8), (e.row, e.column));
+        let e = Reader::new("  \n  tru").parse().unwrap_err();
+        assert_eq!((2, 3), (e.row, e.column));
+
+        let mut r = Reader::new("  \n  true");
+        assert!(r.parse().is_ok());
+        assert_eq!((2, 7, 9), r.position());
+
+        // one line break each
+        let e = Reader::new("[1,\r\n2,\r3,\n\n x]").parse().unwrap_err();
+        assert_eq!((5, 2), (e.row, e.column));
+        let opts = ParseOptions::new().allow_comments(true);
+        let e = Reader::with_options("// a\r\n/* b\n c */ x", opts)
+            .parse()
+            .unwrap_err();
+        assert_eq!((3, 7), (e.row, e.column));
+    }
+
+    #[test]
+    fn test_dangling_escape() {
+        let e = Reader::new("\"abc\\").parse().unwrap_err();
+        assert_eq!("incomplete escape sequence at end of input", e.desc);
+        assert_eq!((1, 5), (e.row, e.column));
+        let e = Reader::new("[\"a\",\n \"\\").parse_ref().unwrap_err();
+        assert_eq!("incomplete escape sequence at end of input", e.desc);
+        assert_eq!((2, 3), (e.row, e.column));
+        let e = Reader::new("\"abc").parse().unwrap_err();
+        assert_eq!("string end char expect '\"'", e.desc);
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let lines = ["{", "  \"a\": [1,", "", "    2],", "  \"b\": x", "}"];
+        for eol in ["\n", "\r\n", "\r"].iter() {
+            let e = Reader::new(&lines.join(eol)).parse().unwrap_err();
+            assert_eq!((5, 8), (e.row, e.column), "{:?}", eol);
+        }
+        // as an editor shows it: "{", "[1,", "", "2,", "x]"
+        let mixed = "{\r\n\"a\": [1,\n\r\n2,\rx]}";
+        let e = Reader::new(mixed).parse().unwrap_err();
+        assert_eq!((5, 1), (e.row, e.column));
+
+        let opts = ParseOptions::new().allow_comments(true);
+        let e = Reader::with_options("// a\r[1, x]", opts)
+            .parse()
+            .unwrap_err();
+        assert_eq!((2, 5), (e.row, e.column));
+    }
+
+    #[test]
+    fn test_string_transform() {
+        let src = "{\"a\": \"x\", \"b\": [\"y\", {\"c\": \"z\\u00e9\"}], \"n\": 1}";
+        let v = Reader::new(src)
+            .with_string_transform(|s| Some(Value::String(s.to_uppercase())))
+            .parse()
+            .unwrap();
+        let expect = "{\"a\": \"X\", \"b\": [\"Y\", {\"c\": \"ZÉ\"}], \"n\": 1}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), v);
+
+        let v = Reader::new("[\"1h\", \"x\"]")
+            .with_string_transform(|s| {
+                let h: f64 = s.strip_suffix('h')?.parse().ok()?;
+                Some(Value::Number(h * 3600.0))
+            })
+            .parse()
+            .unwrap();
+        let expect = vec![Value::Number(3600.0), Value::String(String::from("x"))];
+        assert_eq!(Value::Array(expect), v);
+    }
+
+    #[test]
+    fn test_unquoted_keys() {
+        let src = "{ name: \"x\", _id$2: 1, \"quoted\": {été: []} }";
+        let e = Reader::new(src).parse().unwrap_err();
+        assert_eq!("object key must be a string", e.desc);
+        assert_eq!((1, 3), (e.row, e.column));
+
+        let lenient = || ParseOptions::new().allow_unquoted_keys(true);
+        let v = Reader::with_options(src, lenient()).parse().unwrap();
+        let expect = "{\"name\": \"x\", \"_id$2\": 1, \"quoted\": {\"été\": []}}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), v);
+        let v = Reader::with_options(src, lenient()).parse_ref().unwrap();
+        assert_eq!(Reader::new(expect).parse().unwrap(), v.to_value());
+
+        let e = Reader::with_options("{2x: 1}", lenient())
+            .parse()
+            .unwrap_err();
+        assert_eq!("object key must be a string", e.desc);
+        let e = Reader::with_options("{a-b: 1}", lenient())
+            .parse()
+            .unwrap_err();
+        assert_eq!("member expect ':'", e.desc);
+
+        let e = Reader::new("{\"a\": 1, 2: 3}").parse_ref().unwrap_err();
+        assert_eq!("object key must be a string", e.desc);
+        assert_eq!((1, 10), (e.row, e.column));
+    }
+
+    #[test]
+    fn test_single_quotes() {
+        let src = "{'a': 'it\\'s \"x\"', \"b\": ['\\u00e9\\n', \"'\"]}";
+        let e = Reader::new(src).parse().unwrap_err();
+        assert_eq!("object key must be a string", e.desc);
+
+        let lenient = || ParseOptions::new().allow_single_quotes(true);
+        let v = Reader::with_options(src, lenient()).parse().unwrap();
+        let expect = "{\"a\": \"it's \\\"x\\\"\", \"b\": [\"é\\n\", \"'\"]}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), v);
+        let v = Reader::with_options(src, lenient()).parse_ref().unwrap();
+        assert_eq!(Reader::new(expect).parse().unwrap(), v.to_value());
+
+        // the closing quote matches the opening one
+        let e = Reader::with_options("['a\"]", lenient())
+            .parse()
+            .unwrap_err();
+        assert_eq!("string end char expect '''", e.desc);
+        let e = Reader::with_options("[\"a\\'\"]", lenient())
+            .parse()
+            .unwrap_err();
+        assert_eq!("string unknown escape '\\''", e.desc);
+        let e = Reader::new("['a']").parse().unwrap_err();
+        assert_eq!(ErrorKind::UnexpectedToken, e.kind);
+    }
+
+    #[test]
+    fn test_hex_and_plus_numbers() {
+        let lenient = || {
+            ParseOptions::new()
+                .allow_hex_numbers(true)
+                .allow_plus_sign(true)
+        };
+        let cases = [
+            ("0xFF", 255.0),
+            ("0Xff", 255.0),
+            ("-0x10", -16.0),
+            ("+0x1f", 31.0),
+            ("+5", 5.0),
+            ("+1.5e2", 150.0),
+            ("0", 0.0),
+        ];
+        for (src, expect) in cases.iter() {
+            let v = Reader::with_options(src, lenient()).parse();
+            assert_eq!(Ok(Value::Number(*expect)), v, "{}", src);
+            let v = Reader::with_options(src, lenient()).parse_ref().unwrap();
+            assert_eq!(Value::Number(*expect), v.to_value(), "{}", src);
+        }
+        let src = "[0xFF, +5]";
+        let v = Reader::with_options(src, lenient()).parse().unwrap();
+        assert_eq!(Reader::new("[255, 5]").parse().unwrap(), v);
+
+        // strict by default
+        let e = Reader::new("0xFF").parse().unwrap_err();
+        assert_eq!("unexpected character 'x' after number", e.desc);
+        let e = Reader::new("+5").parse().unwrap_err();
+        assert_eq!("unexpected character '+'", e.desc);
+        let e = Reader::new("[1, +5]").parse().unwrap_err();
+        assert_eq!((1, 5), (e.row, e.column));
+        let hex_only = ParseOptions::new().allow_hex_numbers(true);
+        assert!(Reader::with_options("+0x1", hex_only).parse().is_err());
+
+        let errors = [
+            ("0x", "number hex expect '0..9a..f'", 3),
+            ("0xfg", "unexpected character 'g' after number", 4),
+            ("0x1.5", "value not finished '.5'", 4),
+            ("+-1", "number integer expect '0..9'", 2),
+            ("++1", "number integer expect '0..9'", 2),
+        ];
+        for (src, desc, column) in errors.iter() {
+            let e = Reader::with_options(src, lenient()).parse().unwrap_err();
+            assert_eq!(
+                (*desc, 1, *column),
+                (e.desc.as_str(), e.row, e.column),
+                "{}",
+                src
+            );
+        }
+
+        let e = Reader::with_options(&format!("[1, 0x{}]", "f".repeat(300)), lenient())
+            .parse()
+            .unwrap_err();
+        assert_eq!(ErrorKind::NumberOutOfRange, e.kind);
+        assert_eq!((1, 5), (e.row, e.column));
+        let opts = lenient().allow_nan(true);
+        let v = Reader::with_options("+Infinity", opts).parse();
+        assert_eq!(Ok(Value::Number(f64::INFINITY)), v);
+    }
+
+    #[test]
+    fn test_parse_whitespace() {
+        let mut r = Reader::new("  {}");
+        r.parse_whitespace().unwrap();
+        assert_eq!("{}", r.context);
+    }
 
     #[test]
     fn test_parse_null() {
@@ -507,17 +2668,17 @@ mod tests {
     #[test]
     fn test_parse_false() {
         let mut r = Reader::new("false");
-        assert!(r.parse_literal(Value::False, "false").is_ok());
+        assert!(r.parse_literal(Value::Bool(false), "false").is_ok());
         assert!(r.parse().is_ok());
-        assert_eq!(r.parse().unwrap(), Value::False);
+        assert_eq!(r.parse().unwrap(), Value::Bool(false));
     }
 
     #[test]
     fn test_parse_true() {
         let mut r = Reader::new("true");
-        let x = r.parse_literal(Value::True, "true");
+        let x = r.parse_literal(Value::Bool(true), "true");
         assert!(x.is_ok(), "{}", x.unwrap_err().desc);
-        assert_eq!(r.parse().unwrap(), Value::True);
+        assert_eq!(r.parse().unwrap(), Value::Bool(true));
     }
 
     #[test]
@@ -525,139 +2686,658 @@ mod tests {
         let mut r = Reader::new("0");
         let x = r.parse_number();
         assert!(x.is_ok(), "{}", x.unwrap_err().desc);
-        assert_eq!(r.parse().unwrap(), Value::Number { v: 0.0 });
+        assert_eq!(r.parse().unwrap(), Value::Number(0.0));
 
         let mut r1 = Reader::new("-0.1");
         let x1 = r1.parse_number();
         assert!(x1.is_ok(), "{}", x1.unwrap_err().desc);
-        assert_eq!(r1.parse().unwrap(), Value::Number { v: -0.1 });
+        assert_eq!(r1.parse().unwrap(), Value::Number(-0.1));
 
         let mut r2 = Reader::new("0.");
         let x2 = r2.parse_number();
-        assert!(x2.is_ok(), "{}", x2.unwrap_err().desc);
-        assert_eq!(r2.parse().unwrap(), Value::Number { v: 0.0 });
+        assert_eq!("number fraction expect '0..9'", x2.unwrap_err().desc);
+        assert!(r2.parse().is_err());
 
         let mut r3 = Reader::new("12345");
         let x3 = r3.parse_number();
         assert!(x3.is_ok(), "{}", x3.unwrap_err().desc);
-        assert_eq!(r3.parse().unwrap(), Value::Number { v: 12345.0 });
+        assert_eq!(r3.parse().unwrap(), Value::Number(12345.0));
 
         let mut r4 = Reader::new("-12345");
         let x4 = r4.parse_number();
         assert!(x4.is_ok(), "{}", x4.unwrap_err().desc);
-        assert_eq!(r4.parse().unwrap(), Value::Number { v: -12345.0 });
+        assert_eq!(r4.parse().unwrap(), Value::Number(-12345.0));
+
+        let mut r5 = Reader::new("1.5");
+        assert_eq!(r5.parse().unwrap(), Value::Number(1.5));
+
+        let mut r6 = Reader::new("1.25e3");
+        assert_eq!(r6.parse().unwrap(), Value::Number(1250.0));
+
+        let mut r7 = Reader::new("[1.5E-1,2e+2]");
+        assert_eq!(
+            r7.parse().unwrap(),
+            Value::Array(vec![Value::Number(0.15), Value::Number(200.0)])
+        );
+
+        assert!(Reader::new("1.e3").parse().is_err());
+        assert!(Reader::new("1e").parse().is_err());
+        assert!(Reader::new("1e+").parse().is_err());
+        assert!(Reader::new("1+2").parse().is_err());
+        assert!(Reader::new("[1.5x]").parse().is_err());
     }
 
     #[test]
     fn test_prase_string() {
         let mut r = Reader::new("\"\"");
-        let x = r.parse_string();
+        let x = r.parse();
         assert!(x.is_ok(), "{}", x.unwrap_err().desc);
-        assert_eq!(
-            x.unwrap(),
-            Value::String {
-                v: String::from("")
-            }
-        );
+        assert_eq!(x.unwrap(), Value::String(String::from("")));
 
         let mut r1 = Reader::new("\"string\"");
-        let x1 = r1.parse_string();
+        let x1 = r1.parse();
         assert!(x1.is_ok(), "{}", x1.unwrap_err().desc);
-        assert_eq!(
-            x1.unwrap(),
-            Value::String {
-                v: String::from("string")
-            }
-        );
+        assert_eq!(x1.unwrap(), Value::String(String::from("string")));
 
         let mut r2 = Reader::new("\"\\\"\"");
-        let x2 = r2.parse_string();
+        let x2 = r2.parse();
         assert!(x2.is_ok(), "{}", x2.unwrap_err().desc);
-        assert_eq!(
-            x2.unwrap(),
-            Value::String {
-                v: String::from("\"")
-            }
-        );
+        assert_eq!(x2.unwrap(), Value::String(String::from("\"")));
 
         let mut r3 = Reader::new("\"\\\"\\\\\\/\\n\\r\\t/\"");
-        let x3 = r3.parse_string();
+        let x3 = r3.parse();
         assert!(x3.is_ok(), "{}", x3.unwrap_err().desc);
-        assert_eq!(
-            x3.unwrap(),
-            Value::String {
-                v: String::from("\"\\/\n\r\t/")
-            }
-        );
+        assert_eq!(x3.unwrap(), Value::String(String::from("\"\\/\n\r\t/")));
     }
 
     #[test]
     fn test_parse_array() {
         let mut r = Reader::new("[]");
-        let x = r.parse_array();
+        let x = r.parse();
         assert!(x.is_ok(), "{}", x.unwrap_err().desc);
-        assert_eq!(x.unwrap(), Value::Array { v: vec![] });
+        assert_eq!(x.unwrap(), Value::Array(vec![]));
 
         let mut r1 = Reader::new("[false, true,null]");
-        let x1 = r1.parse_array();
+        let x1 = r1.parse();
         assert!(x1.is_ok(), "{}", x1.unwrap_err().desc);
         assert_eq!(
             x1.unwrap(),
-            Value::Array {
-                v: vec![Value::False, Value::True, Value::Null]
-            }
+            Value::Array(vec![Value::Bool(false), Value::Bool(true), Value::Null])
         );
 
         let mut r2 = Reader::new("[[false,true, false], [null]]");
-        let x2 = r2.parse_array();
+        let x2 = r2.parse();
         assert!(x2.is_ok(), "{}", x2.unwrap_err().desc);
         assert_eq!(
             x2.unwrap(),
-            Value::Array {
-                v: vec![
-                    Value::Array {
-                        v: vec![Value::False, Value::True, Value::False]
-                    },
-                    Value::Array {
-                        v: vec![Value::Null]
-                    }
-                ]
-            }
+            Value::Array(vec![
+                Value::Array(vec![
+                    Value::Bool(false),
+                    Value::Bool(true),
+                    Value::Bool(false)
+                ]),
+                Value::Array(vec![Value::Null])
+            ])
         );
     }
 
     #[test]
     fn test_parse_object() {
         let mut r = Reader::new("{}");
-        let x = r.parse_object();
+        let x = r.parse();
         assert!(x.is_ok(), "{}", x.unwrap_err().desc);
-        assert_eq!(x.unwrap(), Value::Object { v: HashMap::new() });
+        assert_eq!(x.unwrap(), Value::Object(Map::new()));
 
         let mut r1 = Reader::new("{\"hello\":true}");
         let mut m1 = HashMap::new();
-        m1.insert("hello".to_string(), Value::True);
-        let x1 = r1.parse_object();
+        m1.insert("hello".to_string(), Value::Bool(true));
+        let x1 = r1.parse();
         assert!(x1.is_ok(), "{}", x1.unwrap_err().desc);
-        assert_eq!(x1.unwrap(), Value::Object { v: m1 });
+        assert_eq!(x1.unwrap(), Value::Object(m1.into()));
 
         let mut r2 =
             Reader::new("{\"name\":\"zxh\",\"option\":[true,false,3.14159],\"open\":null}");
         let mut m2 = HashMap::new();
-        m2.insert(
-            "name".to_string(),
-            Value::String {
-                v: "zxh".to_string(),
-            },
-        );
+        m2.insert("name".to_string(), Value::String("zxh".to_string()));
         m2.insert("open".to_string(), Value::Null);
         m2.insert(
             "option".to_string(),
-            Value::Array {
-                v: vec![Value::True, Value::False, Value::Number { v: 3.14159 }],
-            },
+            Value::Array(vec![
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Number(3.14159),
+            ]),
         );
-        let x2 = r2.parse_object();
+        let x2 = r2.parse();
         assert!(x2.is_ok(), "{}", x2.unwrap_err().desc);
-        assert_eq!(x2.unwrap(), Value::Object { v: m2 });
+        assert_eq!(x2.unwrap(), Value::Object(m2.into()));
+    }
+
+    #[test]
+    fn test_parse_recoverable() {
+        let (v, errors) = parse_with_recovery("{\"a\":[1 2],\"b\":true,}");
+        assert_eq!(2, errors.len());
+        assert_eq!("array elements expect ','", errors[0].desc);
+        assert_eq!("object trailing ','", errors[1].desc);
+
+        let mut m = HashMap::new();
+        m.insert(
+            "a".to_string(),
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]),
+        );
+        m.insert("b".to_string(), Value::Bool(true));
+        assert_eq!(v.unwrap(), Value::Object(m.into()));
+
+        let (v1, errors1) = parse_with_recovery("[true, nul, false]");
+        assert_eq!(1, errors1.len());
+        assert_eq!(
+            v1.unwrap(),
+            Value::Array(vec![Value::Bool(true), Value::Null, Value::Bool(false)])
+        );
+
+        let (v2, errors2) = parse_with_recovery("[null]");
+        assert!(errors2.is_empty());
+        assert_eq!(v2.unwrap(), Value::Array(vec![Value::Null]));
+    }
+
+    #[test]
+    fn test_recovery_stray_whitespace() {
+        for (src, expect, column) in [
+            ("[[\u{c}", "[[null]]", 3),
+            ("[1[\u{a0}", "[1, [null]]", 4),
+            ("{\"a\":[\u{c}", "{\"a\": [null]}", 7),
+        ]
+        .iter()
+        {
+            let (v, errors) = parse_with_recovery(src);
+            assert_eq!(Some(Reader::new(expect).parse().unwrap()), v, "{:?}", src);
+            let e = errors
+                .iter()
+                .find(|e| e.desc.starts_with("unexpected whitespace-like"))
+                .unwrap();
+            assert_eq!((1, *column), (e.row, e.column), "{:?}", src);
+            assert!(errors.iter().any(|e| e.desc == "array end char expect ']'"));
+        }
+    }
+
+    #[test]
+    fn test_recovery_independent_errors() {
+        let src = "{\n\"a\": [1, tru {\"x\": [2, \"]\"]}, 3],\n\"b\": -,\n\"c\": \"\\q\"\n}";
+        let (v, errors) = parse_with_recovery(src);
+        let found: Vec<_> = errors
+            .iter()
+            .map(|e| (e.row, e.column, e.desc.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (2, 10, "literal not eq true"),
+                (3, 7, "number integer expect '0..9'"),
+                (4, 8, "string unknown escape '\\q'")
+            ],
+            found
+        );
+        let expect = "{\"a\": [1, null, 3], \"b\": null, \"c\": null}";
+        assert_eq!(Reader::new(expect).parse().unwrap(), v.unwrap());
+    }
+
+    #[test]
+    fn test_recovery_unclosed() {
+        let (v, errors) = parse_with_recovery("{\"a\": [1, [2}");
+        let found: Vec<_> = errors.iter().map(|e| e.desc.as_str()).collect();
+        assert_eq!(
+            vec!["array end char expect ']'", "array end char expect ']'"],
+            found
+        );
+        let expect = Reader::new("{\"a\": [1, [2]]}").parse().unwrap();
+        assert_eq!(Some(expect), v);
+    }
+
+    #[test]
+    fn test_recovery_error_cap() {
+        let src = format!("[{}]", vec!["x"; 500].join(","));
+        let (v, errors) = parse_with_recovery(&src);
+        assert_eq!(MAX_RECOVERED_ERRORS, errors.len());
+        assert!(v.is_none());
+
+        let src = format!("[{}]", vec!["x"; 99].join(","));
+        let (v, errors) = parse_with_recovery(&src);
+        assert_eq!(99, errors.len());
+        assert_eq!(Some(Value::Array(vec![Value::Null; 99])), v);
+    }
+
+    #[test]
+    fn test_pointer() {
+        let mut r = Reader::new("{\"a\":[null,{\"b/c\":true,\"d~e\":false}],\"\":1}");
+        let v = r.parse().unwrap();
+        assert_eq!(Some(&v), v.pointer(""));
+        assert_eq!(Some(&Value::Null), v.pointer("/a/0"));
+        assert_eq!(Some(&Value::Bool(true)), v.pointer("/a/1/b~1c"));
+        assert_eq!(Some(&Value::Bool(false)), v.pointer("/a/1/d~0e"));
+        assert_eq!(Some(&Value::Number(1.0)), v.pointer("/"));
+        assert_eq!(None, v.pointer("a"));
+        assert_eq!(None, v.pointer("/a/2"));
+        assert_eq!(None, v.pointer("/a/01"));
+        assert_eq!(None, v.pointer("/a/0/x"));
+    }
+
+    fn hash_of(v: &Value) -> u64 {
+        let mut h = DefaultHasher::new();
+        v.hash(&mut h);
+        h.finish()
+    }
+
+    #[test]
+    fn test_negative_zero() {
+        let zero = Reader::new("0").parse().unwrap();
+        let neg = Reader::new("-0").parse().unwrap();
+        assert!(neg.is_negative_zero());
+        assert!(!zero.is_negative_zero());
+        assert_eq!(zero, neg);
+        assert_eq!(hash_of(&zero), hash_of(&neg));
+        assert_eq!(zero.to_string(), neg.to_string());
+        assert_eq!("0", neg.to_string());
+        assert_eq!("[0]", Reader::new("[-0.0]").parse().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_nan() {
+        assert!(Reader::new("NaN").parse().is_err());
+        assert!(Reader::new("[-Infinity]").parse().is_err());
+
+        let lenient = || ParseOptions::new().allow_nan(true);
+        let nan = Reader::with_options("NaN", lenient()).parse().unwrap();
+        let nan1 = Value::Number(-f64::NAN);
+        assert_eq!(nan, nan1);
+        assert_eq!(hash_of(&nan), hash_of(&nan1));
+        assert_eq!("null", nan.to_string());
+        let literal = WriteOptions::new().non_finite(NonFinite::Literal);
+        assert_eq!("NaN", nan.to_string_with(&literal));
+
+        let arr = Reader::with_options("[Infinity, -Infinity, NaN]", lenient())
+            .parse()
+            .unwrap();
+        assert_eq!("[null,null,null]", arr.to_string());
+        assert!(arr.write_to(&mut Vec::new()).is_err());
+        assert_eq!("[Infinity,-Infinity,NaN]", arr.to_string_with(&literal));
+        assert!(Reader::with_options("-Inf", lenient()).parse().is_err());
+    }
+
+    #[test]
+    fn test_hash_object() {
+        let x = Reader::new("{\"a\":1,\"b\":[true,null]}").parse().unwrap();
+        let y = Reader::new("{\"b\":[true,null],\"a\":1.0}")
+            .parse()
+            .unwrap();
+        assert_eq!(x, y);
+        assert_eq!(hash_of(&x), hash_of(&y));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(x);
+        assert!(set.contains(&y));
+        assert!(!set.contains(&Value::Null));
+    }
+
+    #[test]
+    fn test_number_overflow() {
+        let x = Reader::new("1e500").parse();
+        assert_eq!("number '1e500' out of range", x.unwrap_err().desc);
+        assert!(Reader::new("[-1e500]").parse().is_err());
+
+        let clamp = ParseOptions::new().on_number_overflow(OverflowPolicy::Clamp);
+        let x1 = Reader::with_options("[1e500,-1e500]", clamp).parse();
+        assert_eq!(
+            x1.unwrap(),
+            Value::Array(vec![Value::Number(f64::MAX), Value::Number(f64::MIN)])
+        );
+
+        let raw = ParseOptions::new().on_number_overflow(OverflowPolicy::Raw);
+        let x2 = Reader::with_options("1e500", raw).parse();
+        assert_eq!(x2.unwrap(), Value::Number(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_parse_error_new() {
+        let e = ParseError::new(3, 7, "unknown unit 'kph'");
+        assert_eq!(ErrorKind::Syntax, e.kind);
+        assert_eq!("parse unknown unit 'kph' at pos 3:7", e.to_string());
+
+        let e = ParseError::with_kind(ErrorKind::NumberOutOfRange, 1, 2, format!("{} > {}", 9, 5));
+        assert_eq!(ErrorKind::NumberOutOfRange, e.kind);
+        assert_eq!("parse 9 > 5 at pos 1:2", e.to_string());
+    }
+
+    #[test]
+    fn test_number_out_of_range() {
+        let e = Reader::new("[1, 1e400]").parse().unwrap_err();
+        assert_eq!(ErrorKind::NumberOutOfRange, e.kind);
+        assert_eq!((1, 5), (e.row, e.column));
+        let e = Reader::new("{\"a\":\n  -1e400}").parse().unwrap_err();
+        assert_eq!(ErrorKind::NumberOutOfRange, e.kind);
+        assert_eq!((2, 3), (e.row, e.column));
+        assert_eq!(Value::Number(0.0), Reader::new("1e-400").parse().unwrap());
+
+        let inf = ParseOptions::new().allow_overflow_to_infinity(true);
+        let v = Reader::with_options("[1e400,-1e400]", inf).parse().unwrap();
+        assert_eq!("[null,null]", v.to_string());
+        let literal = WriteOptions::new().non_finite(NonFinite::Literal);
+        assert_eq!("[Infinity,-Infinity]", v.to_string_with(&literal));
+        let strict = ParseOptions::new()
+            .allow_overflow_to_infinity(true)
+            .allow_overflow_to_infinity(false);
+        assert!(Reader::with_options("1e400", strict).parse().is_err());
+    }
+
+    #[test]
+    fn test_walk() {
+        let v = Reader::new("{\"a\":[true,{\"b\":null}],\"c\":1}")
+            .parse()
+            .unwrap();
+        let mut paths = Vec::new();
+        v.walk(&mut |path, x| paths.push((path.to_vec(), matches!(x, Value::Array(..)))));
+        paths.sort();
+
+        let key = |k: &str| PathSegment::Key(k.to_string());
+        assert_eq!(
+            paths,
+            vec![
+                (vec![], false),
+                (vec![key("a")], true),
+                (vec![key("a"), PathSegment::Index(0)], false),
+                (vec![key("a"), PathSegment::Index(1)], false),
+                (vec![key("a"), PathSegment::Index(1), key("b")], false),
+                (vec![key("c")], false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_progress_cancel() {
+        let mut big = String::from("[");
+        for _ in 0..100_000 {
+            big.push_str("1,");
+        }
+        big.push_str("1]");
+
+        let opts = ParseOptions::new().progress(
+            Box::new(|n| {
+                if n >= 1000 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }),
+            100,
+        );
+        let x = Reader::with_options(&big, opts).parse();
+        let e = x.unwrap_err();
+        assert_eq!(ErrorKind::Cancelled, e.kind);
+        assert_eq!(1, e.row);
+        assert!(e.column > 1000 && e.column <= 1101, "{}", e.column);
+
+        // inside one long string
+        let s = format!("\"{}\"", "x".repeat(1_000_000));
+        let opts = ParseOptions::new().progress(Box::new(|_| ControlFlow::Break(())), 4096);
+        let e = Reader::with_options(&s, opts).parse().unwrap_err();
+        assert_eq!(ErrorKind::Cancelled, e.kind);
+        assert_eq!(4097, e.column);
+    }
+
+    #[test]
+    fn test_progress_offsets() {
+        let doc = format!("[{}]", vec!["\"abc\"  "; 1000].join(","));
+        let seen = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let seen1 = seen.clone();
+        let opts = ParseOptions::new().progress(
+            Box::new(move |n| {
+                seen1.borrow_mut().push(n);
+                ControlFlow::Continue(())
+            }),
+            64,
+        );
+        assert!(Reader::with_options(&doc, opts).parse().is_ok());
+
+        let seen = seen.borrow();
+        assert!(seen.len() >= doc.len() / 64);
+        assert!(seen.windows(2).all(|w| w[0] < w[1] && w[1] - w[0] >= 64));
+        assert!(*seen.last().unwrap() <= doc.len());
+    }
+
+    #[test]
+    fn test_max_bytes() {
+        let doc = "[\"abc\", \"def\"]";
+        let opts = ParseOptions::new().max_bytes(doc.len());
+        assert!(Reader::with_options(doc, opts).parse().is_ok());
+
+        let opts = ParseOptions::new().max_bytes(8);
+        let e = Reader::with_options(doc, opts).parse().unwrap_err();
+        assert_eq!(ErrorKind::LimitExceeded, e.kind);
+        assert_eq!("input longer than 8 bytes", e.desc);
+        assert_eq!((1, 10), (e.row, e.column));
+
+        // inside one long string, before its end is seen
+        let s = format!("\"{}\"", "x".repeat(1_000_000));
+        let opts = ParseOptions::new().max_bytes(1000);
+        let e = Reader::with_options(&s, opts).parse().unwrap_err();
+        assert_eq!(ErrorKind::LimitExceeded, e.kind);
+        assert_eq!(1002, e.column);
+    }
+
+    #[test]
+    fn test_deadline() {
+        let s = format!("[\"{}\"]", "x".repeat(1_000_000));
+        let opts = ParseOptions::new().deadline(Instant::now());
+        let e = Reader::with_options(&s, opts).parse().unwrap_err();
+        assert_eq!(ErrorKind::DeadlineExceeded, e.kind);
+        assert_eq!("deadline exceeded after 4096 bytes", e.desc);
+
+        // checked at the progress interval when there is a callback
+        let opts = ParseOptions::new()
+            .deadline(Instant::now())
+            .progress(Box::new(|_| ControlFlow::Continue(())), 100);
+        let e = Reader::with_options(&s, opts).parse().unwrap_err();
+        assert_eq!(ErrorKind::DeadlineExceeded, e.kind);
+        assert_eq!(101, e.column);
+
+        let later = Instant::now() + std::time::Duration::from_secs(3600);
+        let opts = ParseOptions::new().deadline(later);
+        assert!(Reader::with_options(&s, opts).parse().is_ok());
+        assert!(Reader::new(&s).with_deadline(later).parse().is_ok());
+
+        // a big document stops long before its end
+        let items: Vec<String> = (0..200_000).map(|i| format!("{{\"n\":{}}}", i)).collect();
+        let s = format!("[{}]", items.join(","));
+        let soon = Instant::now() + std::time::Duration::from_micros(100);
+        let e = Reader::new(&s).with_deadline(soon).parse().unwrap_err();
+        assert_eq!(ErrorKind::DeadlineExceeded, e.kind);
+        assert!(e.column < s.len(), "{}", e.column);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let x = from_reader("[1, \"a\"]".as_bytes());
+        assert_eq!(
+            x.unwrap(),
+            Value::Array(vec![Value::Number(1.0), Value::String(String::from("a"))])
+        );
+
+        let e = from_reader(&[b'"', 0xff, b'"'][..]).unwrap_err();
+        assert_eq!(ErrorKind::Io, e.kind);
+    }
+
+    #[test]
+    fn test_multibyte_keys() {
+        let src = "{\"café\":\"crème\",\"日本語\":{\"ключ\":[\"😀\"]},\"a\":1}";
+        let v = Reader::new(src).parse().unwrap();
+        assert_eq!(Some(&Value::String(String::from("crème"))), v.get("café"));
+        assert_eq!(None, v.get("cafe"));
+        let nested = v.get("日本語").unwrap();
+        assert_eq!(
+            Some(&Value::String(String::from("😀"))),
+            nested.pointer("/ключ/0")
+        );
+        assert_eq!(Some(&Value::Number(1.0)), v.get("a"));
+        assert_eq!(None, Value::Null.get("a"));
+        assert_eq!(v, Reader::new(&v.to_string()).parse().unwrap());
+
+        // positions count chars, not bytes
+        let e = Reader::new("{\"日本\":tru}").parse().unwrap_err();
+        assert_eq!((1, 7), (e.row, e.column));
+        let e = Reader::new("[\"é\", nulé]").parse().unwrap_err();
+        assert_eq!((1, 7), (e.row, e.column));
+        assert!(Reader::new("nuxé").parse().is_err());
+    }
+
+    #[test]
+    fn test_as_integer() {
+        assert_eq!(Some(42), Value::Number(42.0).as_i64());
+        assert_eq!(Some(42), Value::Number(42.0).as_u64());
+        assert_eq!(None, Value::Number(42.5).as_i64());
+        assert_eq!(None, Value::Number(42.5).as_u64());
+        assert_eq!(Some(-7), Value::Number(-7.0).as_i64());
+        assert_eq!(None, Value::Number(-7.0).as_u64());
+        assert_eq!(None, Value::Number(1e19).as_i64());
+        assert_eq!(
+            Some(10_000_000_000_000_000_000),
+            Value::Number(1e19).as_u64()
+        );
+        assert_eq!(None, Value::Number(9.3e18).as_i64());
+        assert_eq!(Some(i64::MIN), Value::Number(i64::MIN as f64).as_i64());
+        assert_eq!(None, Value::Number(1e20).as_u64());
+        assert_eq!(None, Value::Number(f64::NAN).as_i64());
+        assert_eq!(None, Value::Number(f64::INFINITY).as_u64());
+        assert_eq!(None, Value::Bool(true).as_i64());
+        assert_eq!(Some(true), Value::Bool(true).as_bool());
+        assert_eq!(None, Value::Null.as_bool());
+    }
+
+    #[test]
+    fn test_control_chars() {
+        let v = Reader::new("[\t1,\n\t\"a\tb\" ]").parse();
+        assert!(v.is_err());
+        let e = v.unwrap_err();
+        assert_eq!("string control char '\\u0009' must be escaped", e.desc);
+        assert_eq!((2, 4), (e.row, e.column));
+
+        let v = Reader::new("[\t1,\r\n\t\"a\\tb\" ]").parse().unwrap();
+        let expect = Value::Array(vec![
+            Value::Number(1.0),
+            Value::String(String::from("a\tb")),
+        ]);
+        assert_eq!(expect, v);
+
+        assert!(Reader::new("\"a\nb\"").parse().is_err());
+        assert!(Reader::new("\"\x01\"").parse().is_err());
+        assert!(Reader::new("\x0C1").parse().is_err());
+        assert!(Reader::new("\"\x7f\"").parse().is_ok());
+    }
+
+    #[test]
+    fn test_extra_whitespace() {
+        let lenient = || ParseOptions::new().allow_extra_whitespace(true);
+        for ch in ['\x0C', '\x0B', '\u{a0}', '\u{2028}', '\u{3000}'] {
+            let before = format!("{}[1]", ch);
+            let between = format!("[1,{}2 ]", ch);
+            let after = format!("{{\"a\": 1}}{}", ch);
+            for (src, column) in [(&before, 1), (&between, 4), (&after, 9)] {
+                let e = Reader::new(src).parse().unwrap_err();
+                let desc = format!("unexpected whitespace-like character U+{:04X}", ch as u32);
+                assert_eq!((1, column, desc), (e.row, e.column, e.desc), "{:?}", src);
+
+                let v = Reader::with_options(src, lenient()).parse().unwrap();
+                assert_eq!(Reader::new(&src.replace(ch, " ")).parse().unwrap(), v);
+            }
+        }
+        // inside strings they are plain characters
+        assert!(Reader::new("\"\u{a0}\u{2028}\"").parse().is_ok());
+        let e = Reader::with_options("[1]\n\u{a0}x", lenient())
+            .parse()
+            .unwrap_err();
+        assert_eq!((2, 2), (e.row, e.column));
+    }
+
+    #[test]
+    fn test_max_string_length() {
+        let opts = ParseOptions::new().max_string_length(10);
+        let v = Reader::with_options("[\"0123456789\"]", opts).parse();
+        assert!(v.is_ok());
+
+        let opts = ParseOptions::new().max_string_length(10);
+        let e = Reader::with_options("[\"0123456789a\"]", opts)
+            .parse()
+            .unwrap_err();
+        assert_eq!("string exceeds maximum length", e.desc);
+        assert_eq!((1, 14), (e.row, e.column));
+
+        // counted after unescaping, keys included
+        let opts = ParseOptions::new().max_string_length(10);
+        let src = "{\"\\u0041\\u0042\\u0043\":\"x\"}";
+        assert!(Reader::with_options(src, opts).parse().is_ok());
+        let opts = ParseOptions::new().max_string_length(2);
+        assert!(Reader::with_options(src, opts).parse().is_err());
+        let opts = ParseOptions::new().max_string_length(2);
+        assert!(Reader::with_options(src, opts).parse_ref().is_err());
+    }
+
+    #[test]
+    fn test_max_elements() {
+        let list = |n| format!("[{}]", vec!["0"; n].join(","));
+        let opts = ParseOptions::new().max_elements(1000);
+        assert!(Reader::with_options(&list(1000), opts).parse().is_ok());
+        let opts = ParseOptions::new().max_elements(1000);
+        let e = Reader::with_options(&list(1001), opts).parse().unwrap_err();
+        assert_eq!("array exceeds 1000 elements", e.desc);
+        assert_eq!((1, 2002), (e.row, e.column));
+
+        // per container, not in total
+        let opts = ParseOptions::new().max_elements(2);
+        let src = "{\"a\":[1,2],\"b\":{\"c\":3,\"d\":4}}";
+        assert!(Reader::with_options(src, opts).parse().is_ok());
+        let opts = ParseOptions::new().max_elements(1);
+        let e = Reader::with_options(src, opts).parse().unwrap_err();
+        assert_eq!("array exceeds 1 elements", e.desc);
+        let opts = ParseOptions::new().max_elements(1);
+        let e = Reader::with_options("{\"c\":3,\"d\":4}", opts)
+            .parse()
+            .unwrap_err();
+        assert_eq!("object exceeds 1 members", e.desc);
+
+        let opts = ParseOptions::new().max_elements(1);
+        assert!(Reader::with_options("[[1,2]]", opts).parse_ref().is_err());
+        let (v, errors) = parse_with_recovery("[1,2,3]");
+        assert!(v.is_some() && errors.is_empty());
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let v = Reader::new("\"\\b\\f\\u0041\\u00e9\\uD834\\uDD1E\"").parse();
+        assert_eq!(
+            Value::String(String::from("\x08\x0CA\u{e9}\u{1d11e}")),
+            v.unwrap()
+        );
+
+        let e = Reader::new("\"\\a\"").parse().unwrap_err();
+        assert_eq!("string unknown escape '\\a'", e.desc);
+        let e = Reader::new("\"\\u00g0\"").parse().unwrap_err();
+        assert_eq!("string escape expect 4 hex digits", e.desc);
+        let e = Reader::new("\"\\uD800\"").parse().unwrap_err();
+        assert_eq!("string lone surrogate '\\ud800'", e.desc);
+        assert!(Reader::new("\"\\uDC00\"").parse().is_err());
+        assert!(Reader::new("\"\\uD800\\u0041\"").parse().is_err());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let nested = |n| "[".repeat(n) + &"]".repeat(n);
+        assert!(Reader::new(&nested(512)).parse().is_ok());
+        let e = Reader::new(&nested(513)).parse().unwrap_err();
+        assert_eq!("nesting deeper than 512", e.desc);
+        assert_eq!((1, 513), (e.row, e.column));
+
+        let opts = ParseOptions::new().max_depth(2);
+        let v = Reader::with_options("{\"a\":[1]}", opts).parse();
+        assert!(v.is_ok());
+        let opts = ParseOptions::new().max_depth(2);
+        assert!(Reader::with_options("{\"a\":[[]]}", opts).parse().is_err());
+        assert!(Reader::new(&"[".repeat(100_000)).parse().is_err());
     }
 }
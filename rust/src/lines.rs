@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{ParseError, Reader, Value};
+
+/// Why a line of JSON Lines input gave no value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineParseError {
+    /// The line does not hold exactly one value. The error's row is the
+    /// line's, counted from 1 in the whole input.
+    Invalid(ParseError),
+    /// More lines failed than allowed, those after `line` were not parsed.
+    Aborted { line: usize },
+}
+
+impl fmt::Display for LineParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineParseError::Invalid(e) => e.fmt(f),
+            LineParseError::Aborted { line } => write!(f, "lines aborted after line {}", line),
+        }
+    }
+}
+
+impl Error for LineParseError {}
+
+/// The documents of JSON Lines `input`, one per line, parsed as the
+/// iterator is advanced. Lines end at `\n` or `\r\n`, the last one may
+/// have neither. Lines holding only whitespace are skipped. A bad line is
+/// yielded as its error and iteration goes on with the next one.
+pub fn parse_lines(input: &str) -> JsonLines<'_> {
+    JsonLines {
+        rest: Some(input),
+        line: 0,
+    }
+}
+
+/// Parse every line of `input` like `parse_lines`, keeping the values and
+/// the errors apart, each in line order. Once more than `max_errors` lines
+/// have failed, parsing stops and `LineParseError::Aborted` ends the
+/// errors.
+pub fn parse_lines_collect(input: &str, max_errors: usize) -> (Vec<Value>, Vec<LineParseError>) {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    let mut lines = parse_lines(input);
+    for x in &mut lines {
+        match x {
+            Ok(v) => values.push(v),
+            Err(e) => {
+                errors.push(e);
+                if errors.len() > max_errors {
+                    errors.push(LineParseError::Aborted { line: lines.line });
+                    break;
+                }
+            }
+        }
+    }
+    (values, errors)
+}
+
+/// Iterator returned by `parse_lines`.
+pub struct JsonLines<'a> {
+    rest: Option<&'a str>,
+    // the line last read, from 1
+    line: usize,
+}
+
+impl Iterator for JsonLines<'_> {
+    type Item = Result<Value, LineParseError>;
+
+    fn next(&mut self) -> Option<Result<Value, LineParseError>> {
+        loop {
+            let rest = self.rest?;
+            let (text, next) = match rest.find('\n') {
+                Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+                None => (rest, None),
+            };
+            // a final newline doesn't start another line
+            self.rest = next.filter(|s| !s.is_empty());
+            self.line += 1;
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            if text.trim_matches([' ', '\t', '\r']).is_empty() {
+                continue;
+            }
+            return Some(Reader::new(text).parse().map_err(|mut e| {
+                e.row += self.line - 1;
+                LineParseError::Invalid(e)
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `n` lines, every hundredth one broken.
+    fn fixture(n: usize) -> String {
+        let mut s = String::new();
+        for i in 0..n {
+            if i % 100 == 50 {
+                s.push_str(&format!("{{\"id\": {}, \"name\": \"x\n", i));
+            } else {
+                s.push_str(&format!("{{\"id\": {}, \"tags\": [\"a\", {}]}}\n", i, i));
+            }
+        }
+        s
+    }
+
+    fn id(v: &Value) -> f64 {
+        match v.get("id") {
+            Some(Value::Number(x)) => *x,
+            x => panic!("no id {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_parse_lines_collect() {
+        let src = fixture(10_000);
+        let (values, errors) = parse_lines_collect(&src, usize::MAX);
+        assert_eq!(9_900, values.len());
+        assert_eq!(100, errors.len());
+        let ids: Vec<f64> = values.iter().map(id).collect();
+        let expect: Vec<f64> = (0..10_000)
+            .filter(|i| i % 100 != 50)
+            .map(|i| i as f64)
+            .collect();
+        assert_eq!(expect, ids);
+        for (i, e) in errors.iter().enumerate() {
+            match e {
+                LineParseError::Invalid(e) => assert_eq!(i * 100 + 51, e.row),
+                e => panic!("{:?}", e),
+            }
+        }
+
+        let (values, errors) = parse_lines_collect(&src, 3);
+        assert_eq!(5, errors.len());
+        assert_eq!(LineParseError::Aborted { line: 351 }, errors[4]);
+        assert_eq!(347, values.len());
+        assert_eq!(349.0, id(&values[346]));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let src = "1\r\n  \n\t\r\n[2, 3]\n{\"a\"\n\n\"last\"";
+        let x: Vec<_> = parse_lines(src).collect();
+        assert_eq!(4, x.len());
+        assert_eq!(Ok(Value::Number(1.0)), x[0]);
+        assert!(x[1].is_ok());
+        match x[2].as_ref().unwrap_err() {
+            LineParseError::Invalid(e) => assert_eq!((5, 5), (e.row, e.column)),
+            e => panic!("{:?}", e),
+        }
+        assert_eq!(Ok(Value::String("last".to_string())), x[3]);
+
+        assert_eq!(0, parse_lines("").count());
+        assert_eq!(0, parse_lines(" \n\n").count());
+        assert_eq!(1, parse_lines("null\n").count());
+    }
+}
@@ -0,0 +1,744 @@
+use std::collections::HashMap;
+#[cfg(feature = "intern-keys")]
+use std::collections::HashSet;
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::slice;
+#[cfg(feature = "intern-keys")]
+use std::sync::Arc;
+use std::vec;
+
+// The keyed store, sorted with the `btree-objects` feature so iteration and
+// serialization order are deterministic.
+#[cfg(feature = "btree-objects")]
+use std::collections::{btree_map as members, BTreeMap as Members};
+#[cfg(not(feature = "btree-objects"))]
+use std::collections::{hash_map as members, HashMap as Members};
+
+use crate::Value;
+
+// A member key, shared between all objects of a parse with the
+// `intern-keys` feature. `Arc` rather than `Rc` keeps `Value` `Send`.
+#[cfg(feature = "intern-keys")]
+pub(crate) type Key = Arc<str>;
+#[cfg(not(feature = "intern-keys"))]
+pub(crate) type Key = String;
+
+// Makes the keys of parsed members, one allocation per distinct key with
+// `intern-keys`.
+#[derive(Default)]
+pub(crate) struct KeyPool {
+    #[cfg(feature = "intern-keys")]
+    keys: HashSet<Arc<str>>,
+}
+
+impl KeyPool {
+    #[cfg(feature = "intern-keys")]
+    pub(crate) fn key(&mut self, k: &str) -> Key {
+        if let Some(k) = self.keys.get(k) {
+            return Arc::clone(k);
+        }
+        let k: Arc<str> = Arc::from(k);
+        self.keys.insert(Arc::clone(&k));
+        k
+    }
+
+    #[cfg(not(feature = "intern-keys"))]
+    pub(crate) fn key(&mut self, k: &str) -> Key {
+        k.to_string()
+    }
+}
+
+#[cfg(feature = "intern-keys")]
+fn into_string(k: Key) -> String {
+    k.to_string()
+}
+
+#[cfg(not(feature = "intern-keys"))]
+fn into_string(k: Key) -> String {
+    k
+}
+
+// Heap bytes of a key, counted again for every object sharing it.
+#[cfg(feature = "intern-keys")]
+fn key_size(k: &Key) -> usize {
+    // the strong and weak counts sit before the text
+    k.len() + 2 * mem::size_of::<usize>()
+}
+
+#[cfg(not(feature = "intern-keys"))]
+fn key_size(k: &Key) -> usize {
+    k.capacity()
+}
+
+// Heap bytes of the keyed store's table, by the node or bucket size.
+#[cfg(feature = "btree-objects")]
+fn table_size(m: &Members<Key, Value>) -> usize {
+    m.len() * mem::size_of::<(Key, Value)>()
+}
+
+#[cfg(not(feature = "btree-objects"))]
+fn table_size(m: &Members<Key, Value>) -> usize {
+    // a control byte per bucket
+    m.capacity() * (mem::size_of::<(Key, Value)>() + 1)
+}
+
+#[cfg(feature = "btree-objects")]
+fn members_with_capacity(_: usize) -> Members<Key, Value> {
+    Members::new()
+}
+
+#[cfg(not(feature = "btree-objects"))]
+fn members_with_capacity(n: usize) -> Members<Key, Value> {
+    Members::with_capacity(n)
+}
+
+/// Members of a JSON object.
+///
+/// Normally a hash map where a repeated key replaces the earlier member, or
+/// a `BTreeMap` iterating in key order with the `btree-objects` feature.
+/// A document-mode map (`ParseOptions::document_mode`) keeps every member in
+/// source order, duplicates included; lookups see the last occurrence.
+///
+/// With the `intern-keys` feature the keys of objects from one parse are
+/// shared, a document of many same-shaped objects allocates each key once.
+#[derive(Clone, Default)]
+pub struct Map {
+    repr: Repr,
+}
+
+#[derive(Clone)]
+enum Repr {
+    Keyed(Members<Key, Value>),
+    Entries(Vec<(Key, Value)>),
+}
+
+impl Default for Repr {
+    fn default() -> Repr {
+        Repr::Keyed(Members::new())
+    }
+}
+
+impl Repr {
+    fn empty_like(r: &Repr) -> Repr {
+        match r {
+            Repr::Keyed(_) => Repr::Keyed(Members::new()),
+            Repr::Entries(_) => Repr::Entries(Vec::new()),
+        }
+    }
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Map::default()
+    }
+
+    /// Room for `n` members before reallocating, none to reserve with
+    /// `btree-objects`.
+    pub fn with_capacity(n: usize) -> Map {
+        Map {
+            repr: Repr::Keyed(members_with_capacity(n)),
+        }
+    }
+
+    /// An empty document-mode map.
+    pub fn document() -> Map {
+        Map {
+            repr: Repr::Entries(Vec::new()),
+        }
+    }
+
+    pub fn is_document(&self) -> bool {
+        matches!(self.repr, Repr::Entries(_))
+    }
+
+    /// Number of members, duplicates included in document mode.
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Keyed(m) => m.len(),
+            Repr::Entries(e) => e.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match &self.repr {
+            Repr::Keyed(m) => m.get(key),
+            Repr::Entries(e) => e.iter().rev().find(|(k, _)| &**k == key).map(|(_, v)| v),
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match &mut self.repr {
+            Repr::Keyed(m) => m.get_mut(key),
+            Repr::Entries(e) => e
+                .iter_mut()
+                .rev()
+                .find(|(k, _)| &**k == key)
+                .map(|(_, v)| v),
+        }
+    }
+
+    /// Set `key` to `v`, returning the previous value. In document mode the
+    /// last occurrence is replaced in place, a new key is appended.
+    pub fn insert(&mut self, key: String, v: Value) -> Option<Value> {
+        // a no-op without `intern-keys`
+        #[allow(clippy::useless_conversion)]
+        let key = Key::from(key);
+        match &mut self.repr {
+            Repr::Keyed(m) => m.insert(key, v),
+            Repr::Entries(e) => match e.iter_mut().rev().find(|(k, _)| **k == *key) {
+                Some((_, old)) => Some(std::mem::replace(old, v)),
+                None => {
+                    e.push((key, v));
+                    None
+                }
+            },
+        }
+    }
+
+    /// Remove `key`, returning its value. In document mode every occurrence
+    /// goes and the last one's value is returned.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        match &mut self.repr {
+            Repr::Keyed(m) => m.remove(key),
+            Repr::Entries(e) => {
+                let mut removed = None;
+                e.retain_mut(|(k, v)| {
+                    if &**k != key {
+                        return true;
+                    }
+                    removed = Some(std::mem::replace(v, Value::Null));
+                    false
+                });
+                removed
+            }
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The member `key` for in-place manipulation, present or not.
+    pub fn entry<S: Into<String>>(&mut self, key: S) -> Entry<'_> {
+        Entry {
+            map: self,
+            key: key.into(),
+        }
+    }
+
+    // Heap bytes of the table and the keys, not the values, for
+    // `Value::deep_size`.
+    pub(crate) fn heap_size(&self) -> usize {
+        match &self.repr {
+            Repr::Keyed(m) => table_size(m) + m.keys().map(key_size).sum::<usize>(),
+            Repr::Entries(e) => {
+                let table = e.capacity() * mem::size_of::<(Key, Value)>();
+                table + e.iter().map(|(k, _)| key_size(k)).sum::<usize>()
+            }
+        }
+    }
+
+    // Move the values out onto `out`, dropping the keys, for `Value`'s
+    // iterative drop.
+    pub(crate) fn take_values(&mut self, out: &mut Vec<Value>) {
+        match mem::take(&mut self.repr) {
+            Repr::Keyed(m) => out.extend(m.into_values()),
+            Repr::Entries(e) => out.extend(e.into_iter().map(|(_, v)| v)),
+        }
+    }
+
+    /// Move the members of `other` into `self` as `insert` would, keeping
+    /// duplicates if `self` is in document mode, and leave `other` empty.
+    pub fn append(&mut self, other: &mut Map) {
+        let empty = Repr::empty_like(&other.repr);
+        match std::mem::replace(&mut other.repr, empty) {
+            Repr::Keyed(m) => m.into_iter().for_each(|(k, v)| self.push(k, v)),
+            Repr::Entries(e) => e.into_iter().for_each(|(k, v)| self.push(k, v)),
+        }
+    }
+
+    // Replace every key with `f` of it. A document-mode map keeps its order
+    // and duplicates.
+    pub(crate) fn map_keys<F: FnMut(&str) -> String>(&mut self, mut f: F) {
+        let empty = Repr::empty_like(&self.repr);
+        let old = std::mem::replace(&mut self.repr, empty);
+        // a no-op without `intern-keys`
+        #[allow(clippy::useless_conversion)]
+        let mut set = |k: Key, v| self.push(Key::from(f(&k)), v);
+        match old {
+            Repr::Keyed(m) => m.into_iter().for_each(|(k, v)| set(k, v)),
+            Repr::Entries(e) => e.into_iter().for_each(|(k, v)| set(k, v)),
+        }
+    }
+
+    // Add a parsed member, keeping duplicates in document mode.
+    pub(crate) fn push(&mut self, key: Key, v: Value) {
+        match &mut self.repr {
+            Repr::Keyed(m) => {
+                m.insert(key, v);
+            }
+            Repr::Entries(e) => e.push((key, v)),
+        }
+    }
+
+    /// All members; in document mode in source order with duplicates,
+    /// otherwise in arbitrary order, or key order with `btree-objects`.
+    pub fn entries(&self) -> Iter<'_> {
+        Iter {
+            inner: match &self.repr {
+                Repr::Keyed(m) => IterRepr::Keyed(m.iter()),
+                Repr::Entries(e) => IterRepr::Entries(e.iter()),
+            },
+        }
+    }
+
+    /// Same as `entries`.
+    pub fn iter(&self) -> Iter<'_> {
+        self.entries()
+    }
+
+    /// `entries` with mutable values.
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            inner: match &mut self.repr {
+                Repr::Keyed(m) => IterMutRepr::Keyed(m.iter_mut()),
+                Repr::Entries(e) => IterMutRepr::Entries(e.iter_mut()),
+            },
+        }
+    }
+
+    // Members as seen through `get`: duplicates shadowed by a later
+    // occurrence are skipped.
+    pub(crate) fn visible(&self) -> Vec<(&str, &Value)> {
+        match &self.repr {
+            Repr::Keyed(m) => m.iter().map(|(k, v)| (&**k, v)).collect(),
+            Repr::Entries(e) => {
+                let mut last = HashMap::new();
+                for (i, (k, _)) in e.iter().enumerate() {
+                    last.insert(&**k, i);
+                }
+                e.iter()
+                    .enumerate()
+                    .filter(|(i, (k, _))| last[&**k] == *i)
+                    .map(|(_, (k, v))| (&**k, v))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl PartialEq for Map {
+    // Compares what `get` sees, so a document-mode map equals the plain map
+    // parsed from the same text.
+    fn eq(&self, other: &Map) -> bool {
+        if let (Repr::Keyed(a), Repr::Keyed(b)) = (&self.repr, &other.repr) {
+            return a == b;
+        }
+        let members = self.visible();
+        members.len() == other.visible().len()
+            && members.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+// Members sorted by key, duplicates in source order, so the output of a
+// failed `assert_eq!` does not change from run to run.
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut members: Vec<_> = self.entries().collect();
+        members.sort_by(|a, b| a.0.cmp(b.0));
+        f.debug_map().entries(members).finish()
+    }
+}
+
+impl From<HashMap<String, Value>> for Map {
+    fn from(m: HashMap<String, Value>) -> Map {
+        #[cfg(feature = "intern-keys")]
+        let m = m.into_iter().map(|(k, v)| (k.into(), v)).collect();
+        #[cfg(all(feature = "btree-objects", not(feature = "intern-keys")))]
+        let m = m.into_iter().collect();
+        Map {
+            repr: Repr::Keyed(m),
+        }
+    }
+}
+
+impl From<Map> for HashMap<String, Value> {
+    // A later duplicate of a document-mode map wins, as in `get`.
+    fn from(m: Map) -> HashMap<String, Value> {
+        m.into_iter().collect()
+    }
+}
+
+impl FromIterator<(String, Value)> for Map {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Map {
+        let mut m = Map::new();
+        m.extend(iter);
+        m
+    }
+}
+
+impl Extend<(String, Value)> for Map {
+    fn extend<I: IntoIterator<Item = (String, Value)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+/// A member of a `Map`, from `Map::entry`.
+pub struct Entry<'a> {
+    map: &'a mut Map,
+    key: String,
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The value, after inserting `default` if the member is absent.
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> Value>(self, default: F) -> &'a mut Value {
+        if !self.map.contains_key(&self.key) {
+            self.map.insert(self.key.clone(), default());
+        }
+        self.map.get_mut(&self.key).expect("member just inserted")
+    }
+
+    /// `or_insert(Value::Null)`.
+    pub fn or_default(self) -> &'a mut Value {
+        self.or_insert(Value::Null)
+    }
+
+    /// Call `f` on the value if the member is present.
+    pub fn and_modify<F: FnOnce(&mut Value)>(self, f: F) -> Entry<'a> {
+        if let Some(v) = self.map.get_mut(&self.key) {
+            f(v);
+        }
+        self
+    }
+}
+
+pub struct Iter<'a> {
+    inner: IterRepr<'a>,
+}
+
+enum IterRepr<'a> {
+    Keyed(members::Iter<'a, Key, Value>),
+    Entries(slice::Iter<'a, (Key, Value)>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IterRepr::Keyed(it) => it.next().map(|(k, v)| (&**k, v)),
+            IterRepr::Entries(it) => it.next().map(|(k, v)| (&**k, v)),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a str, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.entries()
+    }
+}
+
+pub struct IterMut<'a> {
+    inner: IterMutRepr<'a>,
+}
+
+enum IterMutRepr<'a> {
+    Keyed(members::IterMut<'a, Key, Value>),
+    Entries(slice::IterMut<'a, (Key, Value)>),
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a str, &'a mut Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IterMutRepr::Keyed(it) => it.next().map(|(k, v)| (&**k, v)),
+            IterMutRepr::Entries(it) => it.next().map(|(k, v)| (&**k, v)),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map {
+    type Item = (&'a str, &'a mut Value);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+/// The members of a `Map` by value, in `entries` order.
+pub struct IntoIter {
+    inner: IntoIterRepr,
+}
+
+enum IntoIterRepr {
+    Keyed(members::IntoIter<Key, Value>),
+    Entries(vec::IntoIter<(Key, Value)>),
+}
+
+impl Iterator for IntoIter {
+    type Item = (String, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = match &mut self.inner {
+            IntoIterRepr::Keyed(it) => it.next()?,
+            IntoIterRepr::Entries(it) => it.next()?,
+        };
+        Some((into_string(k), v))
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, Value);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            inner: match self.repr {
+                Repr::Keyed(m) => IntoIterRepr::Keyed(m.into_iter()),
+                Repr::Entries(e) => IntoIterRepr::Entries(e.into_iter()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, Reader};
+
+    fn num(f: f64) -> Value {
+        Value::Number(f)
+    }
+
+    #[test]
+    fn test_document_mode() {
+        let src = "{\"a\":1,\"b\":2,\"a\":3}";
+        let opts = ParseOptions::new().document_mode(true);
+        let v = Reader::with_options(src, opts).parse().unwrap();
+        let m = match &v {
+            Value::Object(v) => v,
+            _ => panic!("not an object"),
+        };
+
+        assert!(m.is_document());
+        assert_eq!(3, m.len());
+        let entries: Vec<_> = m.entries().collect();
+        assert_eq!(
+            vec![("a", &num(1.0)), ("b", &num(2.0)), ("a", &num(3.0))],
+            entries
+        );
+        assert_eq!(Some(&num(3.0)), m.get("a"));
+        assert_eq!(src, v.to_string());
+
+        let plain = Reader::new(src).parse().unwrap();
+        assert_eq!(plain, v);
+        match &plain {
+            Value::Object(v) => assert_eq!(2, v.len()),
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn test_document_insert() {
+        let mut m = Map::document();
+        m.push("a".into(), num(1.0));
+        m.push("b".into(), num(2.0));
+        m.push("a".into(), num(3.0));
+
+        assert_eq!(Some(num(3.0)), m.insert(String::from("a"), num(4.0)));
+        assert_eq!(None, m.insert(String::from("c"), num(5.0)));
+        let keys: Vec<_> = m.entries().map(|(k, _)| k).collect();
+        assert_eq!(vec!["a", "b", "a", "c"], keys);
+        assert_eq!(Some(&num(1.0)), m.entries().next().map(|(_, v)| v));
+        assert_eq!(Some(&num(4.0)), m.get("a"));
+
+        let mut h = Map::new();
+        h.insert(String::from("b"), num(2.0));
+        h.insert(String::from("a"), num(4.0));
+        assert_ne!(m, h);
+        h.insert(String::from("c"), num(5.0));
+        assert_eq!(m, h);
+        assert_eq!(h, m);
+    }
+
+    #[test]
+    fn test_map_api() {
+        let mut m: Map = vec![(String::from("a"), num(1.0)), (String::from("b"), num(2.0))]
+            .into_iter()
+            .collect();
+        assert_eq!(2, m.len());
+        assert!(m.contains_key("a"));
+        assert!(!m.contains_key("z"));
+        assert_eq!(Some(&num(2.0)), m.get("b"));
+
+        if let Some(v) = m.get_mut("b") {
+            *v = num(20.0);
+        }
+        for (_, v) in m.iter_mut() {
+            if let Value::Number(f) = v {
+                *f += 1.0;
+            }
+        }
+        for (_, v) in &mut m {
+            if let Value::Number(f) = v {
+                *f *= 2.0;
+            }
+        }
+        assert_eq!(Some(&num(4.0)), m.get("a"));
+        assert_eq!(Some(&num(42.0)), m.get("b"));
+
+        m.extend(vec![
+            (String::from("c"), Value::Null),
+            (String::from("a"), num(0.0)),
+        ]);
+        assert_eq!(3, m.len());
+        assert_eq!(Some(num(0.0)), m.remove("a"));
+        assert_eq!(None, m.remove("a"));
+        assert_eq!(2, m.len());
+
+        let mut keys: Vec<&str> = m.iter().map(|(k, _)| k).collect();
+        keys.sort_unstable();
+        assert_eq!(vec!["b", "c"], keys);
+        assert_eq!(m.iter().count(), (&m).into_iter().count());
+
+        let mut owned: Vec<(String, Value)> = m.clone().into_iter().collect();
+        owned.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            vec![
+                (String::from("b"), num(42.0)),
+                (String::from("c"), Value::Null)
+            ],
+            owned
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut m = Map::new();
+        *m.entry("n").or_insert(num(0.0)) = num(1.0);
+        assert_eq!(Some(&num(1.0)), m.get("n"));
+        let v = m.entry(String::from("n")).or_insert_with(|| unreachable!());
+        assert_eq!(&num(1.0), v);
+
+        m.entry("n").and_modify(|v| *v = num(2.0)).or_default();
+        assert_eq!(Some(&num(2.0)), m.get("n"));
+        let e = m.entry("x").and_modify(|_| unreachable!());
+        assert_eq!("x", e.key());
+        assert_eq!(&Value::Null, e.or_default());
+        assert_eq!(2, m.len());
+
+        let mut d = Map::document();
+        d.push("a".into(), num(1.0));
+        d.push("a".into(), num(2.0));
+        *d.entry("a").or_default() = num(3.0);
+        let values: Vec<_> = d.entries().map(|(_, v)| v.clone()).collect();
+        assert_eq!(vec![num(1.0), num(3.0)], values);
+    }
+
+    #[test]
+    fn test_document_remove() {
+        let mut d = Map::document();
+        d.push("a".into(), num(1.0));
+        d.push("b".into(), num(2.0));
+        d.push("a".into(), num(3.0));
+        let h: HashMap<String, Value> = d.clone().into();
+        assert_eq!(Some(&num(3.0)), h.get("a"));
+        assert_eq!(2, h.len());
+        assert_eq!(d, Map::from(h));
+
+        let owned: Vec<_> = d.clone().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(vec!["a", "b", "a"], owned);
+        assert_eq!(Some(num(3.0)), d.remove("a"));
+        assert!(!d.contains_key("a"));
+        assert_eq!(1, d.len());
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut a = Map::new();
+        a.insert(String::from("b"), Value::Null);
+        a.insert(String::from("a"), num(1.0));
+        let mut b = Map::new();
+        b.insert(String::from("a"), num(1.0));
+        b.insert(String::from("b"), Value::Null);
+        let (a, b) = (Value::Object(a), Value::Object(b));
+        assert_eq!(
+            "Object({\"a\": Number(1.0), \"b\": Null})",
+            format!("{:?}", a)
+        );
+        assert_eq!(format!("{:?}", a), format!("{:?}", b));
+
+        let v = Reader::new("{\"z\":[\"q\\\"\",true],\"k\\n\":{}}")
+            .parse()
+            .unwrap();
+        let expect = "Object({
+    \"k\\n\": Object({}),
+    \"z\": Array([
+        String(\"q\\\"\"),
+        Bool(true),
+    ]),
+})";
+        assert_eq!(expect, format!("{:#?}", v));
+    }
+
+    #[cfg(feature = "btree-objects")]
+    #[test]
+    fn test_btree_order() {
+        let src = "{\"b\":1,\"c\":{\"z\":0,\"y\":1},\"a\":2,\"b\":3}";
+        let v = Reader::new(src).parse().unwrap();
+        assert_eq!("{\"a\":2,\"b\":3,\"c\":{\"y\":1,\"z\":0}}", v.to_string());
+
+        let mut m = Map::new();
+        m.insert(String::from("y"), num(1.0));
+        m.insert(String::from("x"), num(2.0));
+        let keys: Vec<_> = m.entries().map(|(k, _)| k).collect();
+        assert_eq!(vec!["x", "y"], keys);
+    }
+
+    #[cfg(feature = "intern-keys")]
+    #[test]
+    fn test_intern_keys() {
+        let item = "{\"id\":1,\"name\":\"x\",\"tags\":[]}";
+        let src = format!("[{}]", vec![item; 1000].join(","));
+        let v = Reader::new(&src).parse().unwrap();
+        let mut keys = Vec::new();
+        if let Value::Array(arr) = &v {
+            for x in arr {
+                if let Value::Object(Map {
+                    repr: Repr::Keyed(m),
+                }) = x
+                {
+                    keys.extend(m.keys().filter(|k| &***k == "name").cloned());
+                }
+            }
+        }
+        assert_eq!(1000, keys.len());
+        // one allocation, shared by every object and the clones above
+        assert!(keys.iter().all(|k| Arc::ptr_eq(k, &keys[0])));
+        assert_eq!(2000, Arc::strong_count(&keys[0]));
+
+        let other = Reader::new(item).parse().unwrap();
+        assert_eq!(Some(&other), v.pointer("/999"));
+    }
+}
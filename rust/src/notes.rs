@@ -0,0 +1,126 @@
+use crate::{ParseError, Reader, Value};
+
+/// Something the parser let through but a caller may want to know about,
+/// from `Reader::parse_with_notes`. The position is where the noted value
+/// starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    pub row: usize,
+    pub column: usize,
+    pub desc: String,
+}
+
+impl Reader<'_> {
+    /// Parse and also collect notes on values read with a loss: number
+    /// literals with more precision or range than an `f64` holds, so
+    /// `12345678901234567890` is read as `12345678901234567000`.
+    pub fn parse_with_notes(&mut self) -> Result<(Value, Vec<Note>), ParseError> {
+        self.notes = Some(Vec::new());
+        let x = self.parse();
+        let notes = self.notes.take().unwrap_or_default();
+        Ok((x?, notes))
+    }
+
+    // Note `literal` if `f`, what was read for it, doesn't give it back.
+    pub(crate) fn note_number(&mut self, f: f64, literal: &str, row: usize, column: usize) {
+        if let Some(notes) = self.notes.as_mut() {
+            if !is_exact(f, literal) {
+                notes.push(Note {
+                    row,
+                    column,
+                    desc: format!("number '{}' read as {} loses precision", literal, f),
+                });
+            }
+        }
+    }
+}
+
+// Whether `f` gives back decimal `literal`: the shortest digits reading as
+// `f` are the literal's own. `0.1` does, though no `f64` is exactly 1/10.
+fn is_exact(f: f64, literal: &str) -> bool {
+    if !f.is_finite() {
+        return false;
+    }
+    let shortest = format!("{:e}", f);
+    match (decimal(&shortest), decimal(literal)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+// The significant digits of a decimal number and the power of ten of the
+// last one, `-1.50e2` is ("15", 1). Zero has no digits.
+fn decimal(s: &str) -> Option<(String, i64)> {
+    let s = s.trim_start_matches(['-', '+']);
+    let (mantissa, exp) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], s[i + 1..].parse::<i64>().ok()?),
+        None => (s, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int, frac);
+    let digits = digits.trim_start_matches('0');
+    let trimmed = digits.trim_end_matches('0');
+    if trimmed.is_empty() {
+        return Some((String::new(), 0));
+    }
+    let exp = exp - frac.len() as i64 + (digits.len() - trimmed.len()) as i64;
+    Some((trimmed.to_string(), exp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OverflowPolicy, ParseOptions};
+
+    #[test]
+    fn test_parse_with_notes() {
+        let src = "[1, 0.1, -2.50e3, 9007199254740993,\n 12345678901234567890, 0.000, 1e-400]";
+        let (v, notes) = Reader::new(src).parse_with_notes().unwrap();
+        assert_eq!(Reader::new(src).parse().unwrap(), v);
+        assert_eq!(3, notes.len(), "{:?}", notes);
+        assert_eq!((1, 19), (notes[0].row, notes[0].column));
+        assert_eq!(
+            Note {
+                row: 2,
+                column: 2,
+                desc: String::from(
+                    "number '12345678901234567890' read as 12345678901234567000 loses precision"
+                ),
+            },
+            notes[1]
+        );
+        assert_eq!((2, 31), (notes[2].row, notes[2].column));
+
+        let opts = ParseOptions::new().on_number_overflow(OverflowPolicy::Clamp);
+        let (_, notes) = Reader::with_options("[1e400, 1.5]", opts)
+            .parse_with_notes()
+            .unwrap();
+        assert_eq!(1, notes.len());
+
+        // only collected when asked for
+        let mut r = Reader::new("12345678901234567890");
+        assert!(r.parse().is_ok());
+        assert!(r.notes.is_none());
+    }
+
+    #[test]
+    fn test_is_exact() {
+        for s in &[
+            "0",
+            "-0.0",
+            "1",
+            "0.1",
+            "1e300",
+            "5e-324",
+            "-1.7976931348623157e308",
+        ] {
+            let f: f64 = s.parse().unwrap();
+            assert!(is_exact(f, s), "{}", s);
+        }
+        for s in &["0.1000000000000000000001", "2e-324", "123456789012345678"] {
+            let f: f64 = s.parse().unwrap();
+            assert!(!is_exact(f, s), "{}", s);
+        }
+        assert!(!is_exact(1.0, "1e99999999999999999999"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use crate::{Map, Value};
+
+/// A total order agreeing with `Value`'s equality, so mixed arrays sort
+/// deterministically. By type first:
+///
+/// null < false < true < number < string < array < object
+///
+/// then by content. Numbers compare numerically with `-0.0 == 0.0` and NaN
+/// above every other number; strings by bytes; arrays element-wise, a
+/// prefix first; objects as their members sorted by key.
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+            (Value::Number(x), Value::Number(y)) => cmp_numbers(*x, *y),
+            (Value::String(x), Value::String(y)) => x.cmp(y),
+            (Value::Array(x), Value::Array(y)) => x.as_slice().cmp(y.as_slice()),
+            (Value::Object(x), Value::Object(y)) => cmp_objects(x, y),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn rank(v: &Value) -> u8 {
+    match v {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+fn cmp_numbers(x: f64, y: f64) -> Ordering {
+    match (x.is_nan(), y.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => x.partial_cmp(&y).unwrap(),
+    }
+}
+
+fn cmp_objects(x: &Map, y: &Map) -> Ordering {
+    let (x, y) = (sorted_members(x), sorted_members(y));
+    for ((ka, va), (kb, vb)) in x.iter().zip(&y) {
+        match ka.cmp(kb).then_with(|| va.cmp(vb)) {
+            Ordering::Equal => {}
+            o => return o,
+        }
+    }
+    x.len().cmp(&y.len())
+}
+
+fn sorted_members(m: &Map) -> Vec<(&str, &Value)> {
+    let mut members = m.visible();
+    members.sort_by(|a, b| a.0.cmp(b.0));
+    members
+}
+
+// Array helpers, all no-ops on other values.
+impl Value {
+    /// Stable sort of an array of objects by member `key`, compared in the
+    /// same total order as values of any type. Elements without the member,
+    /// non-objects included, go first if `missing_first`, else last.
+    pub fn sort_array_by_key(&mut self, key: &str, missing_first: bool) {
+        self.sort_array_by(|a, b| match (a.get(key), b.get(key)) {
+            (Some(x), Some(y)) => x.cmp(y),
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) if missing_first => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) if missing_first => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+        });
+    }
+
+    /// Stable sort of an array's elements with `cmp`.
+    pub fn sort_array_by<F: FnMut(&Value, &Value) -> Ordering>(&mut self, cmp: F) {
+        if let Value::Array(arr) = self {
+            arr.sort_by(cmp);
+        }
+    }
+
+    /// Replace every element of an array with `f` of it.
+    pub fn map_array_in_place<F: FnMut(Value) -> Value>(&mut self, mut f: F) {
+        if let Value::Array(arr) = self {
+            for x in arr.iter_mut() {
+                let old = std::mem::replace(x, Value::Null);
+                *x = f(old);
+            }
+        }
+    }
+
+    /// Remove elements of an array equal to an earlier one, not only
+    /// adjacent ones, keeping the first occurrence.
+    pub fn dedup_array(&mut self) {
+        if let Value::Array(arr) = self {
+            let mut seen = HashSet::new();
+            let keep: Vec<bool> = arr.iter().map(|x| seen.insert(x)).collect();
+            let mut keep = keep.into_iter();
+            arr.retain(|_| keep.next().unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    const USERS: &str = "[{\"name\":\"carol\",\"score\":7},{\"name\":\"alice\",\"score\":9.5},\
+                         {\"score\":1},{\"name\":\"bob\",\"score\":7},{\"name\":\"dave\",\"score\":-2}]";
+
+    fn names(v: &Value) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Value::Array(arr) = v {
+            for x in arr {
+                let name = x.get("name").and_then(Value::coerce_to_string);
+                names.push(name.unwrap_or_default());
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn test_sort_array_by_key() {
+        let mut v = Reader::new(USERS).parse().unwrap();
+        v.sort_array_by_key("name", false);
+        assert_eq!(vec!["alice", "bob", "carol", "dave", ""], names(&v));
+        v.sort_array_by_key("name", true);
+        assert_eq!(vec!["", "alice", "bob", "carol", "dave"], names(&v));
+
+        // stable, carol and bob both score 7
+        let mut v = Reader::new(USERS).parse().unwrap();
+        v.sort_array_by_key("score", false);
+        assert_eq!(vec!["dave", "", "carol", "bob", "alice"], names(&v));
+
+        v.sort_array_by(|a, b| b.get("score").cmp(&a.get("score")));
+        assert_eq!(vec!["alice", "carol", "bob", "", "dave"], names(&v));
+
+        let mut n = Value::Number(1.0);
+        n.sort_array_by_key("name", false);
+        assert_eq!(Value::Number(1.0), n);
+    }
+
+    #[test]
+    fn test_map_dedup() {
+        let mut v = Reader::new("[1,2,1,{\"a\":1},3,2,{\"a\":1},-0,0]")
+            .parse()
+            .unwrap();
+        v.dedup_array();
+        assert_eq!(Reader::new("[1,2,{\"a\":1},3,-0]").parse().unwrap(), v);
+
+        v.map_array_in_place(|x| match x {
+            Value::Number(f) => Value::Number(f * 10.0),
+            x => x,
+        });
+        assert_eq!(Reader::new("[10,20,{\"a\":1},30,0]").parse().unwrap(), v);
+    }
+
+    #[test]
+    fn test_total_order() {
+        let v = Reader::new(
+            "[null,false,true,-1,0,2,\"\",\"a\",[],[1],{},{\"a\":1},{\"a\":2},{\"b\":0}]",
+        )
+        .parse()
+        .unwrap();
+        if let Value::Array(arr) = &v {
+            for (i, a) in arr.iter().enumerate() {
+                for (j, b) in arr.iter().enumerate() {
+                    assert_eq!(i.cmp(&j), a.cmp(b), "{} {}", a, b);
+                }
+            }
+        }
+        assert_eq!(Value::Number(-0.0), Value::Number(0.0));
+        assert!(Value::Number(-0.0) <= Value::Number(0.0));
+        let nan = Value::Number(f64::NAN);
+        assert_eq!(Ordering::Equal, nan.cmp(&nan));
+        assert!(nan > Value::Number(f64::INFINITY));
+        assert!(nan < Value::String(String::new()));
+    }
+
+    #[test]
+    fn test_sort_mixed() {
+        let src = "[{\"a\":1},\"b\",[2],true,null,-3.5,[1,2],\"a\",false,10,{},[1]]";
+        let mut values = Reader::new(src).parse().unwrap().into_array().unwrap();
+        values.sort();
+        let expect = "[null,false,true,-3.5,10,\"a\",\"b\",[1],[1,2],[2],{},{\"a\":1}]";
+        assert_eq!(Reader::new(expect).parse().unwrap(), Value::Array(values));
+    }
+}
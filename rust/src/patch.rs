@@ -0,0 +1,245 @@
+use crate::{Map, Value};
+
+/// The RFC 6902 JSON Patch turning `from` into `to`, an array of `add`,
+/// `remove` and `replace` operations. Objects are compared member by
+/// member, so a change deep down is one `replace` at that node, not of the
+/// object holding it. Arrays are compared index by index, the elements one
+/// has past the other's length added or removed at the end.
+pub fn create_patch(from: &Value, to: &Value) -> Value {
+    let mut ops = Vec::new();
+    diff(&mut ops, &mut String::new(), from, to);
+    Value::Array(ops)
+}
+
+// Push the operations turning `from` into `to`, both at `path`.
+fn diff(ops: &mut Vec<Value>, path: &mut String, from: &Value, to: &Value) {
+    match (from, to) {
+        (Value::Object(a), Value::Object(b)) => {
+            // sorted, for the same patch whatever the map's order
+            let mut removed: Vec<&str> = a
+                .visible()
+                .into_iter()
+                .map(|(k, _)| k)
+                .filter(|k| !b.contains_key(k))
+                .collect();
+            removed.sort_unstable();
+            for k in removed {
+                let len = push_token(path, k);
+                ops.push(op("remove", path, None));
+                path.truncate(len);
+            }
+            let mut members = b.visible();
+            members.sort_unstable_by_key(|m| m.0);
+            for (k, y) in members {
+                let len = push_token(path, k);
+                match a.get(k) {
+                    Some(x) => diff(ops, path, x, y),
+                    None => ops.push(op("add", path, Some(y))),
+                }
+                path.truncate(len);
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                let len = push_token(path, &i.to_string());
+                diff(ops, path, x, y);
+                path.truncate(len);
+            }
+            // from the end, so the indexes before stay valid
+            for i in (b.len()..a.len()).rev() {
+                let len = push_token(path, &i.to_string());
+                ops.push(op("remove", path, None));
+                path.truncate(len);
+            }
+            for (i, y) in b.iter().enumerate().skip(a.len()) {
+                let len = push_token(path, &i.to_string());
+                ops.push(op("add", path, Some(y)));
+                path.truncate(len);
+            }
+        }
+        _ if from == to => {}
+        _ => ops.push(op("replace", path, Some(to))),
+    }
+}
+
+// Append `/token` to `path`, escaped, returning the length to truncate
+// back to.
+fn push_token(path: &mut String, token: &str) -> usize {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    len
+}
+
+fn op(name: &str, path: &str, v: Option<&Value>) -> Value {
+    let mut m = Map::document();
+    m.insert(String::from("op"), Value::String(name.to_string()));
+    m.insert(String::from("path"), Value::String(path.to_string()));
+    if let Some(v) = v {
+        m.insert(String::from("value"), v.clone());
+    }
+    Value::Object(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pointer_index, ParseOptions, Reader};
+
+    fn parse(s: &str) -> Value {
+        Reader::with_options(s, ParseOptions::new().document_mode(true))
+            .parse()
+            .unwrap()
+    }
+
+    // The node at unescaped `tokens` below `v`.
+    fn node_mut<'a>(v: &'a mut Value, tokens: &[String]) -> &'a mut Value {
+        tokens.iter().fold(v, |v, t| match v {
+            Value::Object(m) => m.get_mut(t).unwrap(),
+            Value::Array(arr) => &mut arr[pointer_index(t).unwrap()],
+            v => panic!("{} in {}", t, v),
+        })
+    }
+
+    fn tokens(path: &str) -> Vec<String> {
+        path.split('/')
+            .skip(1)
+            .map(|t| t.replace("~1", "/").replace("~0", "~"))
+            .collect()
+    }
+
+    // Enough of RFC 6902 for the operations `create_patch` writes.
+    fn apply_patch(v: &mut Value, patch: &Value) {
+        for op in patch.as_array().unwrap() {
+            let name = op.get("op").and_then(Value::as_str).unwrap();
+            let mut tokens = tokens(op.get("path").and_then(Value::as_str).unwrap());
+            let value = op.get("value").cloned();
+            let last = match tokens.pop() {
+                Some(t) => t,
+                None => {
+                    *v = value.unwrap();
+                    continue;
+                }
+            };
+            match (name, node_mut(v, &tokens)) {
+                ("remove", Value::Object(m)) => assert!(m.remove(&last).is_some()),
+                ("remove", Value::Array(arr)) => {
+                    let _ = arr.remove(pointer_index(&last).unwrap());
+                }
+                ("add", Value::Object(m)) => assert!(m.insert(last, value.unwrap()).is_none()),
+                ("add", Value::Array(arr)) => {
+                    arr.insert(pointer_index(&last).unwrap(), value.unwrap())
+                }
+                ("replace", parent) => *node_mut(parent, &[last]) = value.unwrap(),
+                (name, parent) => panic!("{} in {}", name, parent),
+            }
+        }
+    }
+
+    fn patched(from: &Value, to: &Value) -> Value {
+        let mut v = from.clone();
+        apply_patch(&mut v, &create_patch(from, to));
+        v
+    }
+
+    #[test]
+    fn test_create_patch() {
+        let from =
+            parse("{\"a\": {\"b\": {\"c\": 1, \"d\": [1, 2, 3]}}, \"x/~\": null, \"gone\": 0}");
+        let to = parse("{\"a\": {\"b\": {\"c\": 2, \"d\": [1, 5]}}, \"x/~\": {}, \"new\": [true]}");
+        let expect = parse(
+            "[{\"op\": \"remove\", \"path\": \"/gone\"},
+              {\"op\": \"replace\", \"path\": \"/a/b/c\", \"value\": 2},
+              {\"op\": \"replace\", \"path\": \"/a/b/d/1\", \"value\": 5},
+              {\"op\": \"remove\", \"path\": \"/a/b/d/2\"},
+              {\"op\": \"add\", \"path\": \"/new\", \"value\": [true]},
+              {\"op\": \"replace\", \"path\": \"/x~1~0\", \"value\": {}}]",
+        );
+        assert_eq!(expect, create_patch(&from, &to));
+        assert_eq!(to, patched(&from, &to));
+        assert_eq!(from, patched(&to, &from));
+
+        assert_eq!("[]", create_patch(&from, &from.clone()).to_string());
+        assert_eq!(
+            "[{\"op\":\"replace\",\"path\":\"\",\"value\":[1]}]",
+            create_patch(&from, &parse("[1]")).to_string()
+        );
+    }
+
+    // Pointers to every node below `v`.
+    fn paths(v: &Value, path: &mut String, out: &mut Vec<String>) {
+        out.push(path.clone());
+        let children: Vec<(String, &Value)> = match v {
+            Value::Object(m) => m
+                .visible()
+                .into_iter()
+                .map(|(k, x)| (k.to_string(), x))
+                .collect(),
+            Value::Array(arr) => arr
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (i.to_string(), x))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (k, x) in children {
+            let len = push_token(path, &k);
+            paths(x, path, out);
+            path.truncate(len);
+        }
+    }
+
+    #[test]
+    fn test_create_patch_mutations() {
+        let fixture = parse(
+            "{\"id\": 7, \"name\": \"zxh\", \"tags\": [\"a\", \"b\", {\"k\": null}],
+              \"owner\": {\"ok\": true, \"list\": [[1, 2], [], {\"a/b\": \"~\"}]}, \"n\": -0.5}",
+        );
+        // a fixed linear congruential sequence, the same every run
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut rand = |n: usize| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 33) as usize % n
+        };
+        let scalars = [
+            Value::Null,
+            Value::Bool(false),
+            Value::Number(3.0),
+            Value::String(String::from("s")),
+            parse("{\"z\": [0]}"),
+            parse("[]"),
+        ];
+
+        for _ in 0..500 {
+            let mut to = fixture.clone();
+            for _ in 0..1 + rand(4) {
+                let mut all = Vec::new();
+                paths(&to, &mut String::new(), &mut all);
+                let path = tokens(&all[rand(all.len())]);
+                let node = node_mut(&mut to, &path);
+                let x = scalars[rand(scalars.len())].clone();
+                match (rand(3), node) {
+                    (0, Value::Object(m)) => {
+                        m.insert(format!("k{}", rand(3)), x);
+                    }
+                    (0, Value::Array(arr)) => arr.push(x),
+                    (1, Value::Object(m)) if !m.is_empty() => {
+                        let k = m.visible()[0].0.to_string();
+                        m.remove(&k);
+                    }
+                    (1, Value::Array(arr)) if !arr.is_empty() => {
+                        let _ = arr.remove(rand(arr.len()));
+                    }
+                    (_, node) => *node = x,
+                }
+            }
+            assert_eq!(
+                to,
+                patched(&fixture, &to),
+                "{}",
+                create_patch(&fixture, &to)
+            );
+            assert_eq!(fixture, patched(&to, &fixture));
+        }
+    }
+}
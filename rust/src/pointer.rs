@@ -0,0 +1,325 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{pointer_index, Map, Value};
+
+// How far `CreateMode::CreateObjects` pads an array with nulls, so a stray
+// `/a/999999999` fails instead of allocating.
+const MAX_PADDING: usize = 1024;
+
+/// What `Value::pointer_set` does about missing intermediate nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateMode {
+    /// Every parent must exist. The last token may still add an object
+    /// member, or append to an array with `-` or the array's length.
+    Existing,
+    /// Create missing parents and replace `null` ones, the root included:
+    /// an array if the token below is an index or `-`, an object otherwise.
+    /// Arrays are padded with `null` up to the index.
+    CreateObjects,
+}
+
+/// `Value::pointer_set` failed, `pointer` is the prefix of the pointer up
+/// to the offending token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointerError {
+    pub pointer: String,
+    pub desc: String,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pointer {} at '{}'", self.desc, self.pointer)
+    }
+}
+
+impl Error for PointerError {}
+
+impl Value {
+    /// Set the node at JSON Pointer `pointer` to `v`, returning the value it
+    /// replaced. A token of `-` appends to an array.
+    pub fn pointer_set(
+        &mut self,
+        pointer: &str,
+        v: Value,
+        create: CreateMode,
+    ) -> Result<Option<Value>, PointerError> {
+        if pointer.is_empty() {
+            return Ok(Some(std::mem::replace(self, v)));
+        }
+        if !pointer.starts_with('/') {
+            return Err(PointerError {
+                pointer: String::new(),
+                desc: String::from("expect '/'"),
+            });
+        }
+
+        let tokens: Vec<&str> = pointer[1..].split('/').collect();
+        let mut setter = Setter {
+            create,
+            pointer: String::new(),
+        };
+        setter.set(self, &tokens, v)
+    }
+}
+
+struct Setter {
+    create: CreateMode,
+    // the pointer walked so far, for errors
+    pointer: String,
+}
+
+impl Setter {
+    // Walk down `tokens` from `target`, creating what is missing as a whole
+    // subtree built off to the side, so a failure leaves `target` as it was.
+    fn set(
+        &mut self,
+        mut target: &mut Value,
+        tokens: &[&str],
+        v: Value,
+    ) -> Result<Option<Value>, PointerError> {
+        let create = self.create == CreateMode::CreateObjects;
+        for (n, raw) in tokens.iter().enumerate() {
+            if create && *target == Value::Null {
+                *target = self.build(&tokens[n..], v)?;
+                return Ok(None);
+            }
+            self.push(raw);
+            let token = raw.replace("~1", "/").replace("~0", "~");
+            let rest = &tokens[n + 1..];
+            target = match target {
+                Value::Object(m) => {
+                    if rest.is_empty() {
+                        return Ok(m.insert(token, v));
+                    }
+                    if m.get(&token).is_none() {
+                        if !create {
+                            return self.error("not found");
+                        }
+                        let child = self.build(rest, v)?;
+                        m.insert(token, child);
+                        return Ok(None);
+                    }
+                    m.get_mut(&token).unwrap()
+                }
+                Value::Array(arr) => {
+                    let i = self.index(&token, arr.len())?;
+                    if i < arr.len() {
+                        if rest.is_empty() {
+                            return Ok(Some(std::mem::replace(&mut arr[i], v)));
+                        }
+                        &mut arr[i]
+                    } else {
+                        if !rest.is_empty() && !create {
+                            return self.error("not found");
+                        }
+                        self.check_padding(arr.len(), i)?;
+                        let child = if rest.is_empty() {
+                            v
+                        } else {
+                            self.build(rest, v)?
+                        };
+                        arr.resize(i, Value::Null);
+                        arr.push(child);
+                        return Ok(None);
+                    }
+                }
+                _ => return self.error("parent is not a container"),
+            };
+        }
+        unreachable!("pointer_set has at least one token")
+    }
+
+    // The new container `tokens[0]` goes into, holding `v` at the end of
+    // `tokens`. Checked top down, for the pointer of a failing token, then
+    // built bottom up.
+    fn build(&mut self, tokens: &[&str], v: Value) -> Result<Value, PointerError> {
+        let mut indexes = Vec::with_capacity(tokens.len());
+        for raw in tokens {
+            self.push(raw);
+            let i = if holds_index(raw) {
+                let i = self.index(raw, 0)?;
+                self.check_padding(0, i)?;
+                Some(i)
+            } else {
+                None
+            };
+            indexes.push(i);
+        }
+        let mut x = v;
+        for (raw, i) in tokens.iter().zip(indexes).rev() {
+            x = match i {
+                Some(i) => {
+                    let mut arr = vec![Value::Null; i];
+                    arr.push(x);
+                    Value::Array(arr)
+                }
+                None => {
+                    let mut m = Map::new();
+                    m.insert(raw.replace("~1", "/").replace("~0", "~"), x);
+                    Value::Object(m)
+                }
+            };
+        }
+        Ok(x)
+    }
+
+    fn push(&mut self, raw: &str) {
+        self.pointer.push('/');
+        self.pointer.push_str(raw);
+    }
+
+    // Index of `token` in an array of length `len`, `-` being `len`.
+    fn index(&self, token: &str, len: usize) -> Result<usize, PointerError> {
+        if token == "-" {
+            return Ok(len);
+        }
+        match pointer_index(token) {
+            Some(i) => Ok(i),
+            None => self.error("invalid array index"),
+        }
+    }
+
+    // Whether an array of length `len` may be padded with nulls up to `i`.
+    fn check_padding(&self, len: usize, i: usize) -> Result<(), PointerError> {
+        if i == len {
+            return Ok(());
+        }
+        if self.create == CreateMode::Existing {
+            return self.error("index past the end");
+        }
+        if i - len > MAX_PADDING {
+            return self.error(&format!("index pads more than {} nulls", MAX_PADDING));
+        }
+        Ok(())
+    }
+
+    fn error<T>(&self, desc: &str) -> Result<T, PointerError> {
+        Err(PointerError {
+            pointer: self.pointer.clone(),
+            desc: desc.to_string(),
+        })
+    }
+}
+
+// Whether a container created for token `next` is an array.
+fn holds_index(next: &str) -> bool {
+    next == "-" || pointer_index(next).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    const FIXTURE: &str = "{\"name\":\"zxh\",\"servers\":[{\"host\":\"a\",\"ports\":[80,443]},\
+                           {\"host\":\"b\",\"ports\":[8080]}],\"a/b\":{\"~\":null},\"empty\":{}}";
+
+    #[test]
+    fn test_pointer_set_rebuild() {
+        let expect = Reader::new(FIXTURE).parse().unwrap();
+        let mut pairs = Vec::new();
+        expect.walk(&mut |path, v| {
+            let leaf = match v {
+                Value::Array(v) => v.is_empty(),
+                Value::Object(v) => v.is_empty(),
+                _ => true,
+            };
+            if leaf {
+                pairs.push((crate::pointer_of(path), v.clone()));
+            }
+        });
+
+        // backwards, so arrays are padded first and the nulls replaced later
+        pairs.reverse();
+        let mut v = Value::Null;
+        for (p, x) in pairs {
+            v.pointer_set(&p, x, CreateMode::CreateObjects).unwrap();
+        }
+        assert_eq!(expect, v);
+    }
+
+    #[test]
+    fn test_pointer_set() {
+        let mut v = Reader::new(FIXTURE).parse().unwrap();
+        let old = v.pointer_set("/name", Value::Bool(true), CreateMode::Existing);
+        assert_eq!(Ok(Some(Value::String(String::from("zxh")))), old);
+
+        let n = |f| Value::Number(f);
+        assert_eq!(
+            Ok(None),
+            v.pointer_set("/servers/1/ports/-", n(1.0), CreateMode::Existing)
+        );
+        assert_eq!(
+            Ok(None),
+            v.pointer_set("/servers/1/ports/2", n(2.0), CreateMode::Existing)
+        );
+        assert_eq!(Some(&n(2.0)), v.pointer("/servers/1/ports/2"));
+
+        let e = v.pointer_set("/servers/1/ports/9", n(0.0), CreateMode::Existing);
+        assert_eq!(
+            "pointer index past the end at '/servers/1/ports/9'",
+            e.unwrap_err().to_string()
+        );
+        let e = v
+            .pointer_set("/x/y", n(0.0), CreateMode::Existing)
+            .unwrap_err();
+        assert_eq!(("/x", "not found"), (e.pointer.as_str(), e.desc.as_str()));
+        let e = v
+            .pointer_set("/name/x", n(0.0), CreateMode::CreateObjects)
+            .unwrap_err();
+        assert_eq!("parent is not a container", e.desc);
+        let e = v
+            .pointer_set("/servers/01", n(0.0), CreateMode::CreateObjects)
+            .unwrap_err();
+        assert_eq!("invalid array index", e.desc);
+
+        v.pointer_set("/x/3/y", n(5.0), CreateMode::CreateObjects)
+            .unwrap();
+        assert_eq!(Some(&Value::Null), v.pointer("/x/2"));
+        assert_eq!(Some(&n(5.0)), v.pointer("/x/3/y"));
+
+        let before = v.clone();
+        let e = v
+            .pointer_set("/big/999999999", n(0.0), CreateMode::CreateObjects)
+            .unwrap_err();
+        assert_eq!("index pads more than 1024 nulls", e.desc);
+        assert_eq!(before, v);
+        let e = v
+            .pointer_set("/x/9999/a/01", n(0.0), CreateMode::CreateObjects)
+            .unwrap_err();
+        assert_eq!("/x/9999", e.pointer);
+        let e = v
+            .pointer_set("/x/4/a/01/2000", n(0.0), CreateMode::CreateObjects)
+            .unwrap_err();
+        assert_eq!(
+            ("/x/4/a/01/2000", "index pads more than 1024 nulls"),
+            (e.pointer.as_str(), e.desc.as_str())
+        );
+        assert_eq!(before, v);
+
+        let old = v
+            .pointer_set("", Value::Null, CreateMode::Existing)
+            .unwrap();
+        assert!(old.is_some());
+        assert_eq!(Value::Null, v);
+    }
+
+    #[test]
+    fn test_pointer_set_deep() {
+        let p = "/a".repeat(200_000);
+        let mut v = Value::Null;
+        let n = |f| Value::Number(f);
+        assert_eq!(
+            Ok(None),
+            v.pointer_set(&p, n(1.0), CreateMode::CreateObjects)
+        );
+        assert_eq!(Some(&n(1.0)), v.pointer(&p));
+        let old = v.pointer_set(&p, n(2.0), CreateMode::Existing);
+        assert_eq!(Ok(Some(n(1.0))), old);
+        let e = v
+            .pointer_set(&format!("{}/b/c", p), n(0.0), CreateMode::Existing)
+            .unwrap_err();
+        assert_eq!("parent is not a container", e.desc);
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+
+use crate::{ParseError, Reader, Value};
+
+/// Parses top-level values from input that arrives in pieces, like reads
+/// from a socket. `feed` takes each piece as it comes; `next_value` gives
+/// the values completed so far, in order, then `None` until more input
+/// completes another one.
+///
+/// Values may be separated by whitespace or nothing, `{"a":1}[2] 3`. A
+/// number or literal at the top level is only known to be complete at the
+/// next whitespace or bracket, or at `end`. A broken value is yielded as
+/// its error, with its position in the whole input, and parsing goes on
+/// with the next one. Input is strict RFC 8259, as with `Reader::new`.
+///
+/// Only the text of the value being read is kept between feeds.
+#[derive(Debug)]
+pub struct PushParser {
+    buf: String,
+    // start of the value being read, text before it is no longer needed
+    start: usize,
+    // next byte of `buf` to scan
+    pos: usize,
+    // row and column of `pos` in the whole input
+    row: usize,
+    column: usize,
+    // row and column of `start` while a value is read
+    value_at: (usize, usize),
+    scan: Scan,
+    ready: VecDeque<Result<Value, ParseError>>,
+    ended: bool,
+}
+
+// What the scanner is in. Containers are only counted, the parser checks
+// them once complete.
+#[derive(Debug, Clone, Copy)]
+enum Scan {
+    Between,
+    // a number or literal
+    Scalar,
+    // a string inside `depth` containers, after a `\` if `escaped`
+    String { depth: usize, escaped: bool },
+    // inside this many containers
+    Nested(usize),
+}
+
+impl Default for PushParser {
+    fn default() -> PushParser {
+        PushParser::new()
+    }
+}
+
+impl PushParser {
+    pub fn new() -> PushParser {
+        PushParser {
+            buf: String::new(),
+            start: 0,
+            pos: 0,
+            row: 1,
+            column: 1,
+            value_at: (1, 1),
+            scan: Scan::Between,
+            ready: VecDeque::new(),
+            ended: false,
+        }
+    }
+
+    /// Add the next piece of input.
+    pub fn feed(&mut self, chunk: &str) {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.pos -= self.start;
+            self.start = 0;
+        }
+        self.buf.push_str(chunk);
+        self.scan();
+    }
+
+    /// Mark the end of input: a number or literal being read is complete
+    /// and an unfinished value becomes an error, both then available from
+    /// `next_value`.
+    pub fn end(&mut self) {
+        self.ended = true;
+        self.scan();
+        if !matches!(self.scan, Scan::Between) {
+            self.complete(self.buf.len());
+        }
+    }
+
+    /// The oldest value completed and not taken yet.
+    pub fn next_value(&mut self) -> Option<Result<Value, ParseError>> {
+        self.ready.pop_front()
+    }
+
+    fn scan(&mut self) {
+        while let Some(ch) = self.buf[self.pos..].chars().next() {
+            let next = self.buf[self.pos + ch.len_utf8()..].chars().next();
+            // a '\r' is a line break of its own unless a '\n' follows
+            if ch == '\r' && next.is_none() && !self.ended {
+                break;
+            }
+            if let Scan::Scalar = self.scan {
+                if is_delimiter(ch) {
+                    self.complete(self.pos);
+                    continue;
+                }
+            }
+
+            let mut done = false;
+            self.scan = match self.scan {
+                Scan::Between if is_whitespace(ch) => Scan::Between,
+                Scan::Between => {
+                    self.start = self.pos;
+                    self.value_at = (self.row, self.column);
+                    match ch {
+                        '[' | '{' => Scan::Nested(1),
+                        '"' => Scan::String {
+                            depth: 0,
+                            escaped: false,
+                        },
+                        ']' | '}' | ',' | ':' => {
+                            done = true;
+                            Scan::Between
+                        }
+                        _ => Scan::Scalar,
+                    }
+                }
+                Scan::Scalar => Scan::Scalar,
+                Scan::String { depth, escaped } => match ch {
+                    _ if escaped => Scan::String {
+                        depth,
+                        escaped: false,
+                    },
+                    '\\' => Scan::String {
+                        depth,
+                        escaped: true,
+                    },
+                    '"' if depth == 0 => {
+                        done = true;
+                        Scan::Between
+                    }
+                    '"' => Scan::Nested(depth),
+                    _ => Scan::String { depth, escaped },
+                },
+                Scan::Nested(depth) => match ch {
+                    '"' => Scan::String {
+                        depth,
+                        escaped: false,
+                    },
+                    '[' | '{' => Scan::Nested(depth + 1),
+                    ']' | '}' if depth == 1 => {
+                        done = true;
+                        Scan::Between
+                    }
+                    ']' | '}' => Scan::Nested(depth - 1),
+                    _ => Scan::Nested(depth),
+                },
+            };
+
+            if ch == '\n' || (ch == '\r' && next != Some('\n')) {
+                self.row += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+            self.pos += ch.len_utf8();
+            if done {
+                self.complete(self.pos);
+            } else if let Scan::Between = self.scan {
+                self.start = self.pos;
+            }
+        }
+    }
+
+    // Parse the value from `start` to `end`, where the scanner found it
+    // complete.
+    fn complete(&mut self, end: usize) {
+        let mut r = Reader::new(&self.buf[self.start..end]);
+        r.row = self.value_at.0;
+        r.column = self.value_at.1;
+        let x = r.parse_tree();
+        let x = match r.abort.take() {
+            Some(e) => Err(e),
+            None => x.and_then(|v| r.expect_end().map(|()| v)),
+        };
+        self.ready.push_back(x);
+        self.start = end;
+        self.scan = Scan::Between;
+    }
+}
+
+fn is_whitespace(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\r')
+}
+
+// What ends a number or literal at the top level.
+fn is_delimiter(ch: char) -> bool {
+    is_whitespace(ch) || matches!(ch, '[' | ']' | '{' | '}' | '"' | ',' | ':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(p: &mut PushParser) -> Vec<Result<Value, ParseError>> {
+        std::iter::from_fn(|| p.next_value()).collect()
+    }
+
+    #[test]
+    fn test_push_parser_one_char_at_a_time() {
+        let src = "{\r\n  \"name\": \"zxh \\\"é\\\" \\u00e9\",\n  \"ports\": [80, 443],\n  \
+                   \"nested\": {\"a\": [[], {}, [{\"b\": null}]]},\n  \"ok\": true\n}";
+        let mut p = PushParser::new();
+        let mut buf = [0; 4];
+        for (i, ch) in src.chars().enumerate() {
+            assert!(p.next_value().is_none(), "complete at {}", i);
+            p.feed(ch.encode_utf8(&mut buf));
+        }
+        let v = p.next_value().unwrap().unwrap();
+        assert_eq!(Reader::new(src).parse().unwrap(), v);
+        p.end();
+        assert!(p.next_value().is_none());
+    }
+
+    #[test]
+    fn test_push_parser_values() {
+        let src = "{\"a\":1}[2] 3\n\"s\" true\r\nnull -4.5e1";
+        let mut p = PushParser::new();
+        p.feed(&src[..9]);
+        let first: Vec<_> = values(&mut p).into_iter().map(Result::unwrap).collect();
+        assert_eq!(vec![Reader::new("{\"a\":1}").parse().unwrap()], first);
+        p.feed(&src[9..]);
+        p.end();
+        let rest: Vec<_> = values(&mut p).into_iter().map(Result::unwrap).collect();
+        let expect = Reader::new("[[2], 3, \"s\", true, null, -45]")
+            .parse()
+            .unwrap();
+        assert_eq!(expect.into_array().unwrap(), rest);
+    }
+
+    #[test]
+    fn test_push_parser_errors() {
+        let mut p = PushParser::new();
+        p.feed("[1, 2]\n[1 2]\n  {\"a\": tru}");
+        p.feed(" 7 [\"x");
+        let x = values(&mut p);
+        assert_eq!(4, x.len());
+        assert!(x[0].is_ok());
+        let e = x[1].as_ref().unwrap_err();
+        assert_eq!((2, 4), (e.row, e.column));
+        let e = x[2].as_ref().unwrap_err();
+        assert_eq!((3, 9), (e.row, e.column));
+        assert_eq!(Ok(Value::Number(7.0)), x[3]);
+
+        p.end();
+        let e = p.next_value().unwrap().unwrap_err();
+        assert_eq!((3, 19), (e.row, e.column));
+        assert!(p.next_value().is_none());
+    }
+}
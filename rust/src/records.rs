@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::{Map, Value};
+
+/// Why an array of records could not be reshaped. `index` is the element
+/// at fault, `None` when the value is not an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeError {
+    pub index: Option<usize>,
+    pub desc: String,
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(i) => write!(f, "shape {} at element {}", self.desc, i),
+            None => write!(f, "shape {}", self.desc),
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+/// Helpers for the common array-of-objects shape. They build new values and
+/// leave `self` as it is.
+impl Value {
+    /// The elements of an array of objects grouped by their `key` member:
+    /// an object of arrays, one per distinct value, elements in order. A
+    /// string is its own group name, other scalars are named as
+    /// `coerce_str` writes them. Every element must be an object with a
+    /// scalar `key`.
+    pub fn group_by_key(&self, key: &str) -> Result<Value, ShapeError> {
+        let mut groups = Map::new();
+        for (i, record) in records(self)? {
+            let name = record_key(i, record, key, false)?;
+            let group = groups
+                .entry(name)
+                .or_insert_with(|| Value::Array(Vec::new()));
+            group.extend_array(Some(record.clone()));
+        }
+        Ok(Value::Object(groups))
+    }
+
+    /// The `key` member of each element of an array, `null` where an
+    /// element has none or is not an object. Empty if `self` is not an
+    /// array.
+    pub fn pluck(&self, key: &str) -> Value {
+        let arr = match self {
+            Value::Array(arr) => arr,
+            _ => return Value::Array(Vec::new()),
+        };
+        let field = |x: &Value| x.get(key).cloned().unwrap_or(Value::Null);
+        Value::Array(arr.iter().map(field).collect())
+    }
+
+    /// An array of objects as an object keyed by each element's `key`
+    /// member. With `strict` that member must be a string and unique;
+    /// otherwise scalars are named as for `group_by_key` and the last of
+    /// several elements with one name is kept.
+    pub fn index_by(&self, key: &str, strict: bool) -> Result<Value, ShapeError> {
+        let mut index = Map::new();
+        let mut first = HashMap::new();
+        for (i, record) in records(self)? {
+            let name = record_key(i, record, key, strict)?;
+            if strict {
+                if let Some(j) = first.insert(name.clone(), i) {
+                    return Err(ShapeError {
+                        index: Some(i),
+                        desc: format!("key '{}' already at element {}", name, j),
+                    });
+                }
+            }
+            index.insert(name, record.clone());
+        }
+        Ok(Value::Object(index))
+    }
+}
+
+// The elements of `v` with their indices, each checked to be an object.
+fn records(v: &Value) -> Result<impl Iterator<Item = (usize, &Value)>, ShapeError> {
+    let arr = match v {
+        Value::Array(arr) => arr,
+        _ => {
+            return Err(ShapeError {
+                index: None,
+                desc: String::from("expect array"),
+            })
+        }
+    };
+    if let Some(i) = arr.iter().position(|x| !matches!(x, Value::Object(_))) {
+        return Err(ShapeError {
+            index: Some(i),
+            desc: String::from("expect object"),
+        });
+    }
+    Ok(arr.iter().enumerate())
+}
+
+// The name record `i` goes under: its `key` member as text, only a string
+// if `strings_only`.
+fn record_key(
+    i: usize,
+    record: &Value,
+    key: &str,
+    strings_only: bool,
+) -> Result<String, ShapeError> {
+    let err = |desc: String| ShapeError {
+        index: Some(i),
+        desc,
+    };
+    let v = record
+        .get(key)
+        .ok_or_else(|| err(format!("missing key '{}'", key)))?;
+    match v {
+        Value::String(s) => Ok(s.clone()),
+        _ if strings_only => Err(err(format!("key '{}' expect string", key))),
+        _ if v.is_container() => Err(err(format!("key '{}' expect scalar", key))),
+        _ => Ok(v.coerce_str(false).unwrap_or_default().into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    const RECORDS: &str = "[{\"id\": \"u1\", \"team\": \"red\", \"age\": 30}, \
+                           {\"id\": \"u2\", \"team\": \"blue\"}, \
+                           {\"id\": \"u3\", \"team\": \"red\", \"age\": null}]";
+
+    fn parse(s: &str) -> Value {
+        Reader::new(s).parse().unwrap()
+    }
+
+    #[test]
+    fn test_group_by_key() {
+        let v = parse(RECORDS);
+        let groups = v.group_by_key("team").unwrap();
+        let expect = format!(
+            "{{\"red\": [{0}, {2}], \"blue\": [{1}]}}",
+            parse(RECORDS).pointer("/0").unwrap(),
+            parse(RECORDS).pointer("/1").unwrap(),
+            parse(RECORDS).pointer("/2").unwrap()
+        );
+        assert_eq!(parse(&expect), groups);
+        assert_eq!(parse(RECORDS), v);
+
+        let v = parse("[{\"n\": 1}, {\"n\": \"1\"}, {\"n\": true}, {\"n\": null}]");
+        let groups = v.group_by_key("n").unwrap();
+        assert_eq!(
+            Some(2),
+            groups.get("1").and_then(Value::as_array).map(Vec::len)
+        );
+        assert!(groups.get("true").is_some() && groups.get("null").is_some());
+
+        let e = parse(RECORDS).group_by_key("age").unwrap_err();
+        assert_eq!((Some(1), "missing key 'age'"), (e.index, e.desc.as_str()));
+        let e = parse("[{\"k\": []}]").group_by_key("k").unwrap_err();
+        assert_eq!("shape key 'k' expect scalar at element 0", e.to_string());
+        let e = parse("[{}, 1]").group_by_key("k").unwrap_err();
+        assert_eq!((Some(1), "expect object"), (e.index, e.desc.as_str()));
+        let e = parse("{}").group_by_key("k").unwrap_err();
+        assert_eq!("shape expect array", e.to_string());
+        assert_eq!(Ok(parse("{}")), parse("[]").group_by_key("k"));
+    }
+
+    #[test]
+    fn test_pluck() {
+        let v = parse(RECORDS);
+        assert_eq!(parse("[\"u1\", \"u2\", \"u3\"]"), v.pluck("id"));
+        assert_eq!(parse("[30, null, null]"), v.pluck("age"));
+        assert_eq!(
+            parse("[null, 2, null]"),
+            parse("[1, {\"a\": 2}, []]").pluck("a")
+        );
+        assert_eq!(parse("[]"), parse("{\"a\": 1}").pluck("a"));
+    }
+
+    #[test]
+    fn test_index_by() {
+        let v = parse(RECORDS);
+        let index = v.index_by("id", true).unwrap();
+        for id in ["u1", "u2", "u3"].iter() {
+            assert_eq!(
+                Some(&Value::String(id.to_string())),
+                index.get(id).and_then(|r| r.get("id"))
+            );
+        }
+
+        // duplicates: an error when strict, else the last wins
+        let e = v.index_by("team", true).unwrap_err();
+        assert_eq!(
+            (Some(2), "key 'red' already at element 0"),
+            (e.index, e.desc.as_str())
+        );
+        let index = v.index_by("team", false).unwrap();
+        assert_eq!(
+            Some(&Value::String(String::from("u3"))),
+            index.get("red").and_then(|r| r.get("id"))
+        );
+
+        // non-string keys
+        let v = parse("[{\"n\": 1, \"x\": \"a\"}, {\"n\": 2.5, \"x\": \"b\"}]");
+        let e = v.index_by("n", true).unwrap_err();
+        assert_eq!(
+            (Some(0), "key 'n' expect string"),
+            (e.index, e.desc.as_str())
+        );
+        let index = v.index_by("n", false).unwrap();
+        assert_eq!(
+            Some(&Value::String(String::from("b"))),
+            index.get("2.5").and_then(|r| r.get("x"))
+        );
+
+        let e = parse(RECORDS).index_by("age", false).unwrap_err();
+        assert_eq!((Some(1), "missing key 'age'"), (e.index, e.desc.as_str()));
+    }
+}
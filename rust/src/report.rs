@@ -0,0 +1,134 @@
+use crate::{ErrorKind, Map, ParseError, Value};
+
+// Characters of the source line kept on each side of the error column.
+const SNIPPET_REACH: usize = 40;
+
+impl ParseError {
+    /// The error as a JSON object, for clients that want it structured:
+    ///
+    /// `{"kind":"unexpected_token","message":"...","line":3,"column":10,
+    /// "offset":27,"snippet":"\"b\": x","marker":5}`
+    ///
+    /// `source` is the input that failed, giving the byte `offset` of the
+    /// error and the `snippet`, its line trimmed of whitespace and cut to
+    /// 40 characters each side of the error. `marker` is the char index of
+    /// the error in the snippet. Columns are taken as `ColumnUnit::Chars`.
+    pub fn to_value(&self, source: &str) -> Value {
+        let (offset, snippet, marker) = locate(source, self.row, self.column);
+        let mut m = Map::document();
+        m.insert(
+            "kind".to_string(),
+            Value::String(kind_name(&self.kind).to_string()),
+        );
+        m.insert("message".to_string(), Value::String(self.desc.clone()));
+        m.insert("line".to_string(), Value::Number(self.row as f64));
+        m.insert("column".to_string(), Value::Number(self.column as f64));
+        m.insert("offset".to_string(), Value::Number(offset as f64));
+        m.insert("snippet".to_string(), Value::String(snippet));
+        m.insert("marker".to_string(), Value::Number(marker as f64));
+        Value::Object(m)
+    }
+
+    /// `to_value` written as compact JSON.
+    pub fn render_json(&self, source: &str) -> String {
+        self.to_value(source).to_string()
+    }
+}
+
+fn kind_name(kind: &ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Syntax => "syntax",
+        ErrorKind::UnexpectedToken => "unexpected_token",
+        ErrorKind::Cancelled => "cancelled",
+        ErrorKind::Io => "io",
+        ErrorKind::LimitExceeded => "limit_exceeded",
+        ErrorKind::DeadlineExceeded => "deadline_exceeded",
+        ErrorKind::InvalidRootType => "invalid_root_type",
+        ErrorKind::NumberOutOfRange => "number_out_of_range",
+        ErrorKind::DuplicateKey(_) => "duplicate_key",
+        ErrorKind::InvalidKey(_) => "invalid_key",
+    }
+}
+
+// Byte offset of `row`:`column` in `source`, the snippet around it and the
+// char index of the column in the snippet. Rows break where the parser
+// breaks them, at `\n`, `\r\n` and a lone `\r`.
+fn locate(source: &str, row: usize, column: usize) -> (usize, String, usize) {
+    let mut start = 0;
+    for _ in 1..row {
+        match source[start..].find(['\n', '\r']) {
+            Some(i) => {
+                start += i + 1;
+                if source[start - 1..].starts_with("\r\n") {
+                    start += 1;
+                }
+            }
+            None => return (source.len(), String::new(), 0),
+        }
+    }
+    let line = &source[start..];
+    let line = &line[..line.find(['\n', '\r']).unwrap_or(line.len())];
+
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let at = column.saturating_sub(1).min(chars.len());
+    let offset = start + chars.get(at).map_or(line.len(), |c| c.0);
+
+    let is_space = |i: usize| chars[i].1.is_whitespace();
+    let mut from = at.saturating_sub(SNIPPET_REACH);
+    let mut to = (at + 1 + SNIPPET_REACH).min(chars.len());
+    while from < at && is_space(from) {
+        from += 1;
+    }
+    while to > from && to > at + 1 && is_space(to - 1) {
+        to -= 1;
+    }
+    let snippet = chars[from..to].iter().map(|c| c.1).collect();
+    (offset, snippet, at - from)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Reader;
+
+    fn report(src: &str) -> String {
+        let e = Reader::new(src).parse().unwrap_err();
+        let s = e.render_json(src);
+        assert_eq!(e.to_value(src), Reader::new(&s).parse().unwrap());
+        s
+    }
+
+    #[test]
+    fn test_render_json() {
+        let src = "{\n  \"a\": [1, 2],\r\n    \"b\": x  \n}";
+        assert_eq!(
+            "{\"kind\":\"unexpected_token\",\"message\":\"unexpected character 'x'\",\
+             \"line\":3,\"column\":10,\"offset\":27,\"snippet\":\"\\\"b\\\": x\",\"marker\":5}",
+            report(src)
+        );
+        assert_eq!('x', src[27..].chars().next().unwrap());
+
+        let src = "[\"é\", 1e999]";
+        assert_eq!(
+            "{\"kind\":\"number_out_of_range\",\"message\":\"number '1e999' out of range\",\
+             \"line\":1,\"column\":7,\"offset\":7,\"snippet\":\"[\\\"é\\\", 1e999]\",\
+             \"marker\":6}",
+            report(src)
+        );
+
+        // the end of input, past the last character
+        let s = report("[1,\n 2");
+        assert!(s.contains("\"line\":2,\"column\":3,\"offset\":6,\"snippet\":\"2\",\"marker\":1"));
+    }
+
+    #[test]
+    fn test_render_json_long_line() {
+        let src = format!("[{}x{}]", "1,".repeat(100), ",1".repeat(100));
+        let s = report(&src);
+        let expect = format!(
+            "\"snippet\":\"{}x{}\",\"marker\":40",
+            "1,".repeat(20),
+            ",1".repeat(20)
+        );
+        assert!(s.contains(&expect), "{}", s);
+    }
+}
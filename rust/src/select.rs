@@ -0,0 +1,105 @@
+use crate::Value;
+
+impl Value {
+    /// Every node for which `pred` of its JSON Pointer and value holds,
+    /// `self` included with the pointer `""`. Nodes come depth-first,
+    /// parents before children, array elements by index and object members
+    /// by key, so the order does not depend on the map's.
+    pub fn select<F: FnMut(&str, &Value) -> bool>(&self, mut pred: F) -> Vec<(String, &Value)> {
+        self.select_nodes(|pointer, _, v| pred(pointer, v))
+    }
+
+    /// Every value of a member named `key`, at any depth, in `select` order.
+    pub fn find_key(&self, key: &str) -> Vec<(String, &Value)> {
+        self.select_nodes(|_, k, _| k == Some(key))
+    }
+
+    /// Every string containing `needle`, in `select` order.
+    pub fn find_string_containing(&self, needle: &str) -> Vec<(String, &Value)> {
+        self.select(|_, v| matches!(v, Value::String(s) if s.contains(needle)))
+    }
+
+    // `select` with the key a node is stored under, `None` for the root and
+    // array elements. Iterative, so deep documents can't overflow the stack.
+    fn select_nodes<F>(&self, mut pred: F) -> Vec<(String, &Value)>
+    where
+        F: FnMut(&str, Option<&str>, &Value) -> bool,
+    {
+        let mut found = Vec::new();
+        let mut stack = vec![(String::new(), None, self)];
+        while let Some((pointer, key, v)) = stack.pop() {
+            // children go on the stack last first, to come off in order
+            match v {
+                Value::Array(arr) => {
+                    for (i, x) in arr.iter().enumerate().rev() {
+                        stack.push((format!("{}/{}", pointer, i), None, x));
+                    }
+                }
+                Value::Object(m) => {
+                    let mut members = m.visible();
+                    members.sort_by(|a, b| a.0.cmp(b.0));
+                    for (k, x) in members.into_iter().rev() {
+                        let token = k.replace('~', "~0").replace('/', "~1");
+                        stack.push((format!("{}/{}", pointer, token), Some(k), x));
+                    }
+                }
+                _ => {}
+            }
+            if pred(&pointer, key, v) {
+                found.push((pointer, v));
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    const FIXTURE: &str = "{\"id\": 1, \"users\": [{\"id\": \"u1\", \"name\": \"ann smith\", \
+                           \"groups\": [{\"id\": 10}, {\"name\": \"id\"}]}, {\"profile\": \
+                           {\"id\": null, \"bio\": \"smith/jones\"}}], \"meta\": {\"a/id\": 2}}";
+
+    #[test]
+    fn test_find_key() {
+        let v = Reader::new(FIXTURE).parse().unwrap();
+        let found = v.find_key("id");
+        let pointers: Vec<&str> = found.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(
+            vec![
+                "/id",
+                "/users/0/groups/0/id",
+                "/users/0/id",
+                "/users/1/profile/id"
+            ],
+            pointers
+        );
+        for (p, x) in &found {
+            assert_eq!(v.pointer(p), Some(*x));
+        }
+        assert_eq!(&Value::Number(10.0), found[1].1);
+        assert!(v.find_key("nope").is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        let v = Reader::new(FIXTURE).parse().unwrap();
+        let found = v.find_string_containing("smith");
+        assert_eq!(
+            vec!["/users/0/name", "/users/1/profile/bio"],
+            found.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>()
+        );
+
+        let numbers = v.select(|_, x| matches!(x, Value::Number(_)));
+        let pointers: Vec<&str> = numbers.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(vec!["/id", "/meta/a~1id", "/users/0/groups/0/id"], pointers);
+
+        let all = v.select(|_, _| true);
+        assert_eq!(("", &v), (all[0].0.as_str(), all[0].1));
+        let mut count = 0;
+        v.walk(&mut |_, _| count += 1);
+        assert_eq!(count, all.len());
+    }
+}
@@ -0,0 +1,1039 @@
+use std::fmt::{self, Write};
+use std::io;
+
+use crate::{ParseOptions, Reader, Trivia, Value};
+
+const INDENT: &str = "  ";
+
+/// A textual output format for `Value::format`.
+pub trait Formatter {
+    fn write_value(&self, w: &mut dyn Write, v: &Value) -> fmt::Result;
+}
+
+/// Compact JSON, what `Display` writes. Numbers are written as JavaScript
+/// writes them, `-0.0` as `0` and non-finite numbers, which JSON has no
+/// literal for, as `null`. See `NonFinite` for the other policies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactJson;
+
+/// JSON with one member or element per line, indented by two spaces, what
+/// `Value::to_string_pretty` writes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrettyJson;
+
+impl Formatter for CompactJson {
+    fn write_value(&self, mut w: &mut dyn Write, v: &Value) -> fmt::Result {
+        Serializer::compact().infallible().value(&mut w, v)
+    }
+}
+
+impl Formatter for PrettyJson {
+    fn write_value(&self, mut w: &mut dyn Write, v: &Value) -> fmt::Result {
+        Serializer::pretty().infallible().value(&mut w, v)
+    }
+}
+
+/// Line terminator of pretty output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Newline {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+/// When output escapes `/` as `\/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolidusMode {
+    #[default]
+    Never,
+    /// Only after `<`, so `</script>` can't end an HTML script element.
+    AfterLt,
+    Always,
+}
+
+/// What to write for a NaN or infinite number, which JSON has no literal
+/// for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinite {
+    /// Fail. The `io` writers return an `InvalidData` error; the writers
+    /// returning a `String`, which cannot fail, write `null` instead.
+    #[default]
+    Error,
+    /// `null`, as JavaScript's `JSON.stringify` writes it.
+    Null,
+    /// The `NaN`, `Infinity` and `-Infinity` literals accepted by
+    /// `ParseOptions::allow_nan`. Not JSON.
+    Literal,
+}
+
+/// Settings for the `_with` writers: `Value::to_string_with` and
+/// `write_to_with` for compact output, `to_string_pretty_with` and
+/// `write_pretty_to_with` for pretty output. The default is what
+/// `to_string` and `to_string_pretty` write. `max_inline_width` only
+/// applies to pretty output.
+#[must_use]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    pub newline: Newline,
+    /// End the output with a line terminator.
+    pub final_newline: bool,
+    /// Write an array or object on one line, `[1, 2]`, when that fits in
+    /// this many columns counting its indentation and member key. Never for
+    /// one holding a string with a line break. 0 never inlines.
+    pub max_inline_width: usize,
+    pub escape_solidus: SolidusMode,
+    /// Escape U+2028 and U+2029, valid in JSON strings but line breaks in
+    /// older JavaScript.
+    pub escape_js_line_separators: bool,
+    /// Escape DEL and every character beyond ASCII as `\uXXXX`, a surrogate
+    /// pair above U+FFFF, so the output is plain ASCII.
+    pub ascii_only: bool,
+    /// Write a string value holding a JSON number literal, `"1.50"`, as that
+    /// number, `1.50`, to put back what `ParseOptions::numbers_as_strings`
+    /// read. Any other string is quoted as usual, as are member keys.
+    pub numbers_as_strings: bool,
+    pub non_finite: NonFinite,
+}
+
+impl WriteOptions {
+    pub fn new() -> WriteOptions {
+        WriteOptions::default()
+    }
+
+    pub fn newline(mut self, newline: Newline) -> WriteOptions {
+        self.newline = newline;
+        self
+    }
+
+    pub fn final_newline(mut self, on: bool) -> WriteOptions {
+        self.final_newline = on;
+        self
+    }
+
+    pub fn max_inline_width(mut self, width: usize) -> WriteOptions {
+        self.max_inline_width = width;
+        self
+    }
+
+    pub fn escape_solidus(mut self, mode: SolidusMode) -> WriteOptions {
+        self.escape_solidus = mode;
+        self
+    }
+
+    pub fn escape_js_line_separators(mut self, on: bool) -> WriteOptions {
+        self.escape_js_line_separators = on;
+        self
+    }
+
+    pub fn ascii_only(mut self, on: bool) -> WriteOptions {
+        self.ascii_only = on;
+        self
+    }
+
+    pub fn numbers_as_strings(mut self, on: bool) -> WriteOptions {
+        self.numbers_as_strings = on;
+        self
+    }
+
+    pub fn non_finite(mut self, policy: NonFinite) -> WriteOptions {
+        self.non_finite = policy;
+        self
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        CompactJson.write_value(f, self)
+    }
+}
+
+impl Value {
+    /// Stream compact JSON to `w` without building a `String` first. Many
+    /// small writes are issued, so wrap unbuffered sinks in a `BufWriter`.
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        write_io(w, self, Serializer::compact())
+    }
+
+    /// `write_to` with the escapes and final line terminator of `opts`.
+    pub fn write_to_with<W: io::Write>(&self, w: &mut W, opts: &WriteOptions) -> io::Result<()> {
+        write_io(w, self, Serializer::compact_with(opts))
+    }
+
+    /// `to_string` with the escapes and final line terminator of `opts`.
+    pub fn to_string_with(&self, opts: &WriteOptions) -> String {
+        let mut s = String::new();
+        Serializer::compact_with(opts)
+            .infallible()
+            .value(&mut s, self)
+            .unwrap();
+        s
+    }
+
+    /// Like `write_to`, one member or element per line, indented by two
+    /// spaces.
+    pub fn write_pretty_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        write_io(w, self, Serializer::pretty())
+    }
+
+    /// `write_pretty_to` with the line terminators of `opts`.
+    pub fn write_pretty_to_with<W: io::Write>(
+        &self,
+        w: &mut W,
+        opts: &WriteOptions,
+    ) -> io::Result<()> {
+        write_io(w, self, Serializer::with_options(opts))
+    }
+
+    pub fn to_string_pretty(&self) -> String {
+        self.format(&PrettyJson)
+    }
+
+    /// `to_string_pretty` with the line terminators of `opts`.
+    pub fn to_string_pretty_with(&self, opts: &WriteOptions) -> String {
+        let mut s = String::new();
+        Serializer::with_options(opts)
+            .infallible()
+            .value(&mut s, self)
+            .unwrap();
+        s
+    }
+
+    /// Write `self` with `f`.
+    pub fn format(&self, f: &dyn Formatter) -> String {
+        let mut s = String::new();
+        f.write_value(&mut s, self).unwrap();
+        s
+    }
+
+    /// Pretty print, putting back the comments and blank lines recorded by
+    /// `Reader::parse_with_trivia` in front of the nodes they preceded.
+    pub fn to_string_pretty_with_trivia(&self, trivia: &Trivia) -> String {
+        let mut ser = Serializer::pretty().infallible();
+        ser.trivia = Some(trivia);
+        let mut s = String::new();
+        ser.value(&mut s, self).unwrap();
+        s
+    }
+}
+
+// fmt::Write over io::Write, keeping the io::Error a fmt::Error can't carry.
+struct IoWriter<'a, W: io::Write> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> Write for IoWriter<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+fn write_io<W: io::Write>(w: &mut W, v: &Value, mut ser: Serializer<'_>) -> io::Result<()> {
+    let mut adapter = IoWriter {
+        inner: w,
+        error: None,
+    };
+    match ser.value(&mut adapter, v) {
+        Ok(()) => Ok(()),
+        // with no io::Error the serializer refused a non-finite number
+        Err(_) => Err(adapter
+            .error
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "non-finite number"))),
+    }
+}
+
+struct Serializer<'t> {
+    // current nesting level when pretty printing, `None` for compact output
+    depth: Option<usize>,
+    trivia: Option<&'t Trivia>,
+    // JSON Pointer of the node being written, kept only with trivia
+    pointer: String,
+    newline: &'static str,
+    final_newline: bool,
+    inline_width: usize,
+    // width of the member key before the node being written
+    prefix: usize,
+    // width of the `,` after the node being written, 0 for the last
+    suffix: usize,
+    escapes: Escapes,
+}
+
+impl<'t> Serializer<'t> {
+    fn compact() -> Serializer<'t> {
+        Serializer {
+            depth: None,
+            trivia: None,
+            pointer: String::new(),
+            newline: "\n",
+            final_newline: false,
+            inline_width: 0,
+            prefix: 0,
+            suffix: 0,
+            escapes: Escapes::default(),
+        }
+    }
+
+    fn with_options(opts: &WriteOptions) -> Serializer<'t> {
+        Serializer {
+            newline: match opts.newline {
+                Newline::Lf => "\n",
+                Newline::CrLf => "\r\n",
+            },
+            final_newline: opts.final_newline,
+            inline_width: opts.max_inline_width,
+            escapes: Escapes {
+                solidus: opts.escape_solidus,
+                line_separators: opts.escape_js_line_separators,
+                ascii_only: opts.ascii_only,
+                bare_numbers: opts.numbers_as_strings,
+                non_finite: opts.non_finite,
+            },
+            ..Serializer::pretty()
+        }
+    }
+
+    fn compact_with(opts: &WriteOptions) -> Serializer<'t> {
+        Serializer {
+            depth: None,
+            ..Serializer::with_options(opts)
+        }
+    }
+
+    fn pretty() -> Serializer<'t> {
+        Serializer {
+            depth: Some(0),
+            ..Serializer::compact()
+        }
+    }
+
+    // For the `String` writers, which can't report a refused number.
+    fn infallible(mut self) -> Serializer<'t> {
+        if self.escapes.non_finite == NonFinite::Error {
+            self.escapes.non_finite = NonFinite::Null;
+        }
+        self
+    }
+
+    fn value<W: Write>(&mut self, w: &mut W, v: &Value) -> fmt::Result {
+        if self.pointer.is_empty() {
+            if let Some(leading) = self.trivia.and_then(|t| t.get("")) {
+                for c in &leading.comments {
+                    w.write_str(c)?;
+                    self.newline(w, self.depth)?;
+                }
+            }
+        }
+        self.node(w, v)?;
+        if self.final_newline {
+            w.write_str(self.newline)?;
+        }
+        Ok(())
+    }
+
+    fn node<W: Write>(&mut self, w: &mut W, v: &Value) -> fmt::Result {
+        if let Some(s) = self.inline(v) {
+            return w.write_str(&s);
+        }
+        match v {
+            Value::Null => w.write_str("null"),
+            Value::Bool(b) => w.write_str(if *b { "true" } else { "false" }),
+            Value::Number(v) if !v.is_finite() => match self.escapes.non_finite {
+                NonFinite::Error => Err(fmt::Error),
+                NonFinite::Null => w.write_str("null"),
+                NonFinite::Literal => write_number(w, *v),
+            },
+            Value::Number(v) => write_number(w, *v),
+            Value::String(v) if self.escapes.bare_numbers && is_number_literal(v) => w.write_str(v),
+            Value::String(v) => write_string_with(w, v, self.escapes),
+            Value::Array(v) => {
+                if v.is_empty() {
+                    return w.write_str("[]");
+                }
+                w.write_char('[')?;
+                let depth = self.depth;
+                self.depth = depth.map(|d| d + 1);
+                for (i, x) in v.iter().enumerate() {
+                    if i > 0 {
+                        w.write_char(',')?;
+                    }
+                    let len = self.enter(&i.to_string());
+                    self.leading(w)?;
+                    self.prefix = 0;
+                    self.suffix = usize::from(i + 1 < v.len());
+                    self.node(w, x)?;
+                    self.pointer.truncate(len);
+                }
+                self.depth = depth;
+                self.newline(w, depth)?;
+                w.write_char(']')
+            }
+            Value::Object(v) => {
+                if v.is_empty() {
+                    return w.write_str("{}");
+                }
+                w.write_char('{')?;
+                let depth = self.depth;
+                self.depth = depth.map(|d| d + 1);
+                let mut members = v.entries().enumerate().peekable();
+                while let Some((i, (k, x))) = members.next() {
+                    if i > 0 {
+                        w.write_char(',')?;
+                    }
+                    let len = self.enter(k);
+                    self.leading(w)?;
+                    write_string_with(w, k, self.escapes)?;
+                    w.write_str(if depth.is_some() { ": " } else { ":" })?;
+                    if self.inline_width > 0 {
+                        let mut key = String::new();
+                        write_string_with(&mut key, k, self.escapes)?;
+                        self.prefix = key.chars().count() + 2;
+                        self.suffix = usize::from(members.peek().is_some());
+                    }
+                    self.node(w, x)?;
+                    self.pointer.truncate(len);
+                }
+                self.depth = depth;
+                self.newline(w, depth)?;
+                w.write_char('}')
+            }
+        }
+    }
+
+    // Container `v` on one line if `inline_width` allows, see
+    // `WriteOptions::max_inline_width`.
+    fn inline(&self, v: &Value) -> Option<String> {
+        let depth = self.depth?;
+        let empty = match v {
+            Value::Array(arr) => arr.is_empty(),
+            Value::Object(m) => m.is_empty(),
+            _ => true,
+        };
+        if empty || self.inline_width == 0 || self.trivia.is_some() {
+            return None;
+        }
+        let used = depth * INDENT.len() + self.prefix + self.suffix;
+        let mut w = Bounded {
+            s: String::new(),
+            width: 0,
+            room: self.inline_width.checked_sub(used)?,
+        };
+        write_inline(&mut w, v, self.escapes).ok()?;
+        Some(w.s)
+    }
+
+    // Extend the pointer by one token, returning the length to restore.
+    fn enter(&mut self, token: &str) -> usize {
+        let len = self.pointer.len();
+        if self.trivia.is_some() {
+            self.pointer.push('/');
+            self.pointer
+                .push_str(&token.replace('~', "~0").replace('/', "~1"));
+        }
+        len
+    }
+
+    // Line break before a child, with its blank lines and comments.
+    fn leading<W: Write>(&mut self, w: &mut W) -> fmt::Result {
+        let leading = self.trivia.and_then(|t| t.get(&self.pointer));
+        if let Some(leading) = leading {
+            for _ in 0..leading.blank_lines {
+                w.write_str(self.newline)?;
+            }
+        }
+        self.newline(w, self.depth)?;
+        if let Some(leading) = leading {
+            for c in &leading.comments {
+                w.write_str(c)?;
+                self.newline(w, self.depth)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn newline<W: Write>(&self, w: &mut W, depth: Option<usize>) -> fmt::Result {
+        if let Some(depth) = depth {
+            w.write_str(self.newline)?;
+            for _ in 0..depth {
+                w.write_str(INDENT)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Collects output while it fits in `room` columns, failing after.
+struct Bounded {
+    s: String,
+    width: usize,
+    room: usize,
+}
+
+impl Write for Bounded {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.width += s.chars().count();
+        if self.width > self.room {
+            return Err(fmt::Error);
+        }
+        self.s.push_str(s);
+        Ok(())
+    }
+}
+
+// `v` on one line with a space after each ',' and ':', failing on a string
+// with a line break.
+fn write_inline<W: Write>(w: &mut W, v: &Value, escapes: Escapes) -> fmt::Result {
+    let multiline = |s: &str| s.contains(['\n', '\r']);
+    match v {
+        Value::String(s) if multiline(s) => Err(fmt::Error),
+        Value::Array(arr) => {
+            w.write_char('[')?;
+            for (i, x) in arr.iter().enumerate() {
+                if i > 0 {
+                    w.write_str(", ")?;
+                }
+                write_inline(w, x, escapes)?;
+            }
+            w.write_char(']')
+        }
+        Value::Object(m) => {
+            w.write_char('{')?;
+            for (i, (k, x)) in m.entries().enumerate() {
+                if multiline(k) {
+                    return Err(fmt::Error);
+                }
+                if i > 0 {
+                    w.write_str(", ")?;
+                }
+                write_string_with(w, k, escapes)?;
+                w.write_str(": ")?;
+                write_inline(w, x, escapes)?;
+            }
+            w.write_char('}')
+        }
+        v => Serializer {
+            escapes,
+            ..Serializer::compact()
+        }
+        .node(w, v),
+    }
+}
+
+pub(crate) fn write_number<W: Write>(w: &mut W, f: f64) -> fmt::Result {
+    if f.is_nan() {
+        w.write_str("NaN")
+    } else if f.is_infinite() {
+        w.write_str(if f > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if f == 0.0 {
+        w.write_char('0')
+    } else {
+        write_js_number(w, f)
+    }
+}
+
+// A finite, non-zero `f` as JavaScript's `Number.prototype.toString` writes
+// it: the shortest digits that round-trip, in plain notation for exponents
+// from -7 to 20 and as `1.5e+21` or `1e-7` outside.
+fn write_js_number<W: Write>(w: &mut W, f: f64) -> fmt::Result {
+    // `{:e}` gives the same shortest digits as `{}`, as `d.ddde[-]x`
+    let exp = format!("{:e}", f.abs());
+    let (mantissa, e) = exp.split_once('e').expect("`{:e}` has an exponent");
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // the decimal point goes after `n` digits
+    let n = e.parse::<i32>().expect("`{:e}` exponent is an integer") + 1;
+
+    if f < 0.0 {
+        w.write_char('-')?;
+    }
+    if k <= n && n <= 21 {
+        w.write_str(&digits)?;
+        (k..n).try_for_each(|_| w.write_char('0'))
+    } else if 0 < n && n <= 21 {
+        let (int, frac) = digits.split_at(n as usize);
+        write!(w, "{}.{}", int, frac)
+    } else if -6 < n && n <= 0 {
+        w.write_str("0.")?;
+        (n..0).try_for_each(|_| w.write_char('0'))?;
+        w.write_str(&digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        w.write_str(first)?;
+        if !rest.is_empty() {
+            write!(w, ".{}", rest)?;
+        }
+        let sign = if n > 0 { "+" } else { "-" };
+        write!(w, "e{}{}", sign, (n - 1).abs())
+    }
+}
+
+// How strings and numbers are written beyond what JSON requires, from
+// `WriteOptions`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Escapes {
+    solidus: SolidusMode,
+    line_separators: bool,
+    ascii_only: bool,
+    // `WriteOptions::numbers_as_strings`, not applied to keys
+    bare_numbers: bool,
+    non_finite: NonFinite,
+}
+
+// Whether `s` is exactly one RFC 8259 number, checked by the parser so that
+// nothing else is written unquoted.
+fn is_number_literal(s: &str) -> bool {
+    let mut r = Reader::with_options(s, ParseOptions::new().numbers_as_strings(true));
+    r.parse_number().is_ok() && r.context.is_empty()
+}
+
+pub(crate) fn write_string<W: Write>(w: &mut W, s: &str) -> fmt::Result {
+    write_string_with(w, s, Escapes::default())
+}
+
+// Every string and key goes through here, whatever the layout. Control
+// characters never go out raw: the short escape where JSON has one, else
+// `\u00XX`.
+fn write_string_with<W: Write>(w: &mut W, s: &str, escapes: Escapes) -> fmt::Result {
+    w.write_char('"')?;
+    let mut prev = '"';
+    for ch in s.chars() {
+        match ch {
+            '/' if escapes.solidus == SolidusMode::Always
+                || (escapes.solidus == SolidusMode::AfterLt && prev == '<') =>
+            {
+                w.write_str("\\/")?
+            }
+            '\u{2028}' | '\u{2029}' if escapes.line_separators => {
+                write!(w, "\\u{:04x}", ch as u32)?
+            }
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\x08' => w.write_str("\\b")?,
+            '\x0C' => w.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(w, "\\u{:04x}", c as u32)?,
+            c if escapes.ascii_only && c >= '\x7f' => {
+                let mut units = [0; 2];
+                for u in c.encode_utf16(&mut units) {
+                    write!(w, "\\u{:04x}", u)?;
+                }
+            }
+            c => w.write_char(c)?,
+        }
+        prev = ch;
+    }
+    w.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, Reader};
+
+    #[test]
+    fn test_display() {
+        assert_eq!("null", Value::Null.to_string());
+        assert_eq!(
+            "[true,false]",
+            Value::Array(vec![Value::Bool(true), Value::Bool(false)]).to_string()
+        );
+        assert_eq!("1.5", Value::Number(1.5).to_string());
+        assert_eq!("-12", Value::Number(-12.0).to_string());
+
+        let s = Value::String(String::from("a\"\\\n\t\x01/"));
+        assert_eq!("\"a\\\"\\\\\\n\\t\\u0001/\"", s.to_string());
+
+        let src = "{\"k\":[1,\"x\",{\"n\":null}]}";
+        let v = Reader::new(src).parse().unwrap();
+        assert_eq!(src, v.to_string());
+        assert_eq!(v, Reader::new(&v.to_string()).parse().unwrap());
+    }
+
+    #[test]
+    fn test_formatters() {
+        let v = Reader::new("{\"a\": [1, {}]}").parse().unwrap();
+        let formats: Vec<Box<dyn Formatter>> = vec![Box::new(CompactJson), Box::new(PrettyJson)];
+        let out: Vec<String> = formats.iter().map(|f| v.format(f.as_ref())).collect();
+        assert_eq!(
+            vec!["{\"a\":[1,{}]}", "{\n  \"a\": [\n    1,\n    {}\n  ]\n}"],
+            out
+        );
+        assert_eq!(v.to_string(), out[0]);
+        assert_eq!(v.to_string_pretty(), out[1]);
+
+        let mut s = String::new();
+        PrettyJson
+            .write_value(&mut s, &Value::Array(Vec::new()))
+            .unwrap();
+        assert_eq!("[]", s);
+    }
+
+    #[test]
+    fn test_write_options() {
+        let v = Reader::new("{\"a\": [1, {}]}").parse().unwrap();
+        let lf = "{\n  \"a\": [\n    1,\n    {}\n  ]\n}";
+        let crlf = lf.replace('\n', "\r\n");
+        let cases = [
+            (Newline::Lf, false, lf.to_string()),
+            (Newline::Lf, true, format!("{}\n", lf)),
+            (Newline::CrLf, false, crlf.clone()),
+            (Newline::CrLf, true, format!("{}\r\n", crlf)),
+        ];
+        for (newline, last, expect) in cases.iter() {
+            let opts = WriteOptions::new().newline(*newline).final_newline(*last);
+            let s = v.to_string_pretty_with(&opts);
+            assert_eq!(expect.as_bytes(), s.as_bytes());
+            let mut out = Vec::new();
+            v.write_pretty_to_with(&mut out, &opts).unwrap();
+            assert_eq!(expect.as_bytes(), &out[..]);
+            assert_eq!(v, Reader::new(&s).parse().unwrap());
+        }
+        assert_eq!(
+            v.to_string_pretty(),
+            v.to_string_pretty_with(&WriteOptions::new())
+        );
+        assert_eq!(
+            "1\r\n",
+            Value::Number(1.0).to_string_pretty_with(&WriteOptions {
+                newline: Newline::CrLf,
+                final_newline: true,
+                ..WriteOptions::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_max_inline_width() {
+        let src = "{\"short\": [1, 2, 3], \"nested\": {\"p\": [1, [2, 3]], \"q\": {}}}";
+        let opts = ParseOptions::new().document_mode(true);
+        let v = Reader::with_options(src, opts).parse().unwrap();
+        let pretty =
+            |width: usize| v.to_string_pretty_with(&WriteOptions::new().max_inline_width(width));
+        assert_eq!(v.to_string_pretty(), pretty(0));
+
+        // everything fits: the root line is 59 columns
+        assert_eq!(src, pretty(59));
+        assert_eq!(src, pretty(100));
+
+        // `  "nested": {...}` is 39 columns, `  "short": [...],` and
+        // `    "p": [...],` 21 with the separator
+        let split_root =
+            "{\n  \"short\": [1, 2, 3],\n  \"nested\": {\"p\": [1, [2, 3]], \"q\": {}}\n}";
+        assert_eq!(split_root, pretty(58));
+        assert_eq!(split_root, pretty(39));
+        let split_nested = "{\n  \"short\": [1, 2, 3],\n  \"nested\": {\n    \"p\": [1, [2, 3]],\n    \"q\": {}\n  }\n}";
+        assert_eq!(split_nested, pretty(38));
+        assert_eq!(split_nested, pretty(21));
+        assert!(pretty(21).lines().all(|l| l.len() <= 21));
+        let s = pretty(20);
+        assert!(s.contains("\"short\": [\n    1,"), "{}", s);
+        assert!(
+            s.contains("\"p\": [\n      1,\n      [2, 3]\n    ]"),
+            "{}",
+            s
+        );
+        for width in 0..80 {
+            assert_eq!(v, Reader::new(&pretty(width)).parse().unwrap());
+        }
+
+        let v = Reader::new("[[\"a\\nb\"], [\"c\"], {\"d\\re\": 1}]")
+            .parse()
+            .unwrap();
+        assert_eq!(
+            "[\n  [\n    \"a\\nb\"\n  ],\n  [\"c\"],\n  {\n    \"d\\re\": 1\n  }\n]",
+            v.to_string_pretty_with(&WriteOptions::new().max_inline_width(80))
+        );
+        let scalar = Value::String(String::from("x\ny"));
+        assert_eq!(
+            "\"x\\ny\"",
+            scalar.to_string_pretty_with(&WriteOptions::new().max_inline_width(80))
+        );
+    }
+
+    #[test]
+    fn test_escape_options() {
+        let text = "</script><!-- a/b <\u{2028}/ \u{2029}";
+        let mut m = crate::Map::new();
+        m.insert(String::from("</k"), Value::String(String::from(text)));
+        let v = Value::Array(vec![Value::String(String::from(text)), Value::Object(m)]);
+
+        let with = |solidus, separators| {
+            let opts = WriteOptions::new()
+                .escape_solidus(solidus)
+                .escape_js_line_separators(separators);
+            [opts, opts.max_inline_width(200)]
+        };
+        for solidus in [
+            SolidusMode::Never,
+            SolidusMode::AfterLt,
+            SolidusMode::Always,
+        ]
+        .iter()
+        {
+            for separators in [false, true].iter() {
+                for opts in with(*solidus, *separators).iter() {
+                    let mut written = Vec::new();
+                    v.write_to_with(&mut written, opts).unwrap();
+                    let written = String::from_utf8(written).unwrap();
+                    let compact = v.to_string_with(opts);
+                    assert_eq!(compact, written);
+                    for s in [v.to_string_pretty_with(opts), compact].iter() {
+                        assert_eq!(v, Reader::new(s).parse().unwrap(), "{}", s);
+                        assert_eq!(*solidus == SolidusMode::Never, s.contains("</"), "{}", s);
+                        assert_eq!(
+                            *solidus == SolidusMode::Always,
+                            s.contains("a\\/b"),
+                            "{}",
+                            s
+                        );
+                        assert_eq!(!*separators, s.contains('\u{2028}'), "{}", s);
+                        assert_eq!(*separators, s.contains("\\u2029"), "{}", s);
+                    }
+                }
+            }
+        }
+
+        let opts = WriteOptions::new().escape_solidus(SolidusMode::AfterLt);
+        let s = v.to_string_pretty_with(&opts);
+        assert!(!s.contains("</script>"));
+        assert!(
+            s.contains("<\\/script>") && s.contains("\"<\\/k\""),
+            "{}",
+            s
+        );
+        // `/` not after `<` is left alone
+        assert!(s.contains("a/b") && s.contains("<\u{2028}/"), "{}", s);
+        assert_eq!(v.to_string(), v.format(&CompactJson));
+        assert_eq!(v.to_string(), v.to_string_with(&WriteOptions::new()));
+        let s = v.to_string_with(&opts.escape_js_line_separators(true));
+        assert!(s.contains("<\\/script>") && s.contains("\\u2028"), "{}", s);
+        assert!(!s.contains('\n'), "{}", s);
+    }
+
+    #[test]
+    fn test_control_escapes() {
+        let escaped = |s: &str, escapes| {
+            let mut out = String::new();
+            write_string_with(&mut out, s, escapes).unwrap();
+            out
+        };
+        for c in 0..0x20u8 {
+            let expect = match c {
+                0x08 => String::from("\\b"),
+                0x09 => String::from("\\t"),
+                0x0A => String::from("\\n"),
+                0x0C => String::from("\\f"),
+                0x0D => String::from("\\r"),
+                c => format!("\\u{:04x}", c),
+            };
+            let s = char::from(c).to_string();
+            assert_eq!(format!("\"{}\"", expect), escaped(&s, Escapes::default()));
+        }
+        assert_eq!("\"\\\"\\\\\"", escaped("\"\\", Escapes::default()));
+        assert_eq!("\"\x7f\"", escaped("\x7f", Escapes::default()));
+
+        let ascii = Escapes {
+            ascii_only: true,
+            ..Escapes::default()
+        };
+        assert_eq!("\"\\u007f\"", escaped("\x7f", ascii));
+        assert_eq!("\"~\\u00e9\\ud83d\\ude00\\n\"", escaped("~é😀\n", ascii));
+        let v = Value::String(String::from("é\x7f"));
+        let opts = WriteOptions::new().ascii_only(true);
+        assert_eq!("\"\\u00e9\\u007f\"", v.to_string_with(&opts));
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        let s: String = (0..=255u8).map(char::from).collect();
+        let mut m = crate::Map::new();
+        m.insert(s.clone(), Value::String(s.clone()));
+        let v = Value::Array(vec![Value::String(s), Value::Object(m)]);
+
+        let mut written = Vec::new();
+        v.write_to(&mut written).unwrap();
+        let ascii_opts = WriteOptions::new().ascii_only(true);
+        let ascii = v.to_string_pretty_with(&ascii_opts);
+        let compact_ascii = v.to_string_with(&ascii_opts);
+        for s in [&ascii, &compact_ascii].iter() {
+            assert!(s.is_ascii() && !s.contains('\x7f'), "{:?}", s);
+        }
+        assert!(!compact_ascii.contains('\n'));
+        let outputs = [
+            v.to_string(),
+            v.to_string_pretty(),
+            v.to_string_pretty_with(&WriteOptions::new().max_inline_width(2000)),
+            ascii,
+            compact_ascii,
+            String::from_utf8(written).unwrap(),
+        ];
+        for out in outputs.iter() {
+            assert_eq!(v, Reader::new(out).parse().unwrap());
+            // only the layout's own line breaks
+            let raw = out.chars().filter(|&c| c < ' ' && c != '\n');
+            assert_eq!(0, raw.count(), "{:?}", out);
+        }
+    }
+
+    #[test]
+    fn test_numbers_as_strings() {
+        let src = "{
+  \"amount\": 123456789012345678901234567890.12,
+  \"rate\": -0.000000000000000000000000000001,
+  \"big\": 1.5E+400,
+  \"list\": [
+    0,
+    1e-7,
+    2.50
+  ]
+}";
+        let opts = ParseOptions::new()
+            .numbers_as_strings(true)
+            .document_mode(true);
+        let v = Reader::with_options(src, opts).parse().unwrap();
+        assert_eq!(Some("2.50"), v.pointer("/list/2").and_then(Value::as_str));
+        assert_eq!(Some("1.5E+400"), v.get("big").and_then(Value::as_str));
+
+        let out = WriteOptions::new().numbers_as_strings(true);
+        assert_eq!(src, v.to_string_pretty_with(&out));
+        let inline = out.max_inline_width(80);
+        assert!(v.to_string_pretty_with(&inline).contains("[0, 1e-7, 2.50]"));
+
+        let src = "[5,123456789012345678901234567890,{\"n\":-1.0e-30}]";
+        let v = Reader::with_options(src, ParseOptions::new().numbers_as_strings(true))
+            .parse()
+            .unwrap();
+        assert_eq!(src, v.to_string_with(&out));
+
+        // only a whole number literal goes out unquoted
+        for s in [
+            "1, \"x\": 2",
+            "1 ",
+            " 1",
+            "01",
+            "+1",
+            "1.",
+            "",
+            "NaN",
+            "0x10",
+            "-",
+        ]
+        .iter()
+        {
+            let v = Value::Array(vec![Value::String(s.to_string())]);
+            let quoted = v.to_string_pretty_with(&WriteOptions::new());
+            assert_eq!(quoted, v.to_string_pretty_with(&out), "{:?}", s);
+        }
+        let mut m = crate::Map::new();
+        m.insert("1".to_string(), Value::String("-2e3".to_string()));
+        let v = Value::Object(m);
+        assert_eq!("{\n  \"1\": -2e3\n}", v.to_string_pretty_with(&out));
+
+        let opts = ParseOptions::new()
+            .numbers_as_strings(true)
+            .allow_plus_sign(true)
+            .allow_hex_numbers(true);
+        let v = Reader::with_options("[+5, 0x1F]", opts).parse().unwrap();
+        assert_eq!("[\"5\",31]", v.to_string());
+    }
+
+    #[test]
+    fn test_js_numbers() {
+        let js = |f: f64| Value::Number(f).to_string();
+        assert_eq!("1", js(1.0));
+        assert_eq!("100000000000000000000", js(100000000000000000000.0));
+        assert_eq!("1e+21", js(1e21));
+        assert_eq!("0.0001", js(0.0001));
+        assert_eq!("0.000001", js(0.000001));
+        assert_eq!("1e-7", js(1e-7));
+        assert_eq!("-1.5e-7", js(-1.5e-7));
+        assert_eq!("1.2345678901234568e+25", js(12345678901234567890123456.0));
+        assert_eq!("123.456", js(123.456));
+        assert_eq!("-42", js(-42.0));
+        assert_eq!("0.1", js(0.1));
+        assert_eq!("5e-324", js(5e-324));
+        assert_eq!("1.7976931348623157e+308", js(f64::MAX));
+    }
+
+    #[test]
+    fn test_write_to() {
+        let src = "{\"name\":\"zxh\",\"option\":[true,false,3.5,{}],\"open\":null}";
+        let v = Reader::new(src).parse().unwrap();
+
+        let mut buf: Vec<u8> = Vec::new();
+        v.write_to(&mut buf).unwrap();
+        assert_eq!(v.to_string().as_bytes(), &buf[..]);
+        let back = Reader::new(std::str::from_utf8(&buf).unwrap()).parse();
+        assert_eq!(v, back.unwrap());
+
+        let mut pretty: Vec<u8> = Vec::new();
+        v.write_pretty_to(&mut pretty).unwrap();
+        assert_eq!(v.to_string_pretty().as_bytes(), &pretty[..]);
+        let back = Reader::new(std::str::from_utf8(&pretty).unwrap()).parse();
+        assert_eq!(v, back.unwrap());
+    }
+
+    #[test]
+    fn test_pretty() {
+        let v = Reader::new("[1,{\"a\":[]},[null]]").parse().unwrap();
+        assert_eq!(
+            "[\n  1,\n  {\n    \"a\": []\n  },\n  [\n    null\n  ]\n]",
+            v.to_string_pretty()
+        );
+    }
+
+    #[test]
+    fn test_write_to_error() {
+        struct Full;
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::WriteZero, "full"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let e = Value::Null.write_to(&mut Full).unwrap_err();
+        assert_eq!(io::ErrorKind::WriteZero, e.kind());
+    }
+
+    #[test]
+    fn test_non_finite() {
+        let v = Value::Array(vec![
+            Value::Number(1.0),
+            Value::Number(f64::INFINITY),
+            Value::Number(f64::NEG_INFINITY),
+            Value::Number(f64::NAN),
+        ]);
+        let mut buf: Vec<u8> = Vec::new();
+        let e = v.write_to(&mut buf).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+        let e = v.write_pretty_to(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+        assert_eq!("[1,null,null,null]", v.to_string());
+        assert_eq!("[\n  1,\n  null,\n  null,\n  null\n]", v.to_string_pretty());
+
+        let null = WriteOptions::new().non_finite(NonFinite::Null);
+        let mut buf: Vec<u8> = Vec::new();
+        v.write_to_with(&mut buf, &null).unwrap();
+        assert_eq!(b"[1,null,null,null]", &buf[..]);
+
+        let literal = WriteOptions::new()
+            .non_finite(NonFinite::Literal)
+            .max_inline_width(80);
+        let mut buf: Vec<u8> = Vec::new();
+        v.write_to_with(&mut buf, &literal).unwrap();
+        assert_eq!(b"[1,Infinity,-Infinity,NaN]", &buf[..]);
+        assert_eq!(
+            "[1, Infinity, -Infinity, NaN]",
+            v.to_string_pretty_with(&literal)
+        );
+        let lenient = ParseOptions::new().allow_nan(true);
+        let back = Reader::with_options(&v.to_string_with(&literal), lenient).parse();
+        assert_eq!(v, back.unwrap());
+    }
+}
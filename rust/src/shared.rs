@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::{pointer_index, same_number, Map, Value};
+
+/// A frozen value from `Value::into_shared`, for reading one document from
+/// many threads. Every string, array and object is behind an `Arc`, so a
+/// clone of the whole or of any part is a reference count increment.
+///
+/// Objects hold the members `get` sees on the `Value`, sorted by key; a
+/// duplicate shadowed by a later occurrence is gone. Numbers compare as on
+/// `Value`.
+#[must_use]
+#[derive(Debug, Clone)]
+pub enum SharedValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Arc<str>),
+    Array(Arc<[SharedValue]>),
+    Object(Arc<[(Arc<str>, SharedValue)]>),
+}
+
+impl Value {
+    /// Freeze into a `SharedValue`, see there. Mutate a `to_value` copy of
+    /// it when needed.
+    pub fn into_shared(self) -> SharedValue {
+        match self {
+            Value::Null => SharedValue::Null,
+            Value::Bool(b) => SharedValue::Bool(b),
+            Value::Number(v) => SharedValue::Number(v),
+            v @ Value::String(_) => SharedValue::String(v.into_string().unwrap_or_default().into()),
+            v @ Value::Array(_) => {
+                let arr = v.into_array().unwrap_or_default();
+                SharedValue::Array(arr.into_iter().map(Value::into_shared).collect())
+            }
+            v @ Value::Object(_) => {
+                // collected in entries order, so the last duplicate wins
+                let members: BTreeMap<String, Value> =
+                    v.into_object().unwrap_or_default().into_iter().collect();
+                SharedValue::Object(
+                    members
+                        .into_iter()
+                        .map(|(k, x)| (k.into(), x.into_shared()))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+impl PartialEq for SharedValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SharedValue::Null, SharedValue::Null) => true,
+            (SharedValue::Bool(a), SharedValue::Bool(b)) => a == b,
+            (SharedValue::Number(a), SharedValue::Number(b)) => same_number(*a, *b),
+            (SharedValue::String(a), SharedValue::String(b)) => a == b,
+            (SharedValue::Array(a), SharedValue::Array(b)) => a == b,
+            (SharedValue::Object(a), SharedValue::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for SharedValue {}
+
+impl SharedValue {
+    /// Member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&SharedValue> {
+        let members = self.as_object()?;
+        let i = members.binary_search_by(|(k, _)| (**k).cmp(key)).ok()?;
+        Some(&members[i].1)
+    }
+
+    /// Look up a value by JSON Pointer, as `Value::pointer`.
+    pub fn pointer(&self, pointer: &str) -> Option<&SharedValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        if !pointer.starts_with('/') {
+            return None;
+        }
+
+        let mut target = self;
+        for token in pointer[1..].split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            target = match target {
+                SharedValue::Object(_) => target.get(&token)?,
+                SharedValue::Array(v) => v.get(pointer_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SharedValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            SharedValue::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SharedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[SharedValue]> {
+        match self {
+            SharedValue::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The members of an object, sorted by key.
+    pub fn as_object(&self) -> Option<&[(Arc<str>, SharedValue)]> {
+        match self {
+            SharedValue::Object(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Copy into an owned `Value`, to change it.
+    pub fn to_value(&self) -> Value {
+        match self {
+            SharedValue::Null => Value::Null,
+            SharedValue::Bool(b) => Value::Bool(*b),
+            SharedValue::Number(v) => Value::Number(*v),
+            SharedValue::String(v) => Value::String(v.to_string()),
+            SharedValue::Array(v) => Value::Array(v.iter().map(SharedValue::to_value).collect()),
+            SharedValue::Object(v) => {
+                let mut m = Map::with_capacity(v.len());
+                for (k, x) in v.iter() {
+                    m.insert(k.to_string(), x.to_value());
+                }
+                Value::Object(m)
+            }
+        }
+    }
+}
+
+impl From<Value> for SharedValue {
+    fn from(v: Value) -> SharedValue {
+        v.into_shared()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{ParseOptions, Reader};
+
+    #[test]
+    fn test_shared_value() {
+        let src =
+            "{\"name\":\"zxh\",\"ports\":[80,443],\"a/b\":{\"~\":[null,true]},\"n\":1,\"n\":2}";
+        let opts = ParseOptions::new().document_mode(true);
+        let v = Reader::with_options(src, opts).parse().unwrap();
+        let shared = v.clone().into_shared();
+
+        assert_eq!(
+            Some("zxh"),
+            shared.get("name").and_then(SharedValue::as_str)
+        );
+        assert_eq!(Some(2.0), shared.get("n").and_then(SharedValue::as_f64));
+        assert_eq!(
+            Some(true),
+            shared.pointer("/a~1b/~0/1").and_then(SharedValue::as_bool)
+        );
+        assert_eq!(
+            2,
+            shared
+                .pointer("/ports")
+                .and_then(SharedValue::as_array)
+                .unwrap()
+                .len()
+        );
+        assert!(shared.pointer("/ports/2").is_none());
+        let keys: Vec<&str> = shared
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(k, _)| &**k)
+            .collect();
+        assert_eq!(vec!["a/b", "n", "name", "ports"], keys);
+        assert_eq!(v, shared.to_value());
+
+        // a clone shares the tree
+        let ports = shared.get("ports").unwrap().clone();
+        match (&ports, shared.get("ports")) {
+            (SharedValue::Array(a), Some(SharedValue::Array(b))) => assert!(Arc::ptr_eq(a, b)),
+            x => panic!("{:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_shared_value_eq_numbers() {
+        let opts = ParseOptions::new().allow_nan(true);
+        let v = Reader::with_options("[NaN, -0.0, {\"a\": NaN}]", opts)
+            .parse()
+            .unwrap();
+        assert_eq!(v.clone().into_shared(), v.clone().into_shared());
+        let zero = Reader::new("[0, 0.0, {\"a\": 1}]").parse().unwrap();
+        assert_ne!(v.into_shared(), zero.into_shared());
+        assert_eq!(SharedValue::Number(-0.0), Value::Number(0.0).into_shared());
+        assert_ne!(SharedValue::Number(0.0), SharedValue::Bool(false));
+    }
+
+    #[test]
+    fn test_shared_value_threads() {
+        let items: Vec<String> = (0..1000)
+            .map(|i| format!("{{\"id\":{},\"tags\":[\"t{}\"]}}", i, i))
+            .collect();
+        let src = format!("{{\"items\":[{}]}}", items.join(","));
+        let doc = Reader::new(&src).parse().unwrap().into_shared();
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|t| {
+                    let doc = doc.clone();
+                    scope.spawn(move || {
+                        for i in (t..1000).step_by(8) {
+                            let id = doc.pointer(&format!("/items/{}/id", i));
+                            assert_eq!(Some(i as f64), id.and_then(SharedValue::as_f64));
+                            let tag = doc.pointer(&format!("/items/{}/tags/0", i));
+                            assert_eq!(
+                                Some(format!("t{}", i).as_str()),
+                                tag.and_then(SharedValue::as_str)
+                            );
+                        }
+                    })
+                })
+                .collect();
+            for h in handles {
+                h.join().unwrap();
+            }
+        });
+    }
+}
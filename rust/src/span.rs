@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+/// Where a node sits in the source: byte offsets `start..end` and the
+/// 1-based line and column of `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Spans from `Reader::parse_with_spans`, keyed by JSON Pointer (`""` for
+/// the root). Member keys have their own table under the member's pointer.
+#[derive(Debug, Clone, Default)]
+pub struct SpanMap {
+    pub(crate) values: HashMap<String, Span>,
+    pub(crate) keys: HashMap<String, Span>,
+}
+
+impl SpanMap {
+    /// Span of the value at `pointer`.
+    pub fn get(&self, pointer: &str) -> Option<&Span> {
+        self.values.get(pointer)
+    }
+
+    /// Span of the quoted key of the member at `pointer`.
+    pub fn key(&self, pointer: &str) -> Option<&Span> {
+        self.keys.get(pointer)
+    }
+
+    /// Number of value spans.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Reader, Value};
+
+    const SRC: &str = "{\n  \"servers\": [\n    {\"host\": \"a\", \"port\": 80},\n    {\"host\": \"日本\", \"port\": 8080}\n  ],\n  \"debug\": true\n}";
+
+    #[test]
+    fn test_parse_with_spans() {
+        let (v, spans) = Reader::new(SRC).parse_with_spans().unwrap();
+
+        let mut count = 0;
+        v.walk(&mut |_, _| count += 1);
+        assert_eq!(count, spans.len());
+
+        // every span slices out text that parses back to the same node
+        for (pointer, span) in &spans.values {
+            let node = v.pointer(pointer).unwrap();
+            let text = &SRC[span.start..span.end];
+            assert_eq!(node, &Reader::new(text).parse().unwrap(), "{}", pointer);
+        }
+
+        let port = spans.get("/servers/1/port").unwrap();
+        assert_eq!("8080", &SRC[port.start..port.end]);
+        assert_eq!((4, 28), (port.line, port.col));
+        assert_eq!((1, 1), {
+            let root = spans.get("").unwrap();
+            (root.line, root.col)
+        });
+    }
+
+    #[test]
+    fn test_key_spans() {
+        let (_, spans) = Reader::new(SRC).parse_with_spans().unwrap();
+
+        for (pointer, span) in &spans.keys {
+            let key = &SRC[span.start..span.end];
+            let name = pointer.rsplit('/').next().unwrap();
+            let parsed = Reader::new(key).parse().unwrap();
+            assert_eq!(parsed, Value::String(name.to_string()));
+        }
+
+        let debug = spans.key("/debug").unwrap();
+        assert_eq!((6, 3), (debug.line, debug.col));
+        assert_eq!("\"debug\"", &SRC[debug.start..debug.end]);
+        assert_eq!(None, spans.key("/servers/0"));
+        assert_eq!(6, spans.keys.len());
+    }
+}
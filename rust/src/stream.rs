@@ -0,0 +1,152 @@
+use crate::{ParseError, Reader, TokenKind, Value};
+
+/// The elements of a top-level array, parsed one at a time as the iterator
+/// is advanced, so only the current element is in memory as a `Value`.
+///
+/// Iteration stops at the closing `]` or after the first error, which is
+/// yielded. Call `finish` to also check what follows the array.
+pub struct ArrayStream<'a> {
+    r: Reader<'a>,
+    state: State,
+}
+
+enum State {
+    // before the first element
+    Start,
+    // after an element, expecting ',' or ']'
+    Next,
+    Closed,
+    Failed(ParseError),
+}
+
+impl<'a> ArrayStream<'a> {
+    /// Start at the `[` opening `input`, failing if there is none.
+    pub fn new(input: &'a str) -> Result<ArrayStream<'a>, ParseError> {
+        let mut r = Reader::new(input);
+        r.parse_whitespace()?;
+        if r.peek_token() != Some(TokenKind::BeginArray) {
+            return Err(r.error("array stream expect '['"));
+        }
+        r.bump();
+        // the elements are nested in the array
+        r.depth = 1;
+        r.parse_whitespace()?;
+        let state = if r.peek_token() == Some(TokenKind::EndArray) {
+            r.bump();
+            State::Closed
+        } else {
+            State::Start
+        };
+        Ok(ArrayStream { r, state })
+    }
+
+    /// Parse the elements not iterated yet, then check that only
+    /// whitespace follows the closing `]`. Returns the error iteration
+    /// stopped at, if any.
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        for x in &mut self {
+            let _ = x?;
+        }
+        if let State::Failed(e) = self.state {
+            return Err(e);
+        }
+        self.r.parse_whitespace()?;
+        self.r.expect_end()
+    }
+
+    fn fail(&mut self, e: ParseError) -> Option<Result<Value, ParseError>> {
+        self.state = State::Failed(e.clone());
+        Some(Err(e))
+    }
+}
+
+impl Iterator for ArrayStream<'_> {
+    type Item = Result<Value, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Value, ParseError>> {
+        match self.state {
+            State::Start => {}
+            State::Next => match self.r.peek_token() {
+                Some(TokenKind::Comma) => {
+                    self.r.bump();
+                }
+                Some(TokenKind::EndArray) => {
+                    self.r.bump();
+                    self.state = State::Closed;
+                    return None;
+                }
+                None => {
+                    let e = self.r.error("array end char expect ']'");
+                    return self.fail(e);
+                }
+                Some(_) => {
+                    let e = self.r.error("array elements expect ','");
+                    return self.fail(e);
+                }
+            },
+            State::Closed | State::Failed(_) => return None,
+        }
+
+        match self.r.parse_tree() {
+            Ok(v) => {
+                self.state = State::Next;
+                Some(Ok(v))
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(n: usize) -> String {
+        let items: Vec<String> = (0..n).map(|i| i.to_string()).collect();
+        format!(" [\n{}\n] ", items.join(",\n"))
+    }
+
+    #[test]
+    fn test_array_stream() {
+        let src = numbers(1_000_000);
+        let mut sum = 0.0;
+        let mut stream = ArrayStream::new(&src).unwrap();
+        for x in &mut stream {
+            match x.unwrap() {
+                Value::Number(v) => sum += v,
+                v => panic!("not a number {:?}", v),
+            }
+        }
+        assert_eq!(499_999_500_000.0, sum);
+        assert!(stream.finish().is_ok());
+
+        let stream = ArrayStream::new(" [ ] ").unwrap();
+        assert!(stream.finish().is_ok());
+        let v: Vec<_> = ArrayStream::new("[{\"a\":[1]}, \"s\"]").unwrap().collect();
+        assert_eq!(2, v.len());
+    }
+
+    #[test]
+    fn test_array_stream_error() {
+        // element i is on row i + 2
+        let src = numbers(1_000_000).replacen("\n500000,", "\nx,", 1);
+        let mut stream = ArrayStream::new(&src).unwrap();
+        let n = stream.by_ref().take_while(Result::is_ok).count();
+        assert_eq!(500_000, n);
+        assert!(stream.next().is_none());
+        let e = stream.finish().unwrap_err();
+        assert_eq!((500_002, 1), (e.row, e.column));
+        assert_eq!("unexpected character 'x'", e.desc);
+
+        let e = ArrayStream::new("{}").err().unwrap();
+        assert_eq!("array stream expect '['", e.desc);
+        let e = ArrayStream::new("[1] x").unwrap().finish().unwrap_err();
+        assert_eq!("value not finished 'x'", e.desc);
+        let e = ArrayStream::new("[1,]").unwrap().finish().unwrap_err();
+        assert_eq!("unexpected ']' — expected a value", e.desc);
+        let e = ArrayStream::new("[1").unwrap().finish().unwrap_err();
+        assert_eq!("array end char expect ']'", e.desc);
+        let e = ArrayStream::new("[1 2]").unwrap().finish().unwrap_err();
+        assert_eq!("array elements expect ','", e.desc);
+    }
+}
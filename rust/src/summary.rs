@@ -0,0 +1,204 @@
+use std::fmt::Write;
+
+use crate::ser::{write_number, write_string};
+use crate::Value;
+
+/// Limits for `Value::summary`.
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SummaryOptions {
+    /// Elements shown from the start of an array.
+    pub head: usize,
+    /// Elements shown from the end of an array.
+    pub tail: usize,
+    /// Members shown of an object.
+    pub members: usize,
+    /// Characters shown of a string or key.
+    pub string_len: usize,
+    /// Containers nested deeper than this are written as `[…]`/`{…}`.
+    pub depth: usize,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> SummaryOptions {
+        SummaryOptions {
+            head: 3,
+            tail: 1,
+            members: 8,
+            string_len: 40,
+            depth: 4,
+        }
+    }
+}
+
+impl SummaryOptions {
+    pub fn new() -> SummaryOptions {
+        SummaryOptions::default()
+    }
+
+    pub fn head(mut self, n: usize) -> SummaryOptions {
+        self.head = n;
+        self
+    }
+
+    pub fn tail(mut self, n: usize) -> SummaryOptions {
+        self.tail = n;
+        self
+    }
+
+    pub fn members(mut self, n: usize) -> SummaryOptions {
+        self.members = n;
+        self
+    }
+
+    pub fn string_len(mut self, n: usize) -> SummaryOptions {
+        self.string_len = n;
+        self
+    }
+
+    pub fn depth(mut self, n: usize) -> SummaryOptions {
+        self.depth = n;
+        self
+    }
+}
+
+impl Value {
+    /// One line overview for debugging, bounded by `opts` whatever the size
+    /// of the value. Skipped elements, members and characters are counted in
+    /// place; the result is not meant to be valid JSON.
+    pub fn summary(&self, opts: SummaryOptions) -> String {
+        let mut s = String::new();
+        summarize(&mut s, self, &opts, 0);
+        s
+    }
+}
+
+fn summarize(w: &mut String, v: &Value, opts: &SummaryOptions, depth: usize) {
+    match v {
+        Value::Array(v) => {
+            if v.is_empty() {
+                w.push_str("[]");
+            } else if depth >= opts.depth {
+                w.push_str("[…]");
+            } else if v.len() <= opts.head + opts.tail {
+                w.push('[');
+                for (i, x) in v.iter().enumerate() {
+                    if i > 0 {
+                        w.push_str(", ");
+                    }
+                    summarize(w, x, opts, depth + 1);
+                }
+                w.push(']');
+            } else {
+                w.push('[');
+                for x in &v[..opts.head] {
+                    summarize(w, x, opts, depth + 1);
+                    w.push_str(", ");
+                }
+                let _ = write!(w, "… {} more", v.len() - opts.head - opts.tail);
+                for x in &v[v.len() - opts.tail..] {
+                    w.push_str(", ");
+                    summarize(w, x, opts, depth + 1);
+                }
+                let _ = write!(w, "] ({} items)", v.len());
+            }
+        }
+        Value::Object(v) => {
+            if v.is_empty() {
+                w.push_str("{}");
+            } else if depth >= opts.depth {
+                w.push_str("{…}");
+            } else {
+                w.push('{');
+                for (i, (k, x)) in v.entries().take(opts.members).enumerate() {
+                    if i > 0 {
+                        w.push_str(", ");
+                    }
+                    summarize_str(w, k, opts);
+                    w.push_str(": ");
+                    summarize(w, x, opts, depth + 1);
+                }
+                if v.len() > opts.members {
+                    if opts.members > 0 {
+                        w.push_str(", ");
+                    }
+                    let _ = write!(w, "… {} more", v.len() - opts.members);
+                }
+                w.push('}');
+            }
+        }
+        Value::String(v) => summarize_str(w, v, opts),
+        Value::Number(v) => {
+            let _ = write_number(w, *v);
+        }
+        Value::Null => w.push_str("null"),
+        Value::Bool(b) => w.push_str(if *b { "true" } else { "false" }),
+    }
+}
+
+fn summarize_str(w: &mut String, s: &str, opts: &SummaryOptions) {
+    match s.char_indices().nth(opts.string_len) {
+        None => {
+            let _ = write_string(w, s);
+        }
+        Some((end, _)) => {
+            let _ = write_string(w, &s[..end]);
+            let _ = write!(w, "… ({} chars)", s.chars().count());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    fn numbers(n: usize) -> Value {
+        Value::Array((0..n).map(|i| Value::Number(i as f64)).collect())
+    }
+
+    #[test]
+    fn test_summary() {
+        let opts = SummaryOptions::new();
+        assert_eq!("[0, 1, 2]", numbers(3).summary(opts));
+        assert_eq!("[0, 1, 2, 3]", numbers(4).summary(opts));
+        assert_eq!(
+            "[0, 1, 2, … 49996 more, 49999] (50000 items)",
+            numbers(50000).summary(opts)
+        );
+
+        let long = "x".repeat(1000);
+        let v = Reader::new(&format!("{{\"s\":\"{}\",\"n\":[[[[[1]]]]]}}", long))
+            .parse()
+            .unwrap();
+        let s = v.summary(opts);
+        assert!(
+            s.contains(&format!("\"{}\"… (1000 chars)", "x".repeat(40))),
+            "{}",
+            s
+        );
+        // which member is kept depends on hash order
+        let s = v.summary(opts.members(1));
+        assert!(s.ends_with(", … 1 more}"), "{}", s);
+
+        let v = Reader::new("{\"n\":[[[[[1]]]]]}").parse().unwrap();
+        assert_eq!("{\"n\": [[[[…]]]]}", v.summary(opts));
+        assert_eq!("{…}", v.summary(opts.depth(0)));
+    }
+
+    #[test]
+    fn test_summary_bounded() {
+        let opts = SummaryOptions::new();
+        let mut lens = Vec::new();
+        for n in [10, 100, 1000].iter() {
+            let row = Value::Array(vec![numbers(*n), Value::String("y".repeat(*n))]);
+            let v = Value::Array((0..*n).map(|_| row.clone()).collect());
+            let s = v.summary(opts);
+            assert!(s.contains(&format!("({} items)", n)), "{}", s);
+            lens.push(s.len());
+        }
+        // only the digits of the counts grow
+        assert!(lens[2] - lens[1] < 20, "{:?}", lens);
+        assert!(lens[2] < 1000, "{:?}", lens);
+    }
+}
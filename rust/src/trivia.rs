@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+/// Comments and blank lines found before one element or member.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leading {
+    /// Source text of each comment, delimiters included, in order.
+    pub comments: Vec<String>,
+    /// Empty lines between the previous token and this node.
+    pub blank_lines: usize,
+}
+
+impl Leading {
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty() && self.blank_lines == 0
+    }
+}
+
+/// Side table from `Reader::parse_with_trivia`, mapping the JSON Pointer of
+/// an element or member (`""` for the root) to what preceded it.
+///
+/// A comment after a member on the same line is kept with the next member,
+/// comments just before a closing bracket are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Trivia {
+    leading: HashMap<String, Leading>,
+}
+
+impl Trivia {
+    pub fn get(&self, pointer: &str) -> Option<&Leading> {
+        self.leading.get(pointer)
+    }
+
+    pub fn len(&self) -> usize {
+        self.leading.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty()
+    }
+
+    pub(crate) fn attach(&mut self, pointer: String, mut l: Leading) {
+        let e = self.leading.entry(pointer).or_default();
+        e.comments.append(&mut l.comments);
+        e.blank_lines += l.blank_lines;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseOptions, Reader};
+
+    const CONFIG: &str = "// service config
+{
+  // display name
+  \"name\": \"zxh\",
+
+  /* listen on
+     both ports */
+  \"ports\": [
+    80,
+    // tls
+    443
+  ],
+  \"debug\": false
+}";
+
+    fn options() -> ParseOptions {
+        ParseOptions::new().allow_comments(true).document_mode(true)
+    }
+
+    #[test]
+    fn test_parse_with_trivia() {
+        let (v, trivia) = Reader::with_options(CONFIG, options())
+            .parse_with_trivia()
+            .unwrap();
+        assert_eq!(Some(&crate::Value::Bool(false)), v.get("debug"));
+
+        assert_eq!(vec!["// service config"], trivia.get("").unwrap().comments);
+        assert_eq!(
+            vec!["// display name"],
+            trivia.get("/name").unwrap().comments
+        );
+        let ports = trivia.get("/ports").unwrap();
+        assert_eq!(vec!["/* listen on\n     both ports */"], ports.comments);
+        assert_eq!(1, ports.blank_lines);
+        assert_eq!(vec!["// tls"], trivia.get("/ports/1").unwrap().comments);
+        assert_eq!(None, trivia.get("/ports/0"));
+        assert_eq!(None, trivia.get("/debug"));
+        assert_eq!(4, trivia.len());
+    }
+
+    #[test]
+    fn test_trivia_line_endings() {
+        for eol in ["\n", "\r\n", "\r"].iter() {
+            let src = CONFIG.replace('\n', eol);
+            let (_, trivia) = Reader::with_options(&src, options())
+                .parse_with_trivia()
+                .unwrap();
+            let ports = trivia.get("/ports").unwrap();
+            assert_eq!(1, ports.blank_lines, "{:?}", eol);
+            let tls = &trivia.get("/ports/1").unwrap().comments;
+            assert_eq!(vec!["// tls"], *tls, "{:?}", eol);
+        }
+    }
+
+    #[test]
+    fn test_trivia_round_trip() {
+        let (v, trivia) = Reader::with_options(CONFIG, options())
+            .parse_with_trivia()
+            .unwrap();
+        let out = v.to_string_pretty_with_trivia(&trivia);
+        assert_eq!(CONFIG, out);
+
+        let (v1, trivia1) = Reader::with_options(&out, options())
+            .parse_with_trivia()
+            .unwrap();
+        assert_eq!(v, v1);
+        assert_eq!(trivia.get("/ports"), trivia1.get("/ports"));
+    }
+
+    #[test]
+    fn test_comments_option() {
+        let src = "[1, /* two */ 2 // end\n]";
+        assert!(Reader::new(src).parse().is_err());
+        let v = Reader::with_options(src, ParseOptions::new().allow_comments(true)).parse();
+        assert_eq!("[1,2]", v.unwrap().to_string());
+
+        let (_, trivia) = Reader::with_options(src, ParseOptions::new().allow_comments(true))
+            .parse_with_trivia()
+            .unwrap();
+        assert_eq!(vec!["/* two */"], trivia.get("/1").unwrap().comments);
+        assert_eq!(1, trivia.len());
+
+        let e = Reader::with_options("[1 / 2]", ParseOptions::new().allow_comments(true))
+            .parse()
+            .unwrap_err();
+        assert_eq!((1, 4), (e.row, e.column));
+    }
+
+    #[test]
+    fn test_unclosed_comment() {
+        let opts = || ParseOptions::new().allow_comments(true);
+        let src = "[1, 2 /* the rest\n  3]";
+        let e = Reader::with_options(src, opts()).parse().unwrap_err();
+        assert_eq!("block comment not closed", e.desc);
+        assert_eq!((1, 7), (e.row, e.column));
+
+        let e = Reader::with_options(src, opts()).parse_ref().unwrap_err();
+        assert_eq!((1, 7), (e.row, e.column));
+        let e = Reader::with_options(src, opts())
+            .parse_with_trivia()
+            .unwrap_err();
+        assert_eq!((1, 7), (e.row, e.column));
+        let e = Reader::with_options("[] /*", opts()).parse().unwrap_err();
+        assert_eq!((1, 4), (e.row, e.column));
+
+        // a line comment may run to the end of input
+        let v = Reader::with_options("[] // end", opts()).parse();
+        assert_eq!("[]", v.unwrap().to_string());
+    }
+}
@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+
+use crate::{Map, Value};
+
+/// A parsed value borrowing from the input, from `Reader::parse_ref`.
+///
+/// Strings and keys without escapes are slices of the input, the others
+/// are unescaped copies. Objects keep every member in source order; `get`
+/// sees the last occurrence of a key.
+#[must_use]
+#[derive(Debug, Clone)]
+pub enum ValueRef<'a> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Cow<'a, str>),
+    Array(Vec<ValueRef<'a>>),
+    Object(Vec<(Cow<'a, str>, ValueRef<'a>)>),
+}
+
+impl<'a> ValueRef<'a> {
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            ValueRef::Null => "null",
+            ValueRef::Bool(_) => "boolean",
+            ValueRef::Number(_) => "number",
+            ValueRef::String(_) => "string",
+            ValueRef::Array(_) => "array",
+            ValueRef::Object(_) => "object",
+        }
+    }
+
+    /// Member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&ValueRef<'a>> {
+        match self {
+            ValueRef::Object(v) => v.iter().rev().find(|(k, _)| k == key).map(|(_, x)| x),
+            _ => None,
+        }
+    }
+
+    /// Copy into an owned `Value`.
+    pub fn to_value(&self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Bool(b) => Value::Bool(*b),
+            ValueRef::Number(v) => Value::Number(*v),
+            ValueRef::String(v) => Value::String(v.to_string()),
+            ValueRef::Array(v) => Value::Array(v.iter().map(ValueRef::to_value).collect()),
+            ValueRef::Object(v) => {
+                let mut m = Map::new();
+                for (k, x) in v {
+                    m.insert(k.to_string(), x.to_value());
+                }
+                Value::Object(m)
+            }
+        }
+    }
+}
+
+impl<'a> From<ValueRef<'a>> for Value {
+    fn from(v: ValueRef<'a>) -> Value {
+        v.to_value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    // An unescaped copy can't point into the input.
+    fn borrowed_from(s: &str, input: &str) -> bool {
+        input.as_bytes().as_ptr_range().contains(&s.as_ptr())
+    }
+
+    #[test]
+    fn test_parse_ref() {
+        let src = String::from(
+            "{\"name\":\"zxh\",\"esc\":\"a\\nb\",\"list\":[1,\"x\",null,{}],\"k\\u0041\":true}",
+        );
+        let v = Reader::new(&src).parse_ref().unwrap();
+
+        match v.get("name") {
+            Some(ValueRef::String(v)) => {
+                assert_eq!("zxh", v);
+                assert!(borrowed_from(v, &src));
+            }
+            x => panic!("{:?}", x),
+        }
+        match v.get("esc") {
+            Some(ValueRef::String(v)) => {
+                assert_eq!("a\nb", v);
+                assert!(matches!(v, Cow::Owned(_)));
+            }
+            x => panic!("{:?}", x),
+        }
+        match &v {
+            ValueRef::Object(v) => {
+                assert!(borrowed_from(&v[0].0, &src));
+                assert_eq!("kA", v[3].0);
+                assert!(!borrowed_from(&v[3].0, &src));
+            }
+            x => panic!("{:?}", x),
+        }
+
+        assert_eq!(Reader::new(&src).parse().unwrap(), v.to_value());
+        assert_eq!(Reader::new(&src).parse().unwrap(), Value::from(v));
+    }
+
+    #[test]
+    fn test_parse_ref_error() {
+        for bad in &[
+            "[1,]",
+            "{\"a\" 1}",
+            "[\"x",
+            "\"\\q\"",
+            "[1] 2",
+            "{\"a\":1,}",
+        ] {
+            let e = Reader::new(bad).parse_ref().unwrap_err();
+            assert_eq!(
+                Reader::new(bad).parse().unwrap_err().desc,
+                e.desc,
+                "{}",
+                bad
+            );
+        }
+        assert!(Reader::new(&"[".repeat(1000)).parse_ref().is_err());
+    }
+}
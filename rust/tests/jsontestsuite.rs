@@ -0,0 +1,70 @@
+//! Conformance against the `test_parsing` files of JSONTestSuite
+//! (https://github.com/nst/JSONTestSuite), vendored in tests/JSONTestSuite.
+//!
+//! `y_` files must parse and `n_` files must fail with default options.
+//! For the implementation-defined `i_` files the expected outcome is the
+//! decision recorded in `I_ACCEPTED`.
+
+use std::fs;
+use std::path::Path;
+use std::str;
+
+use jsonrs::Reader;
+
+// `i_` files the default reader accepts, every other one is rejected:
+// - numbers too large for `f64` fail, see `ParseOptions::on_number_overflow`;
+//   ones that merely lose precision or underflow to zero parse
+// - lone or inverted surrogate escapes fail, a `String` can't hold them
+// - input must be UTF-8 without a byte order mark
+// - nesting up to `ParseOptions::max_depth` (512) parses
+const I_ACCEPTED: &[&str] = &[
+    "i_number_double_huge_neg_exp",
+    "i_number_real_underflow",
+    "i_number_too_big_neg_int",
+    "i_number_too_big_pos_int",
+    "i_number_very_big_negative_int",
+    "i_structure_500_nested_arrays",
+];
+
+fn accepts(bytes: &[u8]) -> bool {
+    match str::from_utf8(bytes) {
+        Ok(s) => {
+            let ok = Reader::new(s).parse().is_ok();
+            assert_eq!(
+                ok,
+                Reader::new(s).parse_ref().is_ok(),
+                "parse_ref on {:?}",
+                s
+            );
+            ok
+        }
+        Err(_) => false,
+    }
+}
+
+#[test]
+fn test_parsing() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/JSONTestSuite/test_parsing");
+    let mut names: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|n| n.ends_with(".json"))
+        .collect();
+    names.sort();
+    assert!(!names.is_empty());
+
+    let mut wrong = Vec::new();
+    for name in &names {
+        let stem = &name[..name.len() - ".json".len()];
+        let expect = match &stem[..2] {
+            "y_" => true,
+            "n_" => false,
+            "i_" => I_ACCEPTED.contains(&stem),
+            _ => continue,
+        };
+        if accepts(&fs::read(dir.join(name)).unwrap()) != expect {
+            wrong.push(stem);
+        }
+    }
+    assert!(wrong.is_empty(), "{} wrong: {:#?}", wrong.len(), wrong);
+}